@@ -0,0 +1,41 @@
+//! 比较较短的路径（栈上转换）与较长的路径（需分配内存）调用 access 的耗时
+//!
+//! cargo run --release --example c_path [调用次数]
+use std::{
+    env,
+    fs,
+    path::Path,
+    time::{Duration, Instant},
+};
+
+use path_permission::PathPermission;
+
+fn time_access(path: &Path, calls: usize) -> Duration {
+    let start = Instant::now();
+    for _ in 0..calls {
+        assert!(path.is_readable().unwrap());
+    }
+    start.elapsed()
+}
+
+fn main() {
+    let calls = env::args().nth(1).map(|arg| arg.parse::<usize>().unwrap()).unwrap_or(200_000);
+
+    let root = env::temp_dir().join(format!("pp-c-path-{}", std::process::id()));
+    let short = root.join("short");
+    // 超出 256 个字节，转换时需在堆上分配
+    let long = (0..6).fold(root.clone(), |path, i| path.join(format!("{:0>60}", i)));
+    fs::create_dir_all(&short).unwrap();
+    fs::create_dir_all(&long).unwrap();
+
+    // 预热
+    time_access(&short, calls / 10);
+
+    let short_time = time_access(&short, calls);
+    let long_time = time_access(&long, calls);
+    println!("{} calls", calls);
+    println!("short path ({:>3} bytes): {:?}", short.as_os_str().len(), short_time);
+    println!("long path  ({:>3} bytes): {:?}", long.as_os_str().len(), long_time);
+
+    fs::remove_dir_all(&root).unwrap();
+}
@@ -0,0 +1,44 @@
+//! 比较 audit_tree 与 par_audit_tree 的耗时
+//!
+//! cargo run --release --features rayon --example parallel_audit [目录数] [每个目录的文件数]
+use std::{
+    env,
+    fs,
+    time::Instant,
+};
+
+use path_permission::PathPermission;
+
+fn main() {
+    let mut args = env::args().skip(1).map(|arg| arg.parse::<usize>().unwrap());
+    let dirs = args.next().unwrap_or(200);
+    let files = args.next().unwrap_or(100);
+
+    let root = env::temp_dir().join(format!("pp-bench-{}", std::process::id()));
+    for d in 0..dirs {
+        let dir = root.join(format!("d{:04}", d));
+        fs::create_dir_all(&dir).unwrap();
+        for f in 0..files {
+            fs::write(dir.join(format!("f{:04}", f)), b"").unwrap();
+        }
+    }
+
+    // 先遍历一次，避免首次遍历受冷缓存影响
+    root.audit_tree().unwrap();
+
+    let start = Instant::now();
+    let serial = root.audit_tree().unwrap();
+    let serial_time = start.elapsed();
+
+    let start = Instant::now();
+    let parallel = root.par_audit_tree().unwrap();
+    let parallel_time = start.elapsed();
+
+    assert_eq!(serial.len(), parallel.len());
+    assert!(serial.iter().zip(&parallel).all(|(a, b)| a.path == b.path));
+    println!("{} entries", serial.len());
+    println!("audit_tree:     {:?}", serial_time);
+    println!("par_audit_tree: {:?}", parallel_time);
+
+    fs::remove_dir_all(&root).unwrap();
+}
@@ -4,7 +4,8 @@
 在已有一个确定的路径时，可依此库来获取路径文件的权限。
 它为`Path(Buf)`提供了一个名为`PathPermission`的特性（trait），在使用`Path`类型
 时，可以此获取其文件权限，是否可读（r）、可写（w）、可执行（x）。  
-目前只支持 *Unix* 类系统。
+目前只支持 *Unix* 类系统；*WASI* 上仅支持部分只读的查询，详见 `wasi` 模块；
+*Redox* 上仅支持通过 `std::fs` 实现的查询及 chmod，详见 `redox` 模块。
 
 ## 示例
 ```rust
@@ -21,13 +22,13 @@ assert_eq!(path.is_writable().unwrap(), true);
 assert_eq!(path.is_excutable().unwrap(), false);
 assert_eq!(path.is_removable().unwrap(), true);
 
-assert_eq!(path.check_access(0o644).unwrap(), true);
+assert_eq!(path.check_mode(Mode::new(0o644)).unwrap(), true);
 assert_eq!(path.get_access().unwrap(), "0644");
 
-assert_eq!(path.chmod(0o640).unwrap(), true);
+assert_eq!(path.set_mode(Mode::new(0o640)).unwrap(), true);
 // 注意，这里已经格式化为字符串
 assert_eq!(path.get_access().unwrap(), "0640");
-assert_eq!(path.chmod(0o644).unwrap(), true);
+assert_eq!(path.set_mode(Mode::new(0o644)).unwrap(), true);
 
 let new_path = Path::new("a/b/d/e/f");
 
@@ -37,9 +38,9 @@ assert_eq!(new_path.is_creatable().unwrap(), true);
 ## 注意
 在使用时需注意：  
   * 当返回值为Ok(false)时，意为无查看此路径的权限，即可能此路径不存在（从起始
-路径开始，至无访问权限的子级路径，是存在的）。  
+    路径开始，至无访问权限的子级路径，是存在的）。  
   * 如无必须，不太建议使用相对路径，可使用
-[path-calculate](https://crates.io/crates/path-calculate)将路径转换为绝对路径。  
+    [path-calculate](https://crates.io/crates/path-calculate)将路径转换为绝对路径。  
   * get_access() 所返回的权限已被格式化为字符串。  
   
 ## 后续计划
@@ -49,10 +50,31 @@ assert_eq!(new_path.is_creatable().unwrap(), true);
 此项目部分代码，来源自项目[permissions](https://crates.io/crates/permissions)。  
 
 */
-#[cfg(unix)]
+mod mode;
+
+pub use mode::*;
+
+pub mod modes;
+
+#[cfg(feature = "serde")]
+pub mod mode_serde;
+
+#[cfg(all(unix, not(target_os = "redox")))]
 pub mod unix;
 
-#[cfg(unix)]
+#[cfg(all(unix, not(target_os = "redox")))]
 pub use unix::*;
 
+#[cfg(target_os = "redox")]
+pub mod redox;
+
+#[cfg(target_os = "redox")]
+pub use redox::*;
+
+#[cfg(target_os = "wasi")]
+pub mod wasi;
+
+#[cfg(target_os = "wasi")]
+pub use wasi::*;
+
 //TODO Windows
@@ -0,0 +1,801 @@
+//! 与平台无关的权限类型
+use std::{
+    cmp::Ordering,
+    convert::TryFrom,
+    error::Error,
+    fmt,
+    ops::{BitAnd, BitOr, Not, Sub},
+    str::FromStr,
+};
+
+use crate::modes;
+
+#[cfg(unix)]
+use std::{fs, os::unix::fs::PermissionsExt};
+
+/// 文件的权限，包括 S_ISUID、S_ISGID、S_ISVTX（0o7777），不包括文件类型
+/// Display 只输出八进制数，Debug 同时输出八进制及 ls -l 的形式。
+///
+/// ```
+/// # use path_permission::Mode;
+/// assert_eq!(format!("{:?}", Mode::new(0o644)), r#"Mode(0o644 "rw-r--r--")"#);
+/// assert_eq!(format!("{:?}", Mode::new(0o4755)), r#"Mode(0o4755 "rwsr-xr-x")"#);
+/// assert_eq!(Mode::new(0o644).to_string(), "0644");
+/// ```
+#[derive(Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub struct Mode(u16);
+
+impl Mode {
+    pub const OWNER_READ: Mode = Mode(modes::OWNER_R);
+    pub const OWNER_WRITE: Mode = Mode(modes::OWNER_W);
+    pub const OWNER_EXEC: Mode = Mode(modes::OWNER_X);
+    pub const GROUP_READ: Mode = Mode(modes::GROUP_R);
+    pub const GROUP_WRITE: Mode = Mode(modes::GROUP_W);
+    pub const GROUP_EXEC: Mode = Mode(modes::GROUP_X);
+    pub const OTHER_READ: Mode = Mode(modes::OTHER_R);
+    pub const OTHER_WRITE: Mode = Mode(modes::OTHER_W);
+    pub const OTHER_EXEC: Mode = Mode(modes::OTHER_X);
+    /// S_ISUID
+    pub const SETUID: Mode = Mode(modes::SETUID);
+    /// S_ISGID
+    pub const SETGID: Mode = Mode(modes::SETGID);
+    /// S_ISVTX
+    pub const STICKY: Mode = Mode(modes::STICKY);
+
+    /// 超出 0o7777 的位（如文件类型）会被忽略
+    pub const fn new(bits: u16) -> Mode {
+        Mode(bits & modes::ALL_BITS)
+    }
+
+    pub const fn bits(&self) -> u16 {
+        self.0
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.0 == 0
+    }
+
+    /// 是否包含 other 中的全部位
+    ///
+    /// ```
+    /// # use path_permission::Mode;
+    /// let mode = Mode::OWNER_READ | Mode::OWNER_WRITE | Mode::GROUP_READ | Mode::SETGID;
+    /// assert_eq!(mode, Mode::new(0o2640));
+    /// assert!(mode.contains(Mode::OWNER_READ | Mode::GROUP_READ));
+    /// assert!(! mode.contains(Mode::OWNER_READ | Mode::OTHER_READ));
+    /// assert!(mode.intersects(Mode::OWNER_READ | Mode::OTHER_READ));
+    /// assert!(! mode.intersects(Mode::SETUID | Mode::STICKY));
+    ///
+    /// assert_eq!(mode - Mode::SETGID, Mode::new(0o640));
+    /// assert_eq!(mode & Mode::new(0o7000), Mode::SETGID);
+    /// assert_eq!(! Mode::new(0o022), Mode::new(0o7755));
+    /// ```
+    pub fn contains(&self, other: Mode) -> bool {
+        self.0 & other.0 == other.0
+    }
+
+    /// 是否包含 other 中的任一位
+    pub fn intersects(&self, other: Mode) -> bool {
+        self.0 & other.0 != 0
+    }
+
+    /// 转换为 std 的 Permissions，可用于 fs::set_permissions，仅 Unix
+    #[cfg(unix)]
+    pub fn to_fs_permissions(&self) -> fs::Permissions {
+        fs::Permissions::from_mode(self.0 as u32)
+    }
+
+    /// S_ISUID、S_ISGID、S_ISVTX 组成的八进制位，即 0o4755 中的 4
+    ///
+    /// ```
+    /// # use path_permission::Mode;
+    /// let mode = Mode::new(0o4755);
+    /// assert_eq!(mode.special_digit(), 4);
+    /// assert_eq!(mode.owner_digit(), 7);
+    /// assert_eq!(mode.group_digit(), 5);
+    /// assert_eq!(mode.other_digit(), 5);
+    /// ```
+    pub fn special_digit(&self) -> u8 {
+        (self.0 >> 9) as u8 & 0o7
+    }
+
+    /// 所有者的权限，即 0o4755 中的 7
+    pub fn owner_digit(&self) -> u8 {
+        (self.0 >> 6) as u8 & 0o7
+    }
+
+    /// 所属组的权限，即 0o4755 中的第一个 5
+    pub fn group_digit(&self) -> u8 {
+        (self.0 >> 3) as u8 & 0o7
+    }
+
+    /// 其他用户的权限，即 0o4755 中的最后一个 5
+    pub fn other_digit(&self) -> u8 {
+        self.0 as u8 & 0o7
+    }
+
+    /// 所有者的读、写、执行权限
+    pub fn owner(&self) -> PermissionSet {
+        PermissionSet::from_bits(self.owner_digit())
+    }
+
+    /// 所属组的读、写、执行权限
+    pub fn group(&self) -> PermissionSet {
+        PermissionSet::from_bits(self.group_digit())
+    }
+
+    /// 其他用户的读、写、执行权限
+    pub fn other(&self) -> PermissionSet {
+        PermissionSet::from_bits(self.other_digit())
+    }
+
+    /// 以 ls -l 的形式返回，如："rwxr-sr-x"
+    /// 设置了 S_ISUID、S_ISGID 时，对应的执行位显示为 s（无执行权限时为 S），
+    /// S_ISVTX 则显示为 t（或 T）。
+    pub fn symbolic(&self) -> String {
+        let mut s = String::with_capacity(9);
+        // 依次为所有者、所属组、其他用户，及各自对应的特殊位
+        for &(shift, special, set, unset) in &[(6, modes::SETUID, 's', 'S'),
+                                               (3, modes::SETGID, 's', 'S'),
+                                               (0, modes::STICKY, 't', 'T')] {
+            let class = (self.0 >> shift) & 0o7;
+            s.push(if class & 0o4 != 0 { 'r' } else { '-' });
+            s.push(if class & 0o2 != 0 { 'w' } else { '-' });
+            s.push(match (class & 0o1 != 0, self.0 & special != 0) {
+                (true, true) => set,
+                (false, true) => unset,
+                (true, false) => 'x',
+                (false, false) => '-',
+            });
+        }
+        s
+    }
+
+    /// 解析 ls -l 形式的权限，即 symbolic 及 FileMode 的输出，如："rw-r-----"、"-rwsr-xr-x"
+    /// 10 个字符时，首个字符为文件类型（-、d、l、c、b、p、s），会被忽略。
+    /// s、t 表示同时有执行权限及对应的特殊位，S、T 则只有特殊位。
+    /// 解析失败时，ParseError::position 为出错字符的字节位置。
+    ///
+    /// ```
+    /// # use path_permission::{FileMode, Mode};
+    /// assert_eq!(Mode::from_symbolic("rw-r-----").unwrap(), Mode::new(0o640));
+    /// assert_eq!(Mode::from_symbolic("-rwsr-xr-x").unwrap(), Mode::new(0o4755));
+    /// assert_eq!(Mode::from_symbolic("rwSr--r-T").unwrap(), Mode::new(0o5644));
+    ///
+    /// let err = Mode::from_symbolic("rw-r-xw--").unwrap_err();
+    /// assert_eq!(err.position(), Some(6));
+    /// assert_eq!(Mode::from_symbolic("xrw-r--r--").unwrap_err().position(), Some(0));
+    /// assert_eq!(Mode::from_symbolic("rw-").unwrap_err().position(), None);
+    ///
+    /// for bits in 0..0o10000 {
+    ///     let mode = Mode::new(bits);
+    ///     assert_eq!(Mode::from_symbolic(&mode.symbolic()).unwrap(), mode);
+    ///     let file_mode = FileMode::new(0o040000 | bits as u32);
+    ///     assert_eq!(Mode::from_symbolic(&file_mode.to_string()).unwrap(), mode);
+    /// }
+    /// ```
+    pub fn from_symbolic(s: &str) -> Result<Mode, ParseError> {
+        let bytes = s.as_bytes();
+        let offset = match bytes.len() {
+            9 => 0,
+            10 => {
+                if ! b"-dlcbps".contains(&bytes[0]) {
+                    return Err(ParseError::at(s, 0, "unknown file type character"))
+                }
+                1
+            }
+            _ => return Err(ParseError::new(s, "expected 9 or 10 characters")),
+        };
+
+        let mut mode = 0;
+        // 依次为所有者、所属组、其他用户，及各自对应的特殊位
+        for (i, &(shift, special, set, unset)) in [(6, modes::SETUID, b's', b'S'),
+                                                  (3, modes::SETGID, b's', b'S'),
+                                                  (0, modes::STICKY, b't', b'T')].iter().enumerate() {
+            let start = offset + i * 3;
+            let class = &bytes[start..start + 3];
+            for (j, &(c, bit)) in [(b'r', 0o4), (b'w', 0o2)].iter().enumerate() {
+                match class[j] {
+                    b'-' => {}
+                    b if b == c => mode |= bit << shift,
+                    _ => return Err(ParseError::at(s, start + j, "unexpected character")),
+                }
+            }
+            match class[2] {
+                b'-' => {}
+                b'x' => mode |= 0o1 << shift,
+                b if b == set => mode |= (0o1 << shift) | special,
+                b if b == unset => mode |= special,
+                _ => return Err(ParseError::at(s, start + 2, "unexpected character")),
+            }
+        }
+        Ok(Mode::new(mode))
+    }
+
+    /// 同 from_symbolic，即 PathPermission::get_access_symbolic 的逆操作
+    /// 接受 9 个字符（"rwxr-xr-x"）或带文件类型的 10 个字符（"drwxr-xr-x"），
+    /// 以及 s、S、t、T 表示的特殊位。
+    ///
+    /// ```
+    /// # use path_permission::Mode;
+    /// assert_eq!(Mode::from_symbolic_rwx("rwxr-xr-x").unwrap(), Mode::new(0o755));
+    /// assert_eq!(Mode::from_symbolic_rwx("drwxrwsr-t").unwrap(), Mode::new(0o3775));
+    /// assert_eq!(Mode::from_symbolic_rwx("rwSr-Sr-T").unwrap(), Mode::new(0o7644));
+    /// assert!(Mode::from_symbolic_rwx("rwxr-xr-").is_err());
+    /// ```
+    pub fn from_symbolic_rwx(s: &str) -> Result<Mode, ParseError> {
+        Mode::from_symbolic(s)
+    }
+
+    /// 解析八进制形式的权限，如："0644"、"755"，最多四位
+    pub fn from_octal(s: &str) -> Result<Mode, ParseError> {
+        if s.is_empty() || s.len() > 4 {
+            return Err(ParseError::new(s, "expected 1 to 4 octal digits"))
+        }
+        u16::from_str_radix(s, 8)
+            .map(Mode::new)
+            .map_err(|_| ParseError::new(s, "expected 1 to 4 octal digits"))
+    }
+
+    /// 按 chmod(1) 的符号形式变更权限，返回变更后的权限
+    ///
+    /// 语法为以 ',' 分隔的若干子句，每个子句为 `[ugoa]*([-+=]([rwxXst]*|[ugo]))+`：
+    ///   * u、g、o、a 分别为所有者、所属组、其他用户及全部，省略时同 a，但不影响
+    ///     umask 中的位；
+    ///   * X 仅当路径为目录（is_dir），或已有任一执行位时，才表示执行权限；
+    ///   * s 为 S_ISUID（u）及 S_ISGID（g），t 为 S_ISVTX（o 或 a）；
+    ///   * 操作符之后为 u、g、o 时，表示复制对应分类的当前权限。
+    ///
+    /// ```
+    /// # use path_permission::Mode;
+    /// let mode = Mode::new(0o644);
+    /// assert_eq!(mode.apply_symbolic("u+x,go-r", false, 0o022).unwrap(), Mode::new(0o700));
+    /// assert_eq!(mode.apply_symbolic("a=rX", true, 0o022).unwrap(), Mode::new(0o555));
+    /// assert_eq!(mode.apply_symbolic("g=u,o+t", false, 0o022).unwrap(), Mode::new(0o1664));
+    /// // 省略 ugoa 时受 umask 影响
+    /// assert_eq!(mode.apply_symbolic("+w", false, 0o022).unwrap(), Mode::new(0o644));
+    /// assert!(mode.apply_symbolic("u+q", false, 0o022).is_err());
+    /// ```
+    pub fn apply_symbolic(&self, spec: &str, is_dir: bool, umask: u16) -> Result<Mode, ParseError> {
+        let mut mode = self.0;
+        for clause in spec.split(',') {
+            let mut chars = clause.chars().peekable();
+
+            let mut who = 0;
+            while let Some(&c) = chars.peek() {
+                who |= match c {
+                    'u' => modes::SETUID | modes::OWNER_RWX,
+                    'g' => modes::SETGID | modes::GROUP_RWX,
+                    'o' => modes::STICKY | modes::OTHER_RWX,
+                    'a' => modes::ALL_BITS,
+                    _ => break,
+                };
+                chars.next();
+            }
+            // 省略时不影响 umask 中的位
+            let (who, mask) = if who == 0 { (modes::ALL_BITS, umask & modes::ALL_RWX) } else { (who, 0) };
+
+            if chars.peek().is_none() {
+                return Err(ParseError::new(spec, "missing operator in clause"))
+            }
+            while let Some(op) = chars.next() {
+                if op != '+' && op != '-' && op != '=' {
+                    return Err(ParseError::new(spec, "expected '+', '-' or '='"))
+                }
+
+                let mut bits = 0;
+                while let Some(&c) = chars.peek() {
+                    bits |= match c {
+                        'r' => modes::ALL_R,
+                        'w' => modes::ALL_W,
+                        'x' => modes::ALL_X,
+                        'X' if is_dir || mode & modes::ALL_X != 0 => modes::ALL_X,
+                        'X' => 0,
+                        's' => modes::SETUID | modes::SETGID,
+                        't' => modes::STICKY,
+                        // 复制某一分类的当前权限
+                        'u' => ((mode >> 6) & 0o7) * 0o111,
+                        'g' => ((mode >> 3) & 0o7) * 0o111,
+                        'o' => (mode & 0o7) * 0o111,
+                        '+' | '-' | '=' => break,
+                        _ => return Err(ParseError::new(spec, "unexpected permission character")),
+                    };
+                    chars.next();
+                }
+                let bits = bits & who & ! mask;
+
+                match op {
+                    '+' => mode |= bits,
+                    '-' => mode &= ! bits,
+                    _ => mode = (mode & ! who) | bits,
+                }
+            }
+        }
+        Ok(Mode::new(mode))
+    }
+}
+
+impl From<u16> for Mode {
+    fn from(bits: u16) -> Mode {
+        Mode::new(bits)
+    }
+}
+
+impl From<Mode> for u16 {
+    fn from(mode: Mode) -> u16 {
+        mode.0
+    }
+}
+
+/// 取 PermissionsExt::mode 中的 0o7777 部分，仅 Unix
+///
+/// ```
+/// # use std::{convert::TryFrom, fs, os::unix::fs::PermissionsExt};
+/// # use path_permission::Mode;
+/// let permissions = fs::metadata("src").unwrap().permissions();
+/// assert_eq!(Mode::from(permissions.clone()).bits() as u32, permissions.mode() & 0o7777);
+///
+/// let mode = Mode::new(0o2750);
+/// assert_eq!(mode.to_fs_permissions().mode(), 0o2750);
+/// assert_eq!(Mode::from(mode.to_fs_permissions()), mode);
+/// assert_eq!(Mode::try_from(0o2750u32).unwrap(), mode);
+/// ```
+#[cfg(unix)]
+impl From<fs::Permissions> for Mode {
+    fn from(permissions: fs::Permissions) -> Mode {
+        Mode::new((permissions.mode() & u32::from(modes::ALL_BITS)) as u16)
+    }
+}
+
+impl BitOr for Mode {
+    type Output = Mode;
+
+    fn bitor(self, rhs: Mode) -> Mode {
+        Mode(self.0 | rhs.0)
+    }
+}
+
+impl BitAnd for Mode {
+    type Output = Mode;
+
+    fn bitand(self, rhs: Mode) -> Mode {
+        Mode(self.0 & rhs.0)
+    }
+}
+
+/// 只在 0o7777 的范围内取反
+impl Not for Mode {
+    type Output = Mode;
+
+    fn not(self) -> Mode {
+        Mode(! self.0 & modes::ALL_BITS)
+    }
+}
+
+/// 去除 rhs 中的位，即 self & ! rhs
+impl Sub for Mode {
+    type Output = Mode;
+
+    fn sub(self, rhs: Mode) -> Mode {
+        Mode(self.0 & ! rhs.0)
+    }
+}
+
+/// 超出 0o7777 时返回错误，而不是像 Mode::new 那样忽略
+impl TryFrom<u32> for Mode {
+    type Error = ParseError;
+
+    fn try_from(bits: u32) -> Result<Mode, ParseError> {
+        if bits > u32::from(modes::ALL_BITS) {
+            return Err(ParseError::new(&format!("{:#o}", bits), "octal mode out of range"))
+        }
+        Ok(Mode::new(bits as u16))
+    }
+}
+
+impl fmt::Debug for Mode {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Mode({:#o} {:?})", self.0, self.symbolic())
+    }
+}
+
+/// 以四位八进制数输出，如："0644"
+impl fmt::Display for Mode {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{:04o}", self.0)
+    }
+}
+
+/// 解析八进制（如 "0640"、"0o640"）或 ls -l 形式（如 "rw-r-----"）的权限
+/// 全为数字（或以 0o 开头）时按八进制解析，否则按 ls -l 形式解析，
+/// 错误信息中的原因对应所选的形式。
+///
+/// ```
+/// # use std::convert::TryFrom;
+/// # use path_permission::Mode;
+/// assert_eq!("0640".parse::<Mode>().unwrap(), Mode::new(0o640));
+/// assert_eq!("0o4755".parse::<Mode>().unwrap(), Mode::new(0o4755));
+/// assert_eq!("-rw-r-----".parse::<Mode>().unwrap(), Mode::new(0o640));
+///
+/// let mode = Mode::new(0o2750);
+/// assert_eq!(mode.to_string().parse::<Mode>().unwrap(), mode);
+/// assert_eq!(mode.symbolic().parse::<Mode>().unwrap(), mode);
+///
+/// assert!("0o".parse::<Mode>().unwrap_err().to_string().contains("octal"));
+/// assert_eq!("rw-r--r-x-".parse::<Mode>().unwrap_err().position(), Some(0));
+/// assert_eq!(Mode::try_from(0o640u32).unwrap(), Mode::new(0o640));
+/// assert!(Mode::try_from(0o100644u32).is_err());
+/// ```
+impl FromStr for Mode {
+    type Err = ParseError;
+
+    fn from_str(s: &str) -> Result<Mode, ParseError> {
+        if let Some(digits) = s.strip_prefix("0o") {
+            return Mode::from_octal(digits).map_err(|_| {
+                ParseError::new(s, "expected 1 to 4 octal digits")
+            })
+        }
+        if is_octal_form(s) {
+            return Mode::from_octal(s)
+        }
+        Mode::from_symbolic(s)
+    }
+}
+
+// FromStr 是否按八进制解析 s
+pub(crate) fn is_octal_form(s: &str) -> bool {
+    s.starts_with("0o") || s.bytes().all(|b| b.is_ascii_digit())
+}
+
+/// 完整的 st_mode，包括文件类型及权限
+/// 以 ls -l 的形式输出，如："drwxr-sr-x"
+///
+/// ```
+/// # use path_permission::FileMode;
+/// for &(mode, expected) in &[(0o100644, "-rw-r--r--"),
+///                            (0o042755, "drwxr-sr-x"),
+///                            (0o041777, "drwxrwxrwt"),
+///                            (0o041776, "drwxrwxrwT"),
+///                            (0o120777, "lrwxrwxrwx"),
+///                            (0o020620, "crw--w----"),
+///                            (0o060660, "brw-rw----"),
+///                            (0o010644, "prw-r--r--"),
+///                            (0o140755, "srwxr-xr-x"),
+///                            (0o104644, "-rwSr--r--")] {
+///     assert_eq!(FileMode::new(mode).to_string(), expected);
+/// }
+/// assert_eq!(FileMode::new(0o755).type_char(), '?');
+/// assert_eq!(format!("{:?}", FileMode::new(0o100644)), r#"FileMode(0o100644 "-rw-r--r--")"#);
+/// ```
+#[derive(Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub struct FileMode(u32);
+
+impl FileMode {
+    pub fn new(st_mode: u32) -> FileMode {
+        FileMode(st_mode)
+    }
+
+    pub fn bits(&self) -> u32 {
+        self.0
+    }
+
+    /// 权限部分
+    pub fn mode(&self) -> Mode {
+        Mode::new(self.0 as u16)
+    }
+
+    /// 文件类型，未知类型（如只有权限部分）时返回 None
+    pub fn kind(&self) -> Option<FileKind> {
+        FileKind::from_mode(self.0)
+    }
+
+    /// ls -l 中表示文件类型的字符：-、d、l、c、b、p、s，未知类型为 ?
+    pub fn type_char(&self) -> char {
+        self.kind().map_or('?', |kind| kind.type_char())
+    }
+
+    /// 解析 Display 的输出，如："drwxr-sr-x"，即 Mode::from_symbolic 加上文件类型
+    /// 首个字符为 ? 时没有文件类型，只有权限部分。
+    ///
+    /// ```
+    /// # use path_permission::FileMode;
+    /// assert_eq!(FileMode::from_symbolic("drwxr-sr-x").unwrap(), FileMode::new(0o042755));
+    /// assert_eq!(FileMode::from_symbolic("?rw-r--r--").unwrap(), FileMode::new(0o644));
+    /// assert_eq!(FileMode::from_symbolic("xrw-r--r--").unwrap_err().position(), Some(0));
+    /// assert_eq!(FileMode::from_symbolic("-rw-r-xw--").unwrap_err().position(), Some(7));
+    /// assert!(FileMode::from_symbolic("rw-r--r--").is_err());
+    /// ```
+    pub fn from_symbolic(s: &str) -> Result<FileMode, ParseError> {
+        if s.len() != 10 {
+            return Err(ParseError::new(s, "expected 10 characters"))
+        }
+        let kind = match s.as_bytes()[0] {
+            b'?' => None,
+            c => Some(FileKind::from_type_char(c as char)
+                .ok_or_else(|| ParseError::at(s, 0, "unknown file type character"))?),
+        };
+        let mode = Mode::from_symbolic(&s[1..]).map_err(|err| ParseError {
+            input: s.to_string(),
+            position: err.position.map(|position| position + 1),
+            ..err
+        })?;
+        Ok(FileMode(kind.map_or(0, |kind| kind.bits()) | mode.0 as u32))
+    }
+}
+
+/// 文件类型，即 st_mode 中的 S_IFMT 部分
+///
+/// ```
+/// # use path_permission::{FileKind, FileMode};
+/// assert_eq!(FileKind::from_mode(0o100644), Some(FileKind::Regular));
+/// assert_eq!(FileKind::from_mode(0o140755), Some(FileKind::Socket));
+/// assert_eq!(FileKind::from_mode(0o755), None);
+/// assert_eq!(FileMode::new(0o041777).kind(), Some(FileKind::Directory));
+/// assert_eq!(FileKind::CharDevice.type_char(), 'c');
+/// assert_eq!(FileKind::from_type_char('p'), Some(FileKind::Fifo));
+/// assert_eq!(FileKind::Directory.name(), "dir");
+/// assert_eq!(FileKind::Symlink.bits(), 0o120000);
+/// ```
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum FileKind {
+    Regular,
+    Directory,
+    Symlink,
+    Fifo,
+    Socket,
+    CharDevice,
+    BlockDevice,
+}
+
+impl FileKind {
+    /// 由完整的 st_mode 得到文件类型，未知类型时返回 None
+    pub fn from_mode(st_mode: u32) -> Option<FileKind> {
+        const KINDS: [FileKind; 7] = [
+            FileKind::Regular, FileKind::Directory, FileKind::Symlink, FileKind::Fifo,
+            FileKind::Socket, FileKind::CharDevice, FileKind::BlockDevice,
+        ];
+        // mode_t 在 macOS 上为 u16，S_IF* 在 Redox 上为 c_int
+        #[allow(clippy::unnecessary_cast)]
+        let format = st_mode & libc::S_IFMT as u32;
+        KINDS.iter().copied().find(|kind| kind.bits() == format)
+    }
+
+    /// 对应的 S_IFMT 位，如：FileKind::Directory 为 0o040000
+    #[allow(clippy::unnecessary_cast)]
+    pub fn bits(&self) -> u32 {
+        (match self {
+            FileKind::Regular => libc::S_IFREG,
+            FileKind::Directory => libc::S_IFDIR,
+            FileKind::Symlink => libc::S_IFLNK,
+            FileKind::Fifo => libc::S_IFIFO,
+            FileKind::Socket => libc::S_IFSOCK,
+            FileKind::CharDevice => libc::S_IFCHR,
+            FileKind::BlockDevice => libc::S_IFBLK,
+        }) as u32
+    }
+
+    /// 由 ls -l 中表示文件类型的字符得到文件类型，即 type_char 的逆操作
+    pub fn from_type_char(c: char) -> Option<FileKind> {
+        match c {
+            '-' => Some(FileKind::Regular),
+            'd' => Some(FileKind::Directory),
+            'l' => Some(FileKind::Symlink),
+            'p' => Some(FileKind::Fifo),
+            's' => Some(FileKind::Socket),
+            'c' => Some(FileKind::CharDevice),
+            'b' => Some(FileKind::BlockDevice),
+            _ => None,
+        }
+    }
+
+    /// 简短的名称："file"、"dir"、"symlink"、"fifo"、"socket"、"char"、"block"
+    /// 与 export_manifest 中的 type 字段相同。
+    pub fn name(&self) -> &'static str {
+        match self {
+            FileKind::Regular => "file",
+            FileKind::Directory => "dir",
+            FileKind::Symlink => "symlink",
+            FileKind::Fifo => "fifo",
+            FileKind::Socket => "socket",
+            FileKind::CharDevice => "char",
+            FileKind::BlockDevice => "block",
+        }
+    }
+
+    /// ls -l 中表示文件类型的字符
+    pub fn type_char(&self) -> char {
+        match self {
+            FileKind::Regular => '-',
+            FileKind::Directory => 'd',
+            FileKind::Symlink => 'l',
+            FileKind::Fifo => 'p',
+            FileKind::Socket => 's',
+            FileKind::CharDevice => 'c',
+            FileKind::BlockDevice => 'b',
+        }
+    }
+}
+
+impl fmt::Debug for FileMode {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "FileMode({:#o} {:?})", self.0, self.to_string())
+    }
+}
+
+impl fmt::Display for FileMode {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}{}", self.type_char(), self.mode().symbolic())
+    }
+}
+
+/// 两个权限之间的差异
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ModeDiff {
+    /// 对方有而自身没有的位
+    pub added: Mode,
+    /// 自身有而对方没有的位
+    pub removed: Mode,
+}
+
+impl ModeDiff {
+    /// from 变为 to 所需增加及去除的位
+    pub fn between(from: Mode, to: Mode) -> ModeDiff {
+        ModeDiff {
+            added: Mode(to.0 & ! from.0),
+            removed: Mode(from.0 & ! to.0),
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.added.is_empty() && self.removed.is_empty()
+    }
+}
+
+/// 以符号形式输出，如："added --x--x--x, removed ----w----"
+impl fmt::Display for ModeDiff {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "added {}, removed {}", self.added.symbolic(), self.removed.symbolic())
+    }
+}
+
+/// 某一分类（所有者、所属组或其他用户）的读、写、执行权限
+///
+/// 比较（`>=` 等）按子集关系：`rwx >= r--`，但 `rw-` 与 `r-x` 互不包含，
+/// 此时 `>=`、`<=` 均为 false。不习惯偏序时，可使用 contains。
+///
+/// ```
+/// # use path_permission::{Mode, PermissionSet};
+/// let r: PermissionSet = "r--".parse().unwrap();
+/// let rw: PermissionSet = "rw-".parse().unwrap();
+/// let rx: PermissionSet = "r-x".parse().unwrap();
+///
+/// assert!(Mode::new(0o755).other() >= r);
+/// assert!("rwx".parse::<PermissionSet>().unwrap() > rw);
+/// // 互不包含
+/// assert!(! (rw >= rx) && ! (rw <= rx));
+/// assert_eq!(rw.partial_cmp(&rx), None);
+/// assert!(rw.contains(r));
+///
+/// assert_eq!(rx.to_string(), "r-x");
+/// assert!("rwz".parse::<PermissionSet>().is_err());
+/// ```
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub struct PermissionSet {
+    pub read: bool,
+    pub write: bool,
+    pub exec: bool,
+}
+
+impl PermissionSet {
+    /// 由一位八进制数构造，如：5 即 r-x，超出 0o7 的位会被忽略
+    pub fn from_bits(bits: u8) -> PermissionSet {
+        PermissionSet {
+            read: bits & 0o4 != 0,
+            write: bits & 0o2 != 0,
+            exec: bits & 0o1 != 0,
+        }
+    }
+
+    /// 对应的一位八进制数
+    pub fn bits(&self) -> u8 {
+        (self.read as u8) << 2 | (self.write as u8) << 1 | self.exec as u8
+    }
+
+    /// 是否包含 other 中的全部权限
+    pub fn contains(&self, other: PermissionSet) -> bool {
+        self.bits() & other.bits() == other.bits()
+    }
+}
+
+/// 按子集关系比较，互不包含时返回 None
+impl PartialOrd for PermissionSet {
+    fn partial_cmp(&self, other: &PermissionSet) -> Option<Ordering> {
+        match (self.contains(*other), other.contains(*self)) {
+            (true, true) => Some(Ordering::Equal),
+            (true, false) => Some(Ordering::Greater),
+            (false, true) => Some(Ordering::Less),
+            (false, false) => None,
+        }
+    }
+}
+
+/// 以 ls -l 的形式输出，如："r-x"
+impl fmt::Display for PermissionSet {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}{}{}",
+               if self.read { 'r' } else { '-' },
+               if self.write { 'w' } else { '-' },
+               if self.exec { 'x' } else { '-' })
+    }
+}
+
+/// 解析 "r-x" 形式的字符串，需恰为三个字符
+impl FromStr for PermissionSet {
+    type Err = ParseError;
+
+    fn from_str(s: &str) -> Result<PermissionSet, ParseError> {
+        let chars = s.chars().collect::<Vec<_>>();
+        if chars.len() != 3 {
+            return Err(ParseError::new(s, "expected 3 characters like \"r-x\""))
+        }
+        let flag = |c: char, set: char| match c {
+            c if c == set => Ok(true),
+            '-' => Ok(false),
+            _ => Err(ParseError::new(s, "unexpected character")),
+        };
+        Ok(PermissionSet {
+            read: flag(chars[0], 'r')?,
+            write: flag(chars[1], 'w')?,
+            exec: flag(chars[2], 'x')?,
+        })
+    }
+}
+
+/// 解析权限字符串失败
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ParseError {
+    input: String,
+    position: Option<usize>,
+    reason: &'static str,
+}
+
+impl ParseError {
+    pub(crate) fn new(input: &str, reason: &'static str) -> ParseError {
+        ParseError {
+            input: input.to_string(),
+            position: None,
+            reason,
+        }
+    }
+
+    // position 为出错字符的字节位置
+    pub(crate) fn at(input: &str, position: usize, reason: &'static str) -> ParseError {
+        ParseError {
+            position: Some(position),
+            ..ParseError::new(input, reason)
+        }
+    }
+
+    /// 无法解析的字符串
+    pub fn input(&self) -> &str {
+        &self.input
+    }
+
+    /// 出错字符在 input 中的字节位置，无法确定到某个字符时（如长度不符）为 None
+    pub fn position(&self) -> Option<usize> {
+        self.position
+    }
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "invalid permission string {:?}: {}", self.input, self.reason)?;
+        if let Some(position) = self.position {
+            write!(f, " at byte {}", position)?;
+        }
+        Ok(())
+    }
+}
+
+impl Error for ParseError {}
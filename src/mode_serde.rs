@@ -0,0 +1,245 @@
+//! Mode 等权限类型的 Serialize、Deserialize，需启用 serde 特性
+//!
+//!   * `Mode`：序列化为四位八进制的字符串，如 `"0640"`；使用
+//!     `#[serde(with = "path_permission::mode_serde::symbolic")]` 时为 ls -l 的形式，
+//!     如 `"rw-r-----"`。反序列化时两者均可，也接受 `"0o640"` 及整数（如 TOML 的
+//!     `0o640`、JSON 的 `416`）。
+//!   * `PermissionSet`：`"r-x"` 形式的字符串，反序列化时也接受 0 至 7 的整数。
+//!   * `FileMode`：ls -l 的形式，如 `"drwxr-xr-x"`，反序列化时也接受完整的 st_mode 整数。
+//!   * `FileKind`：`FileKind::name` 返回的名称，如 `"dir"`。
+//!   * `ModeDiff`：包含 `added`、`removed` 两个 Mode 的结构。
+//!   * `PermSnapshot`、`PermissionSnapshot`（仅 Unix）：见 PermissionSnapshot 的说明。
+//!
+//! 反序列化失败时，错误信息会说明按哪种形式（八进制、ls -l 形式或整数）解析及其原因。
+//!
+//! ```
+//! # use serde::{Deserialize, Serialize};
+//! # use path_permission::{FileKind, FileMode, Mode, PermissionSet};
+//! #[derive(Debug, PartialEq, Serialize, Deserialize)]
+//! struct Expected {
+//!     mode: Mode,
+//!     #[serde(with = "path_permission::mode_serde::symbolic")]
+//!     symbolic: Mode,
+//!     other: PermissionSet,
+//!     file: FileMode,
+//!     kind: FileKind,
+//! }
+//!
+//! let expected = Expected {
+//!     mode: Mode::new(0o2750),
+//!     symbolic: Mode::new(0o640),
+//!     other: PermissionSet::from_bits(5),
+//!     file: FileMode::new(0o041777),
+//!     kind: FileKind::Directory,
+//! };
+//!
+//! let json = serde_json::to_string(&expected).unwrap();
+//! assert_eq!(json, r#"{"mode":"2750","symbolic":"rw-r-----","other":"r-x","file":"drwxrwxrwt","kind":"dir"}"#);
+//! assert_eq!(serde_json::from_str::<Expected>(&json).unwrap(), expected);
+//!
+//! let text = toml::to_string(&expected).unwrap();
+//! assert!(text.contains("mode = \"2750\"\n"));
+//! assert_eq!(toml::from_str::<Expected>(&text).unwrap(), expected);
+//!
+//! // 整数及另一种字符串形式
+//! let config: Expected = toml::from_str(r#"
+//!     mode = 0o2750
+//!     symbolic = "0640"
+//!     other = 5
+//!     file = 17407
+//!     kind = "dir"
+//! "#).unwrap();
+//! assert_eq!(config, expected);
+//! assert_eq!(serde_json::from_str::<Mode>("416").unwrap(), Mode::new(0o640));
+//! assert_eq!(serde_json::from_str::<Mode>(r#""0o640""#).unwrap(), Mode::new(0o640));
+//!
+//! let err = serde_json::from_str::<Mode>(r#""0899""#).unwrap_err().to_string();
+//! assert!(err.starts_with("invalid octal mode: "), "{}", err);
+//! let err = serde_json::from_str::<Mode>(r#""rw-r-xw--""#).unwrap_err().to_string();
+//! assert!(err.starts_with("invalid symbolic mode: ") && err.contains("at byte 6"), "{}", err);
+//! let err = serde_json::from_str::<Mode>("33188").unwrap_err().to_string();
+//! assert!(err.starts_with("invalid integer mode: 0o100644 is out of range"), "{}", err);
+//! assert!(serde_json::from_str::<FileKind>(r#""directory""#).is_err());
+//! ```
+use std::{convert::TryFrom, fmt};
+
+use serde::{
+    de::{self, Deserializer, Visitor},
+    Deserialize, Serialize, Serializer,
+};
+
+use crate::{mode::is_octal_form, modes, FileKind, FileMode, Mode, PermissionSet};
+
+/// 以 ls -l 的形式序列化 Mode，如 `"rw-r-----"`，用于 `#[serde(with = "...")]`
+/// 反序列化与 Mode 的 Deserialize 相同，接受所有的形式。
+pub mod symbolic {
+    use serde::{Deserialize, Deserializer, Serializer};
+
+    use crate::Mode;
+
+    pub fn serialize<S: Serializer>(mode: &Mode, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(&mode.symbolic())
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Mode, D::Error> {
+        Mode::deserialize(deserializer)
+    }
+}
+
+// 以 Mode 的形式序列化 u16 的权限，用于 PermSnapshot 等以 u16 保存权限的结构
+pub(crate) mod octal_bits {
+    use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+    use crate::Mode;
+
+    pub fn serialize<S: Serializer>(bits: &u16, serializer: S) -> Result<S::Ok, S::Error> {
+        Mode::new(*bits).serialize(serializer)
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<u16, D::Error> {
+        Mode::deserialize(deserializer).map(|mode| mode.bits())
+    }
+}
+
+impl Serialize for Mode {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(self)
+    }
+}
+
+impl<'de> Deserialize<'de> for Mode {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Mode, D::Error> {
+        deserializer.deserialize_any(ModeVisitor)
+    }
+}
+
+struct ModeVisitor;
+
+impl<'de> Visitor<'de> for ModeVisitor {
+    type Value = Mode;
+
+    fn expecting(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("an octal string like \"0640\", a symbolic string like \"rw-r-----\" or an integer")
+    }
+
+    fn visit_str<E: de::Error>(self, s: &str) -> Result<Mode, E> {
+        let form = if is_octal_form(s) { "octal" } else { "symbolic" };
+        s.parse().map_err(|err| E::custom(format_args!("invalid {} mode: {}", form, err)))
+    }
+
+    fn visit_u64<E: de::Error>(self, bits: u64) -> Result<Mode, E> {
+        match u16::try_from(bits) {
+            Ok(bits) if bits <= modes::ALL_BITS => Ok(Mode::new(bits)),
+            _ => Err(E::custom(format_args!(
+                "invalid integer mode: {:#o} is out of range, expected at most 0o7777", bits))),
+        }
+    }
+
+    fn visit_i64<E: de::Error>(self, bits: i64) -> Result<Mode, E> {
+        match u64::try_from(bits) {
+            Ok(bits) => self.visit_u64(bits),
+            Err(_) => Err(E::custom(format_args!("invalid integer mode: {} is negative", bits))),
+        }
+    }
+}
+
+impl Serialize for PermissionSet {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(self)
+    }
+}
+
+impl<'de> Deserialize<'de> for PermissionSet {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<PermissionSet, D::Error> {
+        deserializer.deserialize_any(PermissionSetVisitor)
+    }
+}
+
+struct PermissionSetVisitor;
+
+impl<'de> Visitor<'de> for PermissionSetVisitor {
+    type Value = PermissionSet;
+
+    fn expecting(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("a string like \"r-x\" or an integer from 0 to 7")
+    }
+
+    fn visit_str<E: de::Error>(self, s: &str) -> Result<PermissionSet, E> {
+        s.parse().map_err(|err| E::custom(format_args!("invalid symbolic permission set: {}", err)))
+    }
+
+    fn visit_u64<E: de::Error>(self, bits: u64) -> Result<PermissionSet, E> {
+        if bits > 0o7 {
+            return Err(E::custom(format_args!(
+                "invalid integer permission set: {} is out of range, expected at most 7", bits)))
+        }
+        Ok(PermissionSet::from_bits(bits as u8))
+    }
+
+    fn visit_i64<E: de::Error>(self, bits: i64) -> Result<PermissionSet, E> {
+        match u64::try_from(bits) {
+            Ok(bits) => self.visit_u64(bits),
+            Err(_) => Err(E::custom(format_args!("invalid integer permission set: {} is negative", bits))),
+        }
+    }
+}
+
+impl Serialize for FileMode {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(self)
+    }
+}
+
+impl<'de> Deserialize<'de> for FileMode {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<FileMode, D::Error> {
+        deserializer.deserialize_any(FileModeVisitor)
+    }
+}
+
+struct FileModeVisitor;
+
+impl<'de> Visitor<'de> for FileModeVisitor {
+    type Value = FileMode;
+
+    fn expecting(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("a symbolic string like \"drwxr-xr-x\" or an integer st_mode")
+    }
+
+    fn visit_str<E: de::Error>(self, s: &str) -> Result<FileMode, E> {
+        FileMode::from_symbolic(s)
+            .map_err(|err| E::custom(format_args!("invalid symbolic file mode: {}", err)))
+    }
+
+    fn visit_u64<E: de::Error>(self, bits: u64) -> Result<FileMode, E> {
+        u32::try_from(bits).map(FileMode::new).map_err(|_| {
+            E::custom(format_args!("invalid integer file mode: {:#o} is out of range", bits))
+        })
+    }
+
+    fn visit_i64<E: de::Error>(self, bits: i64) -> Result<FileMode, E> {
+        match u64::try_from(bits) {
+            Ok(bits) => self.visit_u64(bits),
+            Err(_) => Err(E::custom(format_args!("invalid integer file mode: {} is negative", bits))),
+        }
+    }
+}
+
+const KIND_NAMES: &[&str] = &["file", "dir", "symlink", "fifo", "socket", "char", "block"];
+
+impl Serialize for FileKind {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(self.name())
+    }
+}
+
+impl<'de> Deserialize<'de> for FileKind {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<FileKind, D::Error> {
+        let name = String::deserialize(deserializer)?;
+        [FileKind::Regular, FileKind::Directory, FileKind::Symlink, FileKind::Fifo,
+         FileKind::Socket, FileKind::CharDevice, FileKind::BlockDevice]
+            .iter()
+            .copied()
+            .find(|kind| kind.name() == name)
+            .ok_or_else(|| de::Error::unknown_variant(&name, KIND_NAMES))
+    }
+}
@@ -0,0 +1,56 @@
+//! 权限位的具名常量，均为 u16，可用于 const 及模式匹配中
+//!
+//! 与 Mode 的关联常量（如 Mode::STICKY）相同的位，可直接与 get_mode 等返回的 u16 运算。
+//!
+//! ```
+//! # use path_permission::{modes, Mode};
+//! assert_eq!(modes::SETUID, 0o4000);
+//! assert_eq!(modes::SETGID, 0o2000);
+//! assert_eq!(modes::STICKY, 0o1000);
+//! assert_eq!(modes::SPECIAL, 0o7000);
+//! assert_eq!((modes::OWNER_RWX, modes::GROUP_RWX, modes::OTHER_RWX), (0o700, 0o070, 0o007));
+//! assert_eq!((modes::OWNER_R, modes::OWNER_W, modes::OWNER_X), (0o400, 0o200, 0o100));
+//! assert_eq!((modes::GROUP_R, modes::GROUP_W, modes::GROUP_X), (0o040, 0o020, 0o010));
+//! assert_eq!((modes::OTHER_R, modes::OTHER_W, modes::OTHER_X), (0o004, 0o002, 0o001));
+//! assert_eq!((modes::ALL_R, modes::ALL_W, modes::ALL_X, modes::ALL_RWX), (0o444, 0o222, 0o111, 0o777));
+//! assert_eq!(modes::ALL_BITS, 0o7777);
+//!
+//! const PRIVATE: u16 = modes::OWNER_R | modes::OWNER_W;
+//! assert_eq!(Mode::new(PRIVATE), Mode::OWNER_READ | Mode::OWNER_WRITE);
+//! assert_eq!(Mode::new(modes::STICKY), Mode::STICKY);
+//! ```
+
+/// S_ISUID
+pub const SETUID: u16 = 0o4000;
+/// S_ISGID
+pub const SETGID: u16 = 0o2000;
+/// S_ISVTX
+pub const STICKY: u16 = 0o1000;
+/// S_ISUID、S_ISGID 及 S_ISVTX
+pub const SPECIAL: u16 = SETUID | SETGID | STICKY;
+
+pub const OWNER_R: u16 = 0o400;
+pub const OWNER_W: u16 = 0o200;
+pub const OWNER_X: u16 = 0o100;
+pub const OWNER_RWX: u16 = OWNER_R | OWNER_W | OWNER_X;
+
+pub const GROUP_R: u16 = 0o040;
+pub const GROUP_W: u16 = 0o020;
+pub const GROUP_X: u16 = 0o010;
+pub const GROUP_RWX: u16 = GROUP_R | GROUP_W | GROUP_X;
+
+pub const OTHER_R: u16 = 0o004;
+pub const OTHER_W: u16 = 0o002;
+pub const OTHER_X: u16 = 0o001;
+pub const OTHER_RWX: u16 = OTHER_R | OTHER_W | OTHER_X;
+
+/// 所有分类的读权限
+pub const ALL_R: u16 = OWNER_R | GROUP_R | OTHER_R;
+/// 所有分类的写权限
+pub const ALL_W: u16 = OWNER_W | GROUP_W | OTHER_W;
+/// 所有分类的执行权限
+pub const ALL_X: u16 = OWNER_X | GROUP_X | OTHER_X;
+pub const ALL_RWX: u16 = OWNER_RWX | GROUP_RWX | OTHER_RWX;
+
+/// 全部权限位，包括 S_ISUID、S_ISGID、S_ISVTX，不包括文件类型
+pub const ALL_BITS: u16 = SPECIAL | ALL_RWX;
@@ -0,0 +1,134 @@
+//! Redox OS 上的有限支持
+//!
+//! Redox 的 libc（relibc）并未完整提供 Unix 模块所使用的调用（statx、faccessat 的
+//! 各标志、扩展属性等），此处只通过 std::fs 实现，不依赖 libc：
+//!   * 权限位来自 metadata().mode()，与 Unix 上相同，包括 S_ISUID、S_ISGID、S_ISVTX；
+//!   * chmod 通过 std::fs::set_permissions 实现。
+//!
+//! 可用的方法：get_mode、check_access、get_access、is_readonly、chmod。
+//! 以下方法总是返回 io::ErrorKind::Unsupported 错误：
+//! access、is_readable、is_writable、is_excutable。
+use std::{
+    fs::{self, Permissions},
+    io,
+    path::{Path, PathBuf},
+    os::unix::fs::{MetadataExt, PermissionsExt},
+};
+
+use crate::modes;
+
+pub trait PathPermission {
+    /// 不支持，返回 Unsupported 错误
+    fn access(&self, amode: i32) -> io::Result<bool>;
+
+    /// 不支持，返回 Unsupported 错误
+    fn is_readable(&self) -> io::Result<bool>;
+
+    /// 不支持，返回 Unsupported 错误
+    fn is_writable(&self) -> io::Result<bool>;
+
+    /// 不支持，返回 Unsupported 错误
+    fn is_excutable(&self) -> io::Result<bool>;
+
+    /// 判断路径是否为只读，即所有者、所属组、其他用户均无写权限
+    fn is_readonly(&self) -> io::Result<bool>;
+
+    /// 检查文件的权限
+    fn check_access(&self, mode: u16) -> io::Result<bool>;
+
+    /// 返回路径的权限，以 stat 的形式：0o0644
+    /// 注意：已经格式化为字符串！
+    fn get_access(&self) -> io::Result<String>;
+
+    /// 返回路径的权限，包括 S_ISUID、S_ISGID、S_ISVTX（mode & 0o7777）
+    fn get_mode(&self) -> io::Result<u16>;
+
+    /// 变更文件的权限，跟随符号链接
+    /// 成功时返回 Ok(true)，权限不足或路径不存在时返回 Ok(false)，与 Unix 上一致
+    fn chmod(&self, mode: u16) -> io::Result<bool>;
+}
+
+impl PathPermission for Path {
+    fn access(&self, _amode: i32) -> io::Result<bool> {
+        Err(unsupported("access"))
+    }
+
+    fn is_readable(&self) -> io::Result<bool> {
+        Err(unsupported("access"))
+    }
+
+    fn is_writable(&self) -> io::Result<bool> {
+        Err(unsupported("access"))
+    }
+
+    fn is_excutable(&self) -> io::Result<bool> {
+        Err(unsupported("access"))
+    }
+
+    fn is_readonly(&self) -> io::Result<bool> {
+        Ok(self.metadata()?.permissions().readonly())
+    }
+
+    fn check_access(&self, mode: u16) -> io::Result<bool> {
+        Ok(self.get_mode()? & mode == mode)
+    }
+
+    fn get_access(&self) -> io::Result<String> {
+        Ok(format!("{:04o}", self.get_mode()?))
+    }
+
+    fn get_mode(&self) -> io::Result<u16> {
+        Ok((self.metadata()?.mode() & u32::from(modes::ALL_BITS)) as u16)
+    }
+
+    fn chmod(&self, mode: u16) -> io::Result<bool> {
+        match fs::set_permissions(self, Permissions::from_mode(u32::from(mode & modes::ALL_BITS))) {
+            Ok(()) => Ok(true),
+            Err(ref e) if e.kind() == io::ErrorKind::PermissionDenied
+                || e.kind() == io::ErrorKind::NotFound => Ok(false),
+            Err(e) => Err(e),
+        }
+    }
+}
+
+impl PathPermission for PathBuf {
+    fn access(&self, amode: i32) -> io::Result<bool> {
+        self.as_path().access(amode)
+    }
+
+    fn is_readable(&self) -> io::Result<bool> {
+        self.as_path().is_readable()
+    }
+
+    fn is_writable(&self) -> io::Result<bool> {
+        self.as_path().is_writable()
+    }
+
+    fn is_excutable(&self) -> io::Result<bool> {
+        self.as_path().is_excutable()
+    }
+
+    fn is_readonly(&self) -> io::Result<bool> {
+        self.as_path().is_readonly()
+    }
+
+    fn check_access(&self, mode: u16) -> io::Result<bool> {
+        self.as_path().check_access(mode)
+    }
+
+    fn get_access(&self) -> io::Result<String> {
+        self.as_path().get_access()
+    }
+
+    fn get_mode(&self) -> io::Result<u16> {
+        self.as_path().get_mode()
+    }
+
+    fn chmod(&self, mode: u16) -> io::Result<bool> {
+        self.as_path().chmod(mode)
+    }
+}
+
+fn unsupported(call: &str) -> io::Error {
+    io::Error::new(io::ErrorKind::Unsupported, format!("{} is not available on Redox", call))
+}
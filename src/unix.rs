@@ -1,17 +1,218 @@
 use std::{
-    io,
+    ffi::{CString, OsStr},
+    fs::{File, OpenOptions},
+    io::{self, Write},
     path::{Path, PathBuf},
     os::{
         raw::c_int,
-        unix::{
-            ffi::OsStrExt,
-            fs::MetadataExt,
-        }
+        unix::{ffi::OsStrExt, fs::{FileTypeExt, MetadataExt}},
     },
 };
 
+use crate::{modes, FileKind, FileMode, Mode, ModeDiff, ParseError};
+
+#[cfg(target_os = "macos")]
+mod darwin;
+
+#[cfg(target_os = "macos")]
+pub use darwin::BsdFlags;
+
+mod umask;
+
+pub use umask::{current_umask, with_umask};
+
+mod stat;
+mod compute;
+mod rules;
+mod walk;
+mod audit;
+mod tree;
+mod rename;
+mod link;
+mod mount;
+mod create;
+mod guard;
+mod which;
+mod script;
+pub mod testing;
+mod open;
+mod mkdir;
+mod inspect;
+mod options;
+mod adjust;
+mod users;
+mod describe;
+mod diff;
+mod snapshot;
+mod manifest;
+mod entries;
+mod poll;
+mod remedy;
+mod private;
+mod trust;
+mod tempdir;
+mod chroot;
+mod ownership;
+
+pub use rename::RenameCheck;
+
+pub use create::{CreateCheck, can_create_in};
+
+pub use guard::PermGuard;
+
+pub use inspect::PermissionInspector;
+
+pub use describe::PathDescription;
+
+pub use diff::{AccessDiff, diff_tree};
+
+pub use snapshot::{PermissionSnapshot, RestoreReport};
+
+pub use manifest::{ManifestFormat, verify_manifest};
+
+pub use entries::{AccessibleEntries, ReadDirExt, TryAccessibleEntries, readable_entries};
+
+pub use poll::{PermissionEvent, PollEvent, PollWatcher};
+
+pub use remedy::{Operation, Remedy, RemedyStep, UserContext};
+
+pub use options::AccessOptions;
+
+pub use private::PrivateOptions;
+
+pub use trust::{TrustReport, UntrustedPath};
+
+pub use tempdir::{TempDirOptions, select_secure_tempdir};
+
+pub use chroot::AccessContext;
+
+pub use ownership::OwnershipAspect;
+
+pub use which::{find_executable, find_all_executables, find_executable_in, find_all_executables_in};
+
+pub use tree::{clone_permissions_tree, clone_permissions_tree_with_owner, ChmodOptions, ChownReport};
+
+pub use audit::AuditEntry;
+
+pub use compute::{AccessMode, current_supplementary_groups, current_groups, primary_gid, effective_gid,
+                  in_group};
+
+#[cfg(target_os = "linux")]
+pub use compute::{Capabilities, process_capabilities};
+
+#[cfg(target_os = "linux")]
+mod xattr;
+
+#[cfg(target_os = "linux")]
+mod file_caps;
+
+#[cfg(target_os = "linux")]
+pub use file_caps::FileCaps;
+
+#[cfg(target_os = "linux")]
+mod attributes;
+
+#[cfg(target_os = "linux")]
+pub use attributes::FileAttributes;
+
+#[cfg(all(target_os = "linux", feature = "selinux"))]
+mod selinux;
+
+#[cfg(all(target_os = "linux", feature = "selinux"))]
+pub use selinux::is_selinux_enforcing;
+
+#[cfg(all(target_os = "linux", feature = "watch"))]
+mod watch;
+
+#[cfg(all(target_os = "linux", feature = "watch"))]
+pub use watch::{PermissionWatcher, WatchEvent};
+
+/// access(2) 的详细结果，由调用者决定如何归并为 bool
+#[derive(Debug)]
+pub enum AccessResult {
+    /// 有权限
+    Granted,
+    /// 无权限（EACCES）
+    Denied,
+    /// 路径不存在（ENOENT）
+    NotFound,
+    /// 请求写权限，但路径位于只读文件系统上（EROFS）
+    ReadOnlyFs,
+    /// 其它错误
+    Other(io::Error),
+}
+
+impl AccessResult {
+    pub fn is_granted(&self) -> bool {
+        matches!(self, AccessResult::Granted)
+    }
+}
+
+/// 路径的权限及所有者，可通过 restore 还原
+/// 启用 serde 特性时可序列化，mode 与 Mode 相同，为四位八进制的字符串。
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct PermSnapshot {
+    /// 权限，包括 S_ISUID、S_ISGID、S_ISVTX
+    #[cfg_attr(feature = "serde", serde(with = "crate::mode_serde::octal_bits"))]
+    pub mode: u16,
+    pub uid: u32,
+    pub gid: u32,
+}
+
+impl PermSnapshot {
+    pub(crate) fn from_stat(stat: &stat::Stat) -> PermSnapshot {
+        PermSnapshot {
+            mode: stat.perm(),
+            uid: stat.uid,
+            gid: stat.gid,
+        }
+    }
+}
+
+/// 路径的类型、权限、所有者及所属组，由 stat_summary 一次 stat 获取
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct StatSummary {
+    /// 文件类型，未知类型时为 None
+    pub kind: Option<FileKind>,
+    /// 权限，包括 S_ISUID、S_ISGID、S_ISVTX
+    pub mode: Mode,
+    pub uid: u32,
+    pub gid: u32,
+}
+
+impl StatSummary {
+    fn from_stat(stat: &stat::Stat) -> StatSummary {
+        StatSummary {
+            kind: stat.kind(),
+            mode: Mode::new(stat.perm()),
+            uid: stat.uid,
+            gid: stat.gid,
+        }
+    }
+}
+
+/// creatable_with_parents 的结果：创建路径前需要创建哪些目录
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct CreationPlan {
+    /// 需要创建的上级目录，按创建的顺序排列（不包括路径本身）
+    pub missing: Vec<PathBuf>,
+    /// 最近的已存在的上级路径，需对其有写和执行的权限
+    pub ancestor: PathBuf,
+    /// 能否创建
+    pub creatable: bool,
+}
+
 pub trait PathPermission {
     /// 检查对路径的权限，通过1(x)、2(w)、4(r)
+    /// 路径中间含有 '\0' 时返回 InvalidInput 错误，而不会检查被截断后的路径。
+    ///
+    /// ```
+    /// # use std::{io::ErrorKind, path::Path};
+    /// # use path_permission::PathPermission;
+    /// let err = Path::new("/tmp\0/etc/passwd").access(libc::R_OK).unwrap_err();
+    /// assert_eq!(err.kind(), ErrorKind::InvalidInput);
+    /// ```
     fn access(&self, amode: c_int) -> io::Result<bool>;
 
     /// 判断路径是否可读
@@ -21,27 +222,2071 @@ pub trait PathPermission {
     fn is_writable(&self) -> io::Result<bool>;
 
     /// 判断路径是否可执行
+    /// root 需文件至少有一个执行位（目录除外）
+    /// 注意：对目录而言表示能否进入；判断能否作为程序运行，请使用 is_executable_file
+    ///
+    /// ```
+    /// # use std::fs;
+    /// # use path_permission::{Mode, PathPermission};
+    /// # let path = std::env::temp_dir().join(format!("pp-exec-{}", std::process::id()));
+    /// fs::write(&path, b"").unwrap();
+    /// path.set_mode(Mode::new(0o644)).unwrap();
+    /// assert_eq!(path.is_excutable().unwrap(), false);
+    /// path.set_mode(Mode::new(0o744)).unwrap();
+    /// assert_eq!(path.is_excutable().unwrap(), true);
+    ///
+    /// // 仅以 root 运行时检查
+    /// if unsafe { libc::geteuid() } == 0 {
+    ///     std::os::unix::fs::chown(&path, Some(65534), Some(65534)).unwrap();
+    ///     path.set_mode(Mode::new(0o700)).unwrap();
+    ///     assert_eq!(path.is_excutable().unwrap(), true);
+    ///     path.set_mode(Mode::new(0o600)).unwrap();
+    ///     assert_eq!(path.is_excutable().unwrap(), false);
+    /// }
+    /// # fs::remove_file(&path).unwrap();
+    /// ```
     fn is_excutable(&self) -> io::Result<bool>;
 
     /// 判断路径可否被创建（当前无此路径）
+    /// 根目录无法被创建，返回Ok(false)；只有文件名的相对路径以当前目录为父级目录
+    /// 只判断能否在父级目录下创建，不管路径本身是否已存在，见 is_creatable_exclusive
+    /// 最近的已存在的上级路径不是目录时（如普通文件、悬空的符号链接），返回Ok(false)
+    ///
+    /// ```
+    /// # use std::{fs, os::unix::fs::symlink};
+    /// # use path_permission::PathPermission;
+    /// # let dir = std::env::temp_dir().join(format!("pp-creatable-{}", std::process::id()));
+    /// # fs::create_dir_all(&dir).unwrap();
+    /// fs::write(dir.join("file"), b"").unwrap();
+    /// symlink(dir.join("file"), dir.join("link")).unwrap();
+    /// symlink(dir.join("missing"), dir.join("dangling")).unwrap();
+    ///
+    /// assert_eq!(dir.join("a/b/c").is_creatable().unwrap(), true);
+    /// assert_eq!(dir.join("file/new").is_creatable().unwrap(), false);
+    /// assert_eq!(dir.join("file/b/c").is_creatable().unwrap(), false);
+    /// assert_eq!(dir.join("link/new").is_creatable().unwrap(), false);
+    /// assert_eq!(dir.join("dangling/new").is_creatable().unwrap(), false);
+    /// # fs::remove_dir_all(&dir).unwrap();
+    /// ```
     fn is_creatable(&self) -> io::Result<bool>;
 
     /// 判断路径能否被删除
+    /// 根目录无法被删除，返回Ok(false)；只有文件名的相对路径以当前目录为父级目录
+    /// 父级目录设置了 S_ISVTX 时，只有文件所有者、目录所有者或特权进程才能删除
+    /// 与内核一致，全部以有效 uid/gid 判断（包括对父级目录的写和执行权限），适用于 setuid 程序
+    ///
+    /// ```
+    /// # use std::fs;
+    /// # use path_permission::{Mode, PathPermission};
+    /// # let dir = std::env::temp_dir().join(format!("pp-sticky-{}", std::process::id()));
+    /// # fs::create_dir_all(&dir).unwrap();
+    /// dir.set_mode(Mode::new(0o1777)).unwrap();
+    /// let file = dir.join("file");
+    /// fs::write(&file, b"").unwrap();
+    /// assert_eq!(file.is_removable().unwrap(), true);
+    ///
+    /// // 仅以 root 运行时检查：他人所有的文件及目录
+    /// if unsafe { libc::geteuid() } == 0 {
+    ///     std::os::unix::fs::chown(&file, Some(65534), None).unwrap();
+    ///     std::os::unix::fs::chown(&dir, Some(65534), None).unwrap();
+    ///     assert_eq!(file.is_removable().unwrap(), true);
+    /// }
+    /// # fs::remove_dir_all(&dir).unwrap();
+    /// ```
+    ///
+    /// ```
+    /// # use std::{fs, path::Path};
+    /// # use path_permission::PathPermission;
+    /// # let dir = std::env::temp_dir().join(format!("pp-bare-{}", std::process::id()));
+    /// # fs::create_dir_all(&dir).unwrap();
+    /// std::env::set_current_dir(&dir).unwrap();
+    /// fs::write("x", b"").unwrap();
+    ///
+    /// assert_eq!(Path::new("x").is_removable().unwrap(), Path::new("./x").is_removable().unwrap());
+    /// assert_eq!(Path::new("y").is_creatable().unwrap(), Path::new("./y").is_creatable().unwrap());
+    /// assert_eq!(Path::new("/").is_removable().unwrap(), false);
+    /// assert_eq!(Path::new("/").is_creatable().unwrap(), false);
+    /// # fs::remove_dir_all(&dir).unwrap();
+    /// ```
+    ///
+    /// ```
+    /// # use std::fs;
+    /// # use path_permission::{Mode, PathPermission};
+    /// # let dir = std::env::temp_dir().join(format!("pp-removable-euid-{}", std::process::id()));
+    /// # fs::create_dir_all(&dir).unwrap();
+    /// dir.set_mode(Mode::new(0o755)).unwrap();
+    /// let file = dir.join("file");
+    /// fs::write(&file, b"").unwrap();
+    ///
+    /// // 仅以 root 运行时检查：实际 uid 为 root，有效 uid 为他人时，对目录无写的权限
+    /// if unsafe { libc::geteuid() } == 0 && unsafe { libc::getuid() } == 0 {
+    ///     assert_eq!(unsafe { libc::seteuid(65534) }, 0);
+    ///     let removable = file.is_removable();
+    ///     assert_eq!(unsafe { libc::seteuid(0) }, 0);
+    ///     assert_eq!(removable.unwrap(), false);
+    /// }
+    /// assert_eq!(file.is_removable().unwrap(), true);
+    /// # fs::remove_dir_all(&dir).unwrap();
+    /// ```
     fn is_removable(&self) -> io::Result<bool>;
 
+    /// 与 is_removable 相同，但不跟随符号链接：路径为符号链接时，判断的是链接本身
+    /// 在设置了 S_ISVTX 的目录（如 /tmp）中，删除符号链接取决于链接本身的所有者，
+    /// 而非其目标的所有者。悬空的符号链接同样可以删除。
+    ///
+    /// ```
+    /// # use std::{fs, os::unix::fs::{symlink, MetadataExt}, path::Path};
+    /// # use path_permission::PathPermission;
+    /// let tmp = std::env::temp_dir();
+    /// let link = tmp.join(format!("pp-rm-nofollow-{}", std::process::id()));
+    /// // 当前用户所有的符号链接，指向 root 所有的文件
+    /// symlink("/etc/passwd", &link).unwrap();
+    /// assert!(link.is_removable_nofollow().unwrap());
+    ///
+    /// let root_owned = |p: &Path| p.metadata().map(|m| m.uid() == 0).unwrap_or(false);
+    /// if unsafe { libc::geteuid() } != 0 && tmp.get_mode().unwrap() & 0o1000 != 0
+    ///     && root_owned(&tmp) && root_owned(Path::new("/etc/passwd")) {
+    ///     // 跟随符号链接时，以目标的所有者（root）判断
+    ///     assert!(! link.is_removable().unwrap());
+    /// }
+    /// # fs::remove_file(&link).unwrap();
+    ///
+    /// let dangling = tmp.join(format!("pp-rm-dangling-{}", std::process::id()));
+    /// symlink(tmp.join("pp-missing"), &dangling).unwrap();
+    /// assert!(! dangling.is_removable().unwrap());
+    /// assert!(dangling.is_removable_nofollow().unwrap());
+    /// # fs::remove_file(&dangling).unwrap();
+    /// ```
+    fn is_removable_nofollow(&self) -> io::Result<bool>;
+
     /// 检查文件的权限
     /// mode 可习惯上使用8进制数字，如：0o0644
     /// The file type and mode: The stat.st_mode contains the file type and mode.
     /// 帮助手册[inode(7)](https://man7.org/linux/man-pages/man7/inode.7.html)
+    /// 已弃用：请使用 check_mode。超出 0o7777 的位（如 S_IFDIR）会被忽略，
+    /// 不再与 st_mode 中的文件类型比较。
+    #[deprecated(since = "0.1.3", note = "use check_mode, which takes a Mode")]
     fn check_access(&self, mode: u16) -> io::Result<bool>;
 
+    /// 判断路径的权限是否包含 mode 中的全部位，跟随符号链接
+    /// 只比较权限（0o7777），st_mode 中的文件类型不会参与比较。
+    ///
+    /// ```
+    /// # use std::fs;
+    /// # use path_permission::{Mode, PathPermission};
+    /// # let path = std::env::temp_dir().join(format!("pp-check-mode-{}", std::process::id()));
+    /// fs::write(&path, b"").unwrap();
+    /// path.set_mode(Mode::OWNER_READ | Mode::OWNER_WRITE | Mode::GROUP_READ).unwrap();
+    /// assert_eq!(path.get_mode().unwrap(), 0o640);
+    /// assert!(path.check_mode(Mode::OWNER_READ | Mode::GROUP_READ).unwrap());
+    /// assert!(! path.check_mode(Mode::OTHER_READ).unwrap());
+    /// # fs::remove_file(&path).unwrap();
+    ///
+    /// // 目录的 S_IFDIR（0o040000）不会混入权限
+    /// let dir = std::env::temp_dir();
+    /// assert!(dir.get_mode().unwrap() <= 0o7777);
+    /// assert_eq!(Mode::new(0o040755), Mode::new(0o755));
+    /// #[allow(deprecated)]
+    /// {
+    ///     assert_eq!(dir.check_access(0o040000).unwrap(), true);
+    ///     assert_eq!(dir.check_access(0o040000 | dir.get_mode().unwrap()).unwrap(),
+    ///                dir.check_mode(Mode::new(dir.get_mode().unwrap())).unwrap());
+    /// }
+    /// ```
+    fn check_mode(&self, mode: Mode) -> io::Result<bool>;
+
     /// 返回路径的权限，以 stat 的形式：0o0644
-    /// 注意：已经格式化为字符串！
+    /// 注意：已经格式化为字符串！总是四位八进制数，包括 S_ISUID、S_ISGID、S_ISVTX。
+    ///
+    /// ```
+    /// # use std::fs;
+    /// # use path_permission::{Mode, PathPermission};
+    /// # let path = std::env::temp_dir().join(format!("pp-get-access-{}", std::process::id()));
+    /// fs::write(&path, b"").unwrap();
+    /// for &(mode, expected) in &[(0o644, "0644"), (0o4755, "4755"), (0o4, "0004"), (0o0, "0000")] {
+    ///     path.set_mode(Mode::new(mode)).unwrap();
+    ///     assert_eq!(path.get_access().unwrap(), expected);
+    /// }
+    /// # fs::remove_file(&path).unwrap();
+    /// ```
     fn get_access(&self) -> io::Result<String>;
 
+    /// 以 ls -l 的形式返回路径的权限，如："rwxr-sr-x"，跟随符号链接
+    /// 不含文件类型，可通过 Mode::from_symbolic_rwx 解析回权限。
+    ///
+    /// ```
+    /// # use std::fs;
+    /// # use path_permission::{Mode, PathPermission};
+    /// # let path = std::env::temp_dir().join(format!("pp-access-symbolic-{}", std::process::id()));
+    /// fs::write(&path, b"").unwrap();
+    /// for special in 0..8 {
+    ///     for &perm in &[0o000, 0o644, 0o755, 0o777] {
+    ///         let mode = Mode::new(special << 9 | perm);
+    ///         path.set_mode(mode).unwrap();
+    ///         let symbolic = path.get_access_symbolic().unwrap();
+    ///         assert_eq!(Mode::from_symbolic_rwx(&symbolic).unwrap(), mode);
+    ///     }
+    /// }
+    /// path.set_mode(Mode::new(0o4754)).unwrap();
+    /// assert_eq!(path.get_access_symbolic().unwrap(), "rwsr-xr--");
+    /// # fs::remove_file(&path).unwrap();
+    /// ```
+    fn get_access_symbolic(&self) -> io::Result<String>;
+
     /// 变更文件的权限
     /// mode 可习惯上使用8进制数字，如：0o0644
+    /// 只有权限位及 S_ISUID、S_ISGID、S_ISVTX（0o7777）有效，文件类型等更高的位会被忽略
+    /// 已弃用：请使用 set_mode。
+    #[deprecated(since = "0.1.3", note = "use set_mode, which takes a Mode")]
     fn chmod(&self, mode: u16) -> io::Result<bool>;
+
+    /// 变更文件的权限，跟随符号链接
+    /// Mode 只包含权限（0o7777），由 metadata.mode() 转换时文件类型已被明确去除。
+    /// 返回权限是否有变化：与当前的权限相同时不调用 chmod(2)，返回 Ok(false)。
+    ///
+    /// ```
+    /// # use std::{fs, os::unix::fs::MetadataExt};
+    /// # use path_permission::{Mode, PathPermission};
+    /// # let path = std::env::temp_dir().join(format!("pp-set-mode-{}", std::process::id()));
+    /// fs::write(&path, b"").unwrap();
+    /// // metadata.mode() 中包含文件类型（S_IFREG）
+    /// assert_eq!(path.metadata().unwrap().mode() & 0o170000, 0o100000);
+    /// assert!(path.set_mode(Mode::new(0o100640)).unwrap());
+    /// assert_eq!(path.get_mode().unwrap(), 0o640);
+    /// assert!(! path.set_mode(Mode::new(0o640)).unwrap());
+    /// # fs::remove_file(&path).unwrap();
+    /// ```
+    fn set_mode(&self, mode: Mode) -> io::Result<bool>;
+
+    /// 变更文件的权限，但保留其已有的 S_ISUID、S_ISGID、S_ISVTX（0o7000）
+    /// mode 中的特殊位同样会被设置；需要清除特殊位时，请使用 set_mode 精确地设置权限。
+    ///
+    /// ```
+    /// # use std::fs;
+    /// # use path_permission::{Mode, PathPermission};
+    /// # let dir = std::env::temp_dir().join(format!("pp-preserve-special-{}", std::process::id()));
+    /// # fs::create_dir_all(&dir).unwrap();
+    /// dir.set_mode(Mode::new(0o2775)).unwrap();
+    /// assert!(dir.chmod_preserving_special(0o755).unwrap());
+    /// assert_eq!(dir.get_mode().unwrap(), 0o2755);
+    ///
+    /// assert!(dir.set_mode(Mode::new(0o755)).unwrap());
+    /// assert_eq!(dir.get_mode().unwrap(), 0o755);
+    /// # fs::remove_dir_all(&dir).unwrap();
+    /// ```
+    fn chmod_preserving_special(&self, mode: u16) -> io::Result<bool>;
+
+    /// 通过 fs::set_permissions 变更权限，跟随符号链接
+    /// 与 set_mode 不同，总是调用 chmod(2)，即使权限没有变化。
+    ///
+    /// ```
+    /// # use std::{fs, io::ErrorKind};
+    /// # use path_permission::{Mode, PathPermission};
+    /// # let path = std::env::temp_dir().join(format!("pp-via-std-{}", std::process::id()));
+    /// fs::write(&path, b"").unwrap();
+    /// path.set_permissions_via_std(Mode::new(0o600)).unwrap();
+    /// assert_eq!(Mode::from(fs::metadata(&path).unwrap().permissions()), Mode::new(0o600));
+    /// # fs::remove_file(&path).unwrap();
+    /// let err = path.set_permissions_via_std(Mode::new(0o600)).unwrap_err();
+    /// assert_eq!(err.kind(), ErrorKind::NotFound);
+    /// ```
+    fn set_permissions_via_std(&self, mode: Mode) -> io::Result<()>;
+
+    /// 判断路径能否被重命名（移动）为 to
+    /// 重命名相当于从源目录中删除，再于目标目录中创建，需对两者的父级目录
+    /// 都有写和执行的权限；目标已存在时，还需满足将其删除的条件。
+    /// 具体的原因见 is_renamable_to
+    ///
+    /// ```
+    /// # use std::{fs, path::Path};
+    /// # use std::os::unix::fs::PermissionsExt;
+    /// # use path_permission::PathPermission;
+    /// # let dir = std::env::temp_dir().join(format!("pp-rename-{}", std::process::id()));
+    /// # fs::create_dir_all(&dir).unwrap();
+    /// // 设置了 S_ISVTX 的目录，如 /tmp
+    /// fs::set_permissions(&dir, fs::Permissions::from_mode(0o1777)).unwrap();
+    /// let from = dir.join("from");
+    /// fs::write(&from, b"").unwrap();
+    ///
+    /// assert_eq!(from.can_rename(&dir.join("to")).unwrap(), true);
+    /// assert_eq!(from.can_rename(Path::new("/nonexistent/to")).unwrap(), false);
+    ///
+    /// // 仅以 root 运行时检查：暂时以有效 uid 65534 运行，只能重命名其所有的文件，
+    /// // 而不能重命名 root 所有的文件
+    /// if unsafe { libc::geteuid() } == 0 {
+    ///     let others = dir.join("others");
+    ///     fs::write(&others, b"").unwrap();
+    ///     std::os::unix::fs::chown(&from, Some(65534), None).unwrap();
+    ///     assert_eq!(unsafe { libc::seteuid(65534) }, 0);
+    ///     let (own, not_own) = (from.can_rename(&dir.join("to")), others.can_rename(&dir.join("to")));
+    ///     assert_eq!(unsafe { libc::seteuid(0) }, 0);
+    ///     assert_eq!(own.unwrap(), true);
+    ///     assert_eq!(not_own.unwrap(), false);
+    /// }
+    /// # fs::remove_dir_all(&dir).unwrap();
+    /// ```
+    fn can_rename(&self, to: &Path) -> io::Result<bool>;
+
+    /// 返回路径的 BSD 标志（st_flags），仅 macOS
+    ///
+    /// ```
+    /// # use std::fs;
+    /// # use path_permission::{BsdFlags, PathPermission};
+    /// # let path = std::env::temp_dir().join(format!("pp-uchg-{}", std::process::id()));
+    /// fs::write(&path, b"").unwrap();
+    /// assert_eq!(path.is_writable().unwrap(), true);
+    ///
+    /// assert_eq!(path.set_bsd_flags(BsdFlags::UF_IMMUTABLE).unwrap(), true);
+    /// assert!(path.bsd_flags().unwrap().contains(BsdFlags::UF_IMMUTABLE));
+    /// assert_eq!(path.is_writable().unwrap(), false);
+    /// assert_eq!(path.is_removable().unwrap(), false);
+    ///
+    /// assert_eq!(path.set_bsd_flags(BsdFlags::default()).unwrap(), true);
+    /// assert_eq!(path.is_writable().unwrap(), true);
+    /// # fs::remove_file(&path).unwrap();
+    /// ```
+    #[cfg(target_os = "macos")]
+    fn bsd_flags(&self) -> io::Result<BsdFlags>;
+
+    /// 变更路径的 BSD 标志（chflags），仅 macOS
+    /// 注意：会整体替换原有的标志，而非追加
+    #[cfg(target_os = "macos")]
+    fn set_bsd_flags(&self, flags: BsdFlags) -> io::Result<bool>;
+
+    /// 返回在此路径创建文件时，其实际将得到的权限（requested 去除 umask 后）
+    /// 路径不可创建时，返回 PermissionDenied 错误。
+    /// 注意：暂未考虑父级目录的默认 ACL。
+    ///
+    /// ```
+    /// # use path_permission::PathPermission;
+    /// # use path_permission::with_umask;
+    /// # let path = std::env::temp_dir().join(format!("pp-umask-{}", std::process::id()));
+    /// with_umask(0o022, || {
+    ///     assert_eq!(path.predicted_create_mode(0o666).unwrap(), 0o644);
+    ///     assert_eq!(path.predicted_create_mode(0o777).unwrap(), 0o755);
+    /// });
+    /// ```
+    fn predicted_create_mode(&self, requested: u16) -> io::Result<u16>;
+
+    /// 返回路径的 SELinux 安全上下文，未设置时返回 None
+    /// 仅 Linux，需启用 selinux 特性
+    #[cfg(all(target_os = "linux", feature = "selinux"))]
+    fn selinux_context(&self) -> io::Result<Option<String>>;
+
+    /// 变更路径的 SELinux 安全上下文，如："system_u:object_r:etc_t:s0"
+    /// 仅 Linux，需启用 selinux 特性
+    #[cfg(all(target_os = "linux", feature = "selinux"))]
+    fn set_selinux_context(&self, context: &str) -> io::Result<bool>;
+
+    /// 根据文件的权限位计算当前进程（有效 uid/gid 及附加组）是否有 amode 权限
+    /// 在 Linux 上会考虑 CAP_DAC_OVERRIDE 及 CAP_DAC_READ_SEARCH，其它系统上
+    /// 仅 root 拥有特权。
+    fn access_for(&self, amode: AccessMode) -> io::Result<bool>;
+
+    /// 根据文件的权限位计算用户 uid（所属组为 gid）是否有 amode 权限
+    ///
+    /// ```
+    /// # use std::{fs, os::unix::fs::{MetadataExt, PermissionsExt}};
+    /// # use path_permission::{AccessMode, PathPermission};
+    /// # let path = std::env::temp_dir().join(format!("pp-accessible-{}", std::process::id()));
+    /// fs::write(&path, b"").unwrap();
+    /// fs::set_permissions(&path, fs::Permissions::from_mode(0o640)).unwrap();
+    /// let metadata = path.metadata().unwrap();
+    /// let (uid, gid) = (metadata.uid(), metadata.gid());
+    ///
+    /// assert!(path.is_accessible_by(uid, gid, AccessMode::READ | AccessMode::WRITE).unwrap());
+    /// assert!(! path.is_accessible_by(uid, gid, AccessMode::EXECUTE).unwrap());
+    /// // 同组的其他用户
+    /// assert!(path.is_accessible_by(uid + 1, gid, AccessMode::READ).unwrap());
+    /// assert!(! path.is_accessible_by(uid + 1, gid, AccessMode::WRITE).unwrap());
+    /// // 其他用户
+    /// assert!(! path.is_accessible_by(uid + 1, gid + 1, AccessMode::READ).unwrap());
+    /// # fs::remove_file(&path).unwrap();
+    /// ```
+    fn is_accessible_by(&self, uid: u32, gid: u32, amode: AccessMode) -> io::Result<bool>;
+
+    /// 与 access 相同，但区分无权限、路径不存在、只读文件系统等情况
+    ///
+    /// ```
+    /// # use std::path::Path;
+    /// # use path_permission::{AccessResult, PathPermission};
+    /// let result = Path::new("/nonexistent").access_detailed(libc::W_OK).unwrap();
+    /// assert!(matches!(result, AccessResult::NotFound));
+    /// assert!(Path::new("src/lib.rs").access_detailed(libc::R_OK).unwrap().is_granted());
+    /// ```
+    fn access_detailed(&self, amode: c_int) -> io::Result<AccessResult>;
+
+    /// 记录路径当前的权限及所有者
+    fn snapshot(&self) -> io::Result<PermSnapshot>;
+
+    /// 将路径的权限及所有者还原为 snap 中记录的值
+    ///
+    /// ```
+    /// # use std::fs;
+    /// # use path_permission::{Mode, PathPermission};
+    /// # let path = std::env::temp_dir().join(format!("pp-snapshot-{}", std::process::id()));
+    /// fs::write(&path, b"").unwrap();
+    /// path.set_mode(Mode::new(0o400)).unwrap();
+    ///
+    /// let snap = path.snapshot().unwrap();
+    /// path.set_mode(Mode::new(0o600)).unwrap();
+    /// assert_eq!(path.get_access().unwrap(), "0600");
+    ///
+    /// path.restore(&snap).unwrap();
+    /// assert_eq!(path.get_access().unwrap(), "0400");
+    /// # fs::remove_file(&path).unwrap();
+    /// ```
+    fn restore(&self, snap: &PermSnapshot) -> io::Result<()>;
+
+    /// 递归记录目录下所有路径的权限、所有者及所属组，不跟随符号链接
+    /// 可通过 PermissionSnapshot::restore 还原，详见 PermissionSnapshot。
+    /// 遇到无法读取的目录等错误时返回遇到的第一个错误。
+    fn snapshot_tree(&self) -> io::Result<PermissionSnapshot>;
+
+    /// 监视路径本身及（为目录时）其直接子路径的权限、所有者变化，详见 PermissionWatcher
+    /// 仅 Linux，需启用 watch 特性
+    #[cfg(all(target_os = "linux", feature = "watch"))]
+    fn watch_permissions(&self) -> io::Result<PermissionWatcher>;
+
+    /// 递归导出目录下所有路径的类型、权限、所有者及所属组，不跟随符号链接
+    /// 逐个路径写入 writer，不会在内存中保留整个目录树；返回写入的路径数量。
+    /// 字段的格式见 manifest 模块的说明，可以 verify_manifest 检查之后的变化。
+    /// 路径以相对于起始路径的形式写入，起始路径本身为 ""。
+    /// 遇到无法读取的目录等错误时，在已写入的内容之后停止，并返回该错误。
+    ///
+    /// ```
+    /// # use std::{ffi::OsStr, fs, os::unix::ffi::OsStrExt};
+    /// # use path_permission::{verify_manifest, ManifestFormat, Mode, PathPermission};
+    /// # let dir = std::env::temp_dir().join(format!("pp-export-{}", std::process::id()));
+    /// # fs::create_dir_all(&dir).unwrap();
+    /// // 不是有效 UTF-8 的文件名以 base64 保存
+    /// let name = dir.join(OsStr::from_bytes(b"bad-\xff-\"quoted\""));
+    /// fs::write(&name, b"").unwrap();
+    /// name.set_mode(Mode::new(0o4755)).unwrap();
+    ///
+    /// let mut manifest = Vec::new();
+    /// dir.export_manifest(&mut manifest, ManifestFormat::JsonLines).unwrap();
+    /// let text = String::from_utf8(manifest.clone()).unwrap();
+    /// let line = text.lines().nth(1).unwrap();
+    /// assert!(line.contains(r#""path_encoding":"base64""#));
+    /// assert!(line.contains(r#""flags":["setuid"]"#));
+    /// assert!(verify_manifest(&dir, &manifest[..]).unwrap().is_empty());
+    /// # fs::remove_dir_all(&dir).unwrap();
+    /// ```
+    fn export_manifest(&self, writer: &mut dyn Write, format: ManifestFormat) -> io::Result<u64>;
+
+    /// 返回文件的 capability（如 `setcap cap_net_raw+ep` 所设置的），未设置时返回 None
+    /// 仅 Linux
+    #[cfg(target_os = "linux")]
+    fn file_capabilities(&self) -> io::Result<Option<FileCaps>>;
+
+    /// 返回路径的文件属性（chattr 所设置的 +i、+a 等），跟随符号链接，仅 Linux
+    /// 通过 statx(2) 获取，内核不支持 statx 时返回 Unsupported 错误。
+    ///
+    /// ```
+    /// # use std::fs;
+    /// # use path_permission::{FileAttributes, PathPermission};
+    /// # let path = std::env::temp_dir().join(format!("pp-attributes-{}", std::process::id()));
+    /// fs::write(&path, b"").unwrap();
+    /// let attrs = path.file_attributes().unwrap();
+    /// assert!(! attrs.is_immutable());
+    /// assert!(! attrs.contains(FileAttributes::APPEND));
+    /// # fs::remove_file(&path).unwrap();
+    /// ```
+    #[cfg(target_os = "linux")]
+    fn file_attributes(&self) -> io::Result<FileAttributes>;
+
+    /// 递归审计目录下所有路径的权限及所有者，不跟随符号链接
+    /// 遇到无权限等错误时，将其记录于对应条目的 error 中，并继续审计
+    ///
+    /// ```
+    /// # use std::fs;
+    /// # use path_permission::{Mode, PathPermission};
+    /// # let dir = std::env::temp_dir().join(format!("pp-audit-{}", std::process::id()));
+    /// # fs::create_dir_all(&dir).unwrap();
+    /// fs::write(dir.join("setuid"), b"").unwrap();
+    /// dir.join("setuid").set_mode(Mode::new(0o4755)).unwrap();
+    /// fs::write(dir.join("shared"), b"").unwrap();
+    /// dir.join("shared").set_mode(Mode::new(0o666)).unwrap();
+    ///
+    /// let entries = dir.audit_tree().unwrap();
+    /// assert_eq!(entries.len(), 3);
+    /// assert_eq!(entries[0].path, dir);
+    /// assert!(entries[1].setuid && ! entries[1].world_writable);
+    /// assert!(entries[2].world_writable && ! entries[2].setuid);
+    /// # fs::remove_dir_all(&dir).unwrap();
+    /// ```
+    fn audit_tree(&self) -> io::Result<Vec<AuditEntry>>;
+
+    /// 递归查找目录下权限满足 predicate 的路径（包括目录本身），跳过符号链接
+    /// 无法读取的目录会被跳过，并继续查找；返回的条目中包括所有者及所属组。
+    ///
+    /// ```
+    /// # use std::fs;
+    /// # use path_permission::{Mode, PathPermission};
+    /// # let dir = std::env::temp_dir().join(format!("pp-find-matching-{}", std::process::id()));
+    /// # fs::create_dir_all(&dir).unwrap();
+    /// dir.set_mode(Mode::new(0o755)).unwrap();
+    /// fs::write(dir.join("shared"), b"").unwrap();
+    /// dir.join("shared").set_mode(Mode::new(0o666)).unwrap();
+    ///
+    /// let found = dir.find_matching(&|mode| mode.bits() & 0o002 != 0).unwrap();
+    /// assert_eq!(found.len(), 1);
+    /// assert_eq!(found[0].path, dir.join("shared"));
+    /// assert_eq!(found[0].uid, dir.join("shared").owner_uid().unwrap());
+    /// # fs::remove_dir_all(&dir).unwrap();
+    /// ```
+    fn find_matching(&self, predicate: &dyn Fn(Mode) -> bool) -> io::Result<Vec<AuditEntry>>;
+
+    /// 递归查找目录下设置了 S_ISUID 的普通文件，跳过符号链接及无法读取的目录
+    /// 需要所有者等信息时，使用 find_matching。
+    ///
+    /// ```
+    /// # use std::{fs, os::unix::fs::symlink};
+    /// # use path_permission::{Mode, PathPermission};
+    /// # let dir = std::env::temp_dir().join(format!("pp-find-setuid-{}", std::process::id()));
+    /// # fs::create_dir_all(dir.join("sub")).unwrap();
+    /// fs::write(dir.join("sub/setuid"), b"").unwrap();
+    /// dir.join("sub/setuid").set_mode(Mode::new(0o4755)).unwrap();
+    /// fs::write(dir.join("setgid"), b"").unwrap();
+    /// dir.join("setgid").set_mode(Mode::new(0o2755)).unwrap();
+    /// symlink(dir.join("sub/setuid"), dir.join("link")).unwrap();
+    /// // 设置了 S_ISGID 的目录不是普通文件
+    /// dir.join("sub").set_mode(Mode::new(0o2755)).unwrap();
+    ///
+    /// assert_eq!(dir.find_setuid().unwrap(), vec![dir.join("sub/setuid")]);
+    /// assert_eq!(dir.find_setgid().unwrap(), vec![dir.join("setgid")]);
+    /// # fs::remove_dir_all(&dir).unwrap();
+    /// ```
+    fn find_setuid(&self) -> io::Result<Vec<PathBuf>>;
+
+    /// 递归查找目录下设置了 S_ISGID 的普通文件，跳过符号链接及无法读取的目录
+    fn find_setgid(&self) -> io::Result<Vec<PathBuf>>;
+
+    /// 递归变更目录下所有路径的权限，跳过符号链接
+    /// 目录上已有的 S_ISUID、S_ISGID、S_ISVTX 会被保留，需清除时见 ChmodOptions。
+    /// 遇到错误时继续处理其余路径，最后返回遇到的第一个错误；
+    /// 全部成功时返回实际变更了权限的路径数量
+    ///
+    /// ```
+    /// # use std::fs;
+    /// # use path_permission::{Mode, PathPermission};
+    /// # let dir = std::env::temp_dir().join(format!("pp-chmod-r-{}", std::process::id()));
+    /// # fs::create_dir_all(dir.join("sub")).unwrap();
+    /// fs::write(dir.join("sub/file"), b"").unwrap();
+    /// dir.set_mode(Mode::new(0o755)).unwrap();
+    ///
+    /// assert_eq!(dir.chmod_recursive(0o750).unwrap(), 3);
+    /// assert_eq!(dir.join("sub/file").get_access().unwrap(), "0750");
+    /// assert_eq!(dir.chmod_recursive(0o750).unwrap(), 0);
+    /// # dir.chmod_recursive(0o755).unwrap();
+    /// # fs::remove_dir_all(&dir).unwrap();
+    /// ```
+    fn chmod_recursive(&self, mode: u16) -> io::Result<usize>;
+
+    /// 递归变更目录下所有路径的所有者及所属组，uid、gid 为 None 时保持不变
+    /// 符号链接使用 lchown(2)，变更的是链接本身，而非其目标。
+    /// keep_going 为 true 时，未能变更的路径（如 EPERM）记录于 ChownReport::failed 中，
+    /// 并继续处理其余路径；为 false 时在遇到第一个错误时停止遍历并返回该错误。
+    /// 变更为其他用户通常需要 root 权限（或 CAP_CHOWN）。
+    ///
+    /// ```
+    /// # use std::{fs, os::unix::fs::symlink};
+    /// # use path_permission::PathPermission;
+    /// # let dir = std::env::temp_dir().join(format!("pp-chown-r-{}", std::process::id()));
+    /// # fs::create_dir_all(dir.join("sub")).unwrap();
+    /// fs::write(dir.join("sub/file"), b"").unwrap();
+    /// symlink(dir.join("missing"), dir.join("link")).unwrap();
+    /// let (uid, gid) = unsafe { (libc::geteuid(), libc::getegid()) };
+    ///
+    /// // 已是当前用户所有，无需变更
+    /// let report = dir.chown_recursive(Some(uid), None, false).unwrap();
+    /// assert_eq!(report.changed, 0);
+    /// assert!(report.failed.is_empty());
+    ///
+    /// let report = dir.chown_recursive(Some(uid), Some(gid), true).unwrap();
+    /// assert!(report.failed.is_empty());
+    /// assert_eq!(dir.join("sub/file").owner_uid().unwrap(), uid);
+    /// // 悬空的符号链接不会被跟随
+    /// assert_eq!(dir.join("link").owner_uid_nofollow().unwrap(), uid);
+    /// # fs::remove_dir_all(&dir).unwrap();
+    /// ```
+    fn chown_recursive(&self, uid: Option<u32>, gid: Option<u32>, keep_going: bool)
+        -> io::Result<ChownReport>;
+
+    /// 多线程版本的 audit_tree，结果同样按路径排序
+    /// 需启用 rayon 特性
+    #[cfg(feature = "rayon")]
+    fn par_audit_tree(&self) -> io::Result<Vec<AuditEntry>>;
+
+    /// 多线程版本的 chmod_recursive，各路径被处理的顺序不确定
+    /// 需启用 rayon 特性
+    #[cfg(feature = "rayon")]
+    fn par_chmod_recursive(&self, mode: u16) -> io::Result<usize>;
+
+    /// 返回在此路径创建目录时，其实际将得到的权限
+    /// 除去除 umask 外，若最近的已存在的上级目录设置了 S_ISGID，新目录将继承此位。
+    ///
+    /// ```
+    /// # use std::fs;
+    /// # use path_permission::{Mode, PathPermission, with_umask};
+    /// # let dir = std::env::temp_dir().join(format!("pp-dirmode-{}", std::process::id()));
+    /// # fs::create_dir_all(&dir).unwrap();
+    /// dir.set_mode(Mode::new(0o2775)).unwrap();
+    /// with_umask(0o002, || {
+    ///     assert_eq!(dir.join("a/b").predicted_dir_mode(0o777).unwrap(), 0o2775);
+    /// });
+    /// # fs::remove_dir_all(&dir).unwrap();
+    /// ```
+    fn predicted_dir_mode(&self, requested: u16) -> io::Result<u16>;
+
+    /// 判断路径是否为只读，即所有者、所属组、其他用户均无写权限（mode & 0o222 == 0）
+    /// 与 std::fs::Permissions::readonly() 一致，只检查权限位，而非当前用户能否写入
+    fn is_readonly(&self) -> io::Result<bool>;
+
+    /// 设置或取消只读
+    /// readonly 为 true 时清除所有写权限位；为 false 时仅添加所有者的写权限（0o200）
+    /// 返回权限是否有变化，同 set_mode
+    ///
+    /// ```
+    /// # use std::fs;
+    /// # use path_permission::{Mode, PathPermission};
+    /// # let path = std::env::temp_dir().join(format!("pp-readonly-{}", std::process::id()));
+    /// fs::write(&path, b"").unwrap();
+    /// path.set_mode(Mode::new(0o664)).unwrap();
+    ///
+    /// assert_eq!(path.set_readonly(true).unwrap(), true);
+    /// assert_eq!(path.is_readonly().unwrap(), true);
+    /// assert_eq!(path.get_access().unwrap(), "0444");
+    /// assert_eq!(path.set_readonly(true).unwrap(), false);
+    ///
+    /// assert_eq!(path.set_readonly(false).unwrap(), true);
+    /// assert_eq!(path.is_readonly().unwrap(), false);
+    /// assert_eq!(path.get_access().unwrap(), "0644");
+    /// # fs::remove_file(&path).unwrap();
+    /// ```
+    fn set_readonly(&self, readonly: bool) -> io::Result<bool>;
+
+    /// 返回路径的权限，包括 S_ISUID、S_ISGID、S_ISVTX（mode & 0o7777）
+    ///
+    /// ```
+    /// # use std::{os::unix::fs::MetadataExt, path::Path};
+    /// # use path_permission::PathPermission;
+    /// for path in &["src/lib.rs", "src", "/dev/null"] {
+    ///     let path = Path::new(path);
+    ///     assert_eq!(path.get_mode().unwrap() as u32, path.metadata().unwrap().mode() & 0o7777);
+    /// }
+    /// ```
+    fn get_mode(&self) -> io::Result<u16>;
+
+    /// 与 get_mode 相同，但不跟随符号链接：路径为符号链接时，返回链接本身的权限
+    /// 悬空的符号链接无法通过 get_mode、get_access、check_mode 获取权限，
+    /// 它们返回获取 stat 时的错误（NotFound），此时仍可以此检查链接本身。
+    ///
+    /// ```
+    /// # use std::{fs, io, os::unix::fs::{symlink, MetadataExt}};
+    /// # use path_permission::{Mode, PathPermission};
+    /// # let dir = std::env::temp_dir().join(format!("pp-mode-nofollow-{}", std::process::id()));
+    /// # fs::create_dir_all(&dir).unwrap();
+    /// let (target, link) = (dir.join("target"), dir.join("link"));
+    /// fs::write(&target, b"").unwrap();
+    /// symlink(&target, &link).unwrap();
+    /// fs::remove_file(&target).unwrap();
+    ///
+    /// assert_eq!(link.get_access().unwrap_err().kind(), io::ErrorKind::NotFound);
+    /// assert_eq!(link.check_mode(Mode::new(0o400)).unwrap_err().kind(), io::ErrorKind::NotFound);
+    /// assert_eq!(link.get_mode().unwrap_err().kind(), io::ErrorKind::NotFound);
+    /// assert_eq!(link.get_mode_nofollow().unwrap() as u32,
+    ///            link.symlink_metadata().unwrap().mode() & 0o7777);
+    /// # fs::remove_dir_all(&dir).unwrap();
+    /// ```
+    fn get_mode_nofollow(&self) -> io::Result<u16>;
+
+    /// 以 ls -l 的形式返回路径的类型及权限，如："drwxr-sr-x"，跟随符号链接
+    /// 格式见 FileMode。
+    ///
+    /// ```
+    /// # use std::{fs, os::unix::fs::symlink};
+    /// # use path_permission::{Mode, PathPermission};
+    /// # let dir = std::env::temp_dir().join(format!("pp-long-format-{}", std::process::id()));
+    /// # fs::create_dir_all(&dir).unwrap();
+    /// let file = dir.join("file");
+    /// fs::write(&file, b"").unwrap();
+    /// file.set_mode(Mode::new(0o4750)).unwrap();
+    /// dir.set_mode(Mode::new(0o1777)).unwrap();
+    /// symlink(&file, dir.join("link")).unwrap();
+    ///
+    /// assert_eq!(file.long_format().unwrap(), "-rwsr-x---");
+    /// assert_eq!(dir.long_format().unwrap(), "drwxrwxrwt");
+    /// assert_eq!(dir.join("link").long_format().unwrap(), "-rwsr-x---");
+    /// assert_eq!(dir.join("link").long_format_nofollow().unwrap(), "lrwxrwxrwx");
+    /// # fs::remove_dir_all(&dir).unwrap();
+    /// ```
+    fn long_format(&self) -> io::Result<String>;
+
+    /// 与 long_format 相同，但不跟随符号链接，符号链接显示为 l
+    fn long_format_nofollow(&self) -> io::Result<String>;
+
+    /// 返回完整的 st_mode，包括文件类型（S_IFMT）及权限，跟随符号链接
+    /// get_mode 等只返回权限部分；需按类型区分时（如其他用户可写的套接字与普通文件）
+    /// 可使用 file_kind 或 stat_summary。
+    ///
+    /// ```
+    /// # use std::{fs, os::unix::fs::symlink};
+    /// # use path_permission::{FileKind, Mode, PathPermission};
+    /// # let dir = std::env::temp_dir().join(format!("pp-full-mode-{}", std::process::id()));
+    /// # fs::create_dir_all(&dir).unwrap();
+    /// let file = dir.join("file");
+    /// fs::write(&file, b"").unwrap();
+    /// file.set_mode(Mode::new(0o640)).unwrap();
+    /// let link = dir.join("link");
+    /// symlink(&file, &link).unwrap();
+    ///
+    /// assert_eq!(file.full_mode().unwrap(), 0o100640);
+    /// assert_eq!(link.full_mode().unwrap(), 0o100640);
+    /// assert_eq!(link.full_mode_nofollow().unwrap() & 0o170000, 0o120000);
+    /// assert_eq!(dir.file_kind().unwrap(), Some(FileKind::Directory));
+    /// assert_eq!(link.file_kind().unwrap(), Some(FileKind::Regular));
+    /// assert_eq!(link.file_kind_nofollow().unwrap(), Some(FileKind::Symlink));
+    ///
+    /// let summary = link.stat_summary().unwrap();
+    /// assert_eq!((summary.kind, summary.mode), (Some(FileKind::Regular), Mode::new(0o640)));
+    /// assert_eq!(summary.uid, file.owner_uid().unwrap());
+    /// assert_eq!(link.stat_summary_nofollow().unwrap().kind, Some(FileKind::Symlink));
+    /// # fs::remove_dir_all(&dir).unwrap();
+    /// ```
+    fn full_mode(&self) -> io::Result<u32>;
+
+    /// 同 full_mode，不跟随符号链接
+    fn full_mode_nofollow(&self) -> io::Result<u32>;
+
+    /// 返回文件类型，跟随符号链接；未知类型时返回 None
+    fn file_kind(&self) -> io::Result<Option<FileKind>>;
+
+    /// 同 file_kind，不跟随符号链接，路径为符号链接时返回 FileKind::Symlink
+    fn file_kind_nofollow(&self) -> io::Result<Option<FileKind>>;
+
+    /// 一次 stat 获取类型、权限、所有者及所属组，跟随符号链接
+    fn stat_summary(&self) -> io::Result<StatSummary>;
+
+    /// 同 stat_summary，不跟随符号链接
+    fn stat_summary_nofollow(&self) -> io::Result<StatSummary>;
+
+    /// 判断路径可否被新建，即 open(O_CREAT|O_EXCL) 能否成功
+    /// 与 is_creatable 不同，路径已存在（包括悬空的符号链接）时返回Ok(false)；
+    /// is_creatable 只判断能否在其父级目录下创建，而不管路径本身是否已存在。
+    ///
+    /// ```
+    /// # use std::{fs, os::unix::fs::symlink};
+    /// # use path_permission::PathPermission;
+    /// # let dir = std::env::temp_dir().join(format!("pp-exclusive-{}", std::process::id()));
+    /// # fs::create_dir_all(&dir).unwrap();
+    /// fs::write(dir.join("file"), b"").unwrap();
+    /// symlink(dir.join("missing"), dir.join("dangling")).unwrap();
+    ///
+    /// assert_eq!(dir.join("new").is_creatable_exclusive().unwrap(), true);
+    /// assert_eq!(dir.join("file").is_creatable().unwrap(), true);
+    /// assert_eq!(dir.join("file").is_creatable_exclusive().unwrap(), false);
+    /// assert_eq!(dir.join("dangling").is_creatable_exclusive().unwrap(), false);
+    /// # fs::remove_dir_all(&dir).unwrap();
+    /// ```
+    fn is_creatable_exclusive(&self) -> io::Result<bool>;
+
+    /// 判断路径可否被创建，并列出需要先行创建的上级目录
+    /// 相对路径以当前目录（std::env::current_dir()）为起点
+    ///
+    /// ```
+    /// # use std::fs;
+    /// # use path_permission::PathPermission;
+    /// # let dir = std::env::temp_dir().join(format!("pp-plan-{}", std::process::id()));
+    /// # fs::create_dir_all(&dir).unwrap();
+    /// let plan = dir.join("app/logs/out.log").creatable_with_parents().unwrap();
+    /// assert_eq!(plan.missing, vec![dir.join("app"), dir.join("app/logs")]);
+    /// assert_eq!(plan.ancestor, dir);
+    /// assert!(plan.creatable);
+    /// # fs::remove_dir_all(&dir).unwrap();
+    /// ```
+    fn creatable_with_parents(&self) -> io::Result<CreationPlan>;
+
+    /// 比较两个路径的权限（0o7777），返回自身变为 other 所需增加及去除的位
+    ///
+    /// ```
+    /// # use std::fs;
+    /// # use path_permission::{Mode, PathPermission};
+    /// # let dir = std::env::temp_dir().join(format!("pp-diff-{}", std::process::id()));
+    /// # fs::create_dir_all(&dir).unwrap();
+    /// # let (a, b) = (dir.join("a"), dir.join("b"));
+    /// # fs::write(&a, b"").unwrap();
+    /// # fs::write(&b, b"").unwrap();
+    /// a.set_mode(Mode::new(0o644)).unwrap();
+    /// b.set_mode(Mode::new(0o755)).unwrap();
+    ///
+    /// let diff = a.diff_mode(&b).unwrap();
+    /// assert_eq!(diff.added, Mode::new(0o111));
+    /// assert_eq!(diff.removed, Mode::new(0));
+    /// assert_eq!(diff.to_string(), "added --x--x--x, removed ---------");
+    /// assert!(a.diff_mode(&a).unwrap().is_empty());
+    /// # fs::remove_dir_all(&dir).unwrap();
+    /// ```
+    fn diff_mode(&self, other: &Path) -> io::Result<ModeDiff>;
+
+    /// 比较与 other 的权限、所有者及所属组，相同时返回 None
+    /// 不跟随符号链接：符号链接比较的是其本身的 lstat，而非目标。目录树的比较见 diff_tree。
+    ///
+    /// ```
+    /// # use std::fs;
+    /// # use path_permission::{Mode, PathPermission};
+    /// # let dir = std::env::temp_dir().join(format!("pp-diff-access-{}", std::process::id()));
+    /// # fs::create_dir_all(&dir).unwrap();
+    /// let (a, b) = (dir.join("a"), dir.join("b"));
+    /// fs::write(&a, b"").unwrap();
+    /// fs::write(&b, b"").unwrap();
+    /// a.set_mode(Mode::new(0o644)).unwrap();
+    /// b.set_mode(Mode::new(0o644)).unwrap();
+    /// assert_eq!(a.diff_access(&b).unwrap(), None);
+    ///
+    /// b.set_mode(Mode::new(0o600)).unwrap();
+    /// let diff = a.diff_access(&b).unwrap().unwrap();
+    /// assert_eq!(diff.to_string(), "mode 0644 -> 0600");
+    /// assert_eq!(diff.second.unwrap().mode, 0o600);
+    /// # fs::remove_dir_all(&dir).unwrap();
+    /// ```
+    fn diff_access(&self, other: &Path) -> io::Result<Option<AccessDiff>>;
+
+    /// 判断目录能否被删除（rmdir）
+    /// 除 is_removable 的条件外，还需对目录本身有读和执行的权限（以列出其内容）；
+    /// must_be_empty 为 true 时，目录还需为空。对于非目录的路径，与 is_removable 相同。
+    ///
+    /// ```
+    /// # use std::fs;
+    /// # use path_permission::PathPermission;
+    /// # let dir = std::env::temp_dir().join(format!("pp-rmdir-{}", std::process::id()));
+    /// # fs::create_dir_all(dir.join("empty")).unwrap();
+    /// # fs::create_dir_all(dir.join("full")).unwrap();
+    /// fs::write(dir.join("full/file"), b"").unwrap();
+    ///
+    /// assert_eq!(dir.join("empty").is_removable_dir(true).unwrap(), true);
+    /// assert_eq!(dir.join("full").is_removable_dir(true).unwrap(), false);
+    /// assert_eq!(dir.join("full").is_removable_dir(false).unwrap(), true);
+    /// assert_eq!(dir.is_tree_removable().unwrap(), true);
+    /// # fs::remove_dir_all(&dir).unwrap();
+    /// ```
+    fn is_removable_dir(&self, must_be_empty: bool) -> io::Result<bool>;
+
+    /// 判断能否递归删除路径及其下的所有内容（如 rm -r）
+    fn is_tree_removable(&self) -> io::Result<bool>;
+
+    /// 返回第一个阻碍递归删除的路径（先序遍历），可以删除时返回 None
+    /// 找到后即停止遍历，不会读取其余的目录。
+    fn tree_removal_blocker(&self) -> io::Result<Option<PathBuf>>;
+
+    /// 根据文件的权限位计算用户 uid（所属组为 gids，包括主组及附加组）是否有 amode 权限
+    /// 文件的所属组与 gids 中任意一个相同时，即适用所属组的权限
+    ///
+    /// ```
+    /// # use std::{fs, os::unix::fs::{MetadataExt, PermissionsExt}};
+    /// # use path_permission::{AccessMode, PathPermission};
+    /// # let path = std::env::temp_dir().join(format!("pp-groups-{}", std::process::id()));
+    /// fs::write(&path, b"").unwrap();
+    /// fs::set_permissions(&path, fs::Permissions::from_mode(0o640)).unwrap();
+    /// let metadata = path.metadata().unwrap();
+    /// let (uid, gid) = (metadata.uid() + 1, metadata.gid());
+    ///
+    /// // 文件的所属组为第二个附加组
+    /// assert!(path.is_accessible_by_groups(uid, &[gid + 1, gid], AccessMode::READ).unwrap());
+    /// assert!(! path.is_accessible_by_groups(uid, &[gid + 1], AccessMode::READ).unwrap());
+    /// # fs::remove_file(&path).unwrap();
+    /// ```
+    fn is_accessible_by_groups(&self, uid: u32, gids: &[u32], amode: AccessMode) -> io::Result<bool>;
+
+    /// 计算使用户 uid（所属组为 gids）得到 desired 权限所需加入当前权限中的最少的位，跟随符号链接
+    /// 规则同 is_accessible_by_groups：只在 uid 所匹配到的分类（所有者、所属组或其他用户）中
+    /// 增加，已有权限时返回 Some(0)。当前进程既不是文件的所有者，也没有特权（root 或
+    /// CAP_FOWNER），因而无法 chmod 时返回 None，此时需先变更所有者。
+    ///
+    /// ```
+    /// # use std::{fs, os::unix::fs::MetadataExt};
+    /// # use path_permission::{AccessMode, Mode, PathPermission};
+    /// # let path = std::env::temp_dir().join(format!("pp-grant-plan-{}", std::process::id()));
+    /// fs::write(&path, b"").unwrap();
+    /// path.set_mode(Mode::new(0o600)).unwrap();
+    /// let metadata = path.metadata().unwrap();
+    /// let (owner, group) = (metadata.uid(), metadata.gid());
+    /// let other = owner + 1;
+    ///
+    /// // 所有者已可读写，只缺执行位
+    /// assert_eq!(path.grant_plan(owner, &[group], AccessMode::READ).unwrap(), Some(0));
+    /// assert_eq!(path.grant_plan(owner, &[group], AccessMode::READ | AccessMode::EXECUTE).unwrap(),
+    ///            Some(0o100));
+    /// // 同组的其他用户
+    /// assert_eq!(path.grant_plan(other, &[group], AccessMode::READ).unwrap(), Some(0o040));
+    /// // 其他用户
+    /// assert_eq!(path.grant_plan(other, &[group + 1], AccessMode::READ | AccessMode::WRITE).unwrap(),
+    ///            Some(0o006));
+    ///
+    /// path.set_mode(Mode::new(0o600 | 0o006)).unwrap();
+    /// assert!(path.is_accessible_by_groups(other, &[group + 1], AccessMode::READ).unwrap());
+    /// # fs::remove_file(&path).unwrap();
+    /// ```
+    fn grant_plan(&self, uid: u32, gids: &[u32], desired: AccessMode) -> io::Result<Option<u16>>;
+
+    /// 计算使用户 who 获得 op 权限所需的最少变更，跟随符号链接，相对路径相对于当前目录
+    /// 与 grant_plan 不同，同时检查各级父级目录的执行权限：缺少的可能是某个父级目录的
+    /// x 位，而非路径本身的权限。所属组的权限位已允许时，也可将用户加入该组；当前进程
+    /// 无法变更其权限的路径，需由所有者变更，或先变更所有者。路径或其父级目录不存在时
+    /// 返回 NotFound 错误。规则同 is_accessible_by_groups，未考虑 ACL 等。
+    ///
+    /// ```
+    /// # use std::{fs, os::unix::fs::MetadataExt};
+    /// # use path_permission::{Mode, Operation, PathPermission, UserContext};
+    /// # let dir = std::env::temp_dir().join(format!("pp-remedy-{}", std::process::id()));
+    /// # fs::create_dir_all(&dir).unwrap();
+    /// let file = dir.join("app.log");
+    /// fs::write(&file, b"").unwrap();
+    /// dir.set_mode(Mode::new(0o700)).unwrap();
+    /// file.set_mode(Mode::new(0o640)).unwrap();
+    /// let metadata = file.metadata().unwrap();
+    /// let who = UserContext::new(metadata.uid() + 1, vec![metadata.gid() + 1]);
+    ///
+    /// let remedy = file.required_mode_for(&who, Operation::Read).unwrap();
+    /// let steps = remedy.steps();
+    /// assert_eq!(steps.len(), 2);
+    /// // 目录缺少其他用户的 x 位，所属组同样不允许
+    /// assert_eq!((&steps[0].path, steps[0].bits, steps[0].group), (&dir, 0o001, None));
+    /// // 文件所属组已可读，也可将用户加入该组
+    /// assert_eq!((&steps[1].path, steps[1].bits, steps[1].group), (&file, 0o004, Some(metadata.gid())));
+    /// assert!(remedy.to_string().starts_with(&format!("chmod o+x {}; chmod o+r {}", dir.display(), file.display())));
+    ///
+    /// dir.set_mode(Mode::new(0o701)).unwrap();
+    /// file.set_mode(Mode::new(0o644)).unwrap();
+    /// assert!(file.required_mode_for(&who, Operation::Read).unwrap().is_granted());
+    /// assert!(file.required_mode_for(&who, Operation::Traverse).is_err());
+    /// # fs::remove_dir_all(&dir).unwrap();
+    /// ```
+    fn required_mode_for(&self, who: &UserContext, op: Operation) -> io::Result<Remedy>;
+
+    /// 根据文件的权限位计算用户 name 是否有读权限，跟随符号链接
+    /// 通过 getpwnam_r(3) 获取用户的 uid 及主组，通过 getgrouplist(3) 获取其附加组，
+    /// 规则同 is_accessible_by_groups，用户为 root 时拥有特权。用户不存在时返回 NotFound 错误。
+    /// 注意：这是计算的结果，而非以该用户的身份调用 access(2)，ACL 及 SELinux 等强制访问控制
+    /// 可能改变实际的结果。
+    ///
+    /// ```
+    /// # use std::{fs, io};
+    /// # use path_permission::{Mode, PathPermission};
+    /// # let path = std::env::temp_dir().join(format!("pp-by-user-{}", std::process::id()));
+    /// fs::write(&path, b"").unwrap();
+    /// path.set_mode(Mode::new(0o600)).unwrap();
+    ///
+    /// // root 可读写任意文件，但只能执行至少有一个执行位的文件
+    /// assert!(path.is_readable_by_user("root").unwrap());
+    /// assert!(path.is_writable_by_user("root").unwrap());
+    /// assert!(! path.is_executable_by_user("root").unwrap());
+    ///
+    /// if let Some(owner) = path.describe().unwrap().owner_name().unwrap() {
+    ///     assert!(path.is_readable_by_user(&owner).unwrap());
+    ///     assert!(! path.is_executable_by_user(&owner).unwrap());
+    /// }
+    ///
+    /// let err = path.is_readable_by_user("pp-no-such-user").unwrap_err();
+    /// assert_eq!(err.kind(), io::ErrorKind::NotFound);
+    /// assert!(err.to_string().contains("pp-no-such-user"));
+    /// # fs::remove_file(&path).unwrap();
+    /// ```
+    fn is_readable_by_user(&self, name: &str) -> io::Result<bool>;
+
+    /// 根据文件的权限位计算用户 name 是否有写权限，见 is_readable_by_user
+    fn is_writable_by_user(&self, name: &str) -> io::Result<bool>;
+
+    /// 根据文件的权限位计算用户 name 是否有执行权限，见 is_readable_by_user
+    fn is_executable_by_user(&self, name: &str) -> io::Result<bool>;
+
+    /// 预览 chmod_recursive 的结果，不做任何修改
+    /// 返回权限需要变更的路径，及其当前的权限、将变更为的权限
+    ///
+    /// ```
+    /// # use std::fs;
+    /// # use path_permission::{Mode, PathPermission};
+    /// # let dir = std::env::temp_dir().join(format!("pp-dry-run-{}", std::process::id()));
+    /// # fs::create_dir_all(&dir).unwrap();
+    /// fs::write(dir.join("file"), b"").unwrap();
+    /// dir.set_mode(Mode::new(0o750)).unwrap();
+    /// dir.join("file").set_mode(Mode::new(0o644)).unwrap();
+    ///
+    /// let changes = dir.chmod_recursive_dry_run(0o750).unwrap();
+    /// assert_eq!(changes, vec![(dir.join("file"), 0o644, 0o750)]);
+    /// assert_eq!(dir.join("file").get_mode().unwrap(), 0o644);
+    /// # fs::remove_dir_all(&dir).unwrap();
+    /// ```
+    fn chmod_recursive_dry_run(&self, mode: u16) -> io::Result<Vec<(PathBuf, u16, u16)>>;
+
+    /// 按类型规范化路径的权限，跟随符号链接：目录变更为 dir_mode，普通文件变更为 file_mode，
+    /// 其它类型不变；返回是否变更了权限。目录上已有的特殊位会被保留，同 chmod_recursive。
+    /// 文件原有的执行权限不会保留，需保留（如脚本）时见 ChmodOptions::preserve_executable。
+    ///
+    /// ```
+    /// # use std::fs;
+    /// # use path_permission::{ChmodOptions, Mode, PathPermission};
+    /// # let dir = std::env::temp_dir().join(format!("pp-normalize-{}", std::process::id()));
+    /// # fs::create_dir_all(dir.join("sub")).unwrap();
+    /// fs::write(dir.join("sub/data"), b"").unwrap();
+    /// fs::write(dir.join("sub/run.sh"), b"").unwrap();
+    /// dir.join("sub").set_mode(Mode::new(0o700)).unwrap();
+    /// dir.join("sub/data").set_mode(Mode::new(0o777)).unwrap();
+    /// dir.join("sub/run.sh").set_mode(Mode::new(0o700)).unwrap();
+    ///
+    /// assert!(dir.join("sub").normalize_mode(0o644, 0o755).unwrap());
+    /// assert!(! dir.join("sub").normalize_mode(0o644, 0o755).unwrap());
+    /// assert_eq!(dir.join("sub").get_mode().unwrap(), 0o755);
+    ///
+    /// let mut options = ChmodOptions::new();
+    /// options.preserve_executable(true);
+    /// assert_eq!(options.normalize_tree(&dir, 0o640, 0o750).unwrap(), 4);
+    /// assert_eq!(dir.get_mode().unwrap(), 0o750);
+    /// assert_eq!(dir.join("sub/data").get_mode().unwrap(), 0o750);
+    /// assert_eq!(dir.join("sub/run.sh").get_mode().unwrap(), 0o750);
+    ///
+    /// // 默认清除文件的执行权限
+    /// assert_eq!(dir.normalize_tree(0o644, 0o755).unwrap(), 4);
+    /// assert_eq!(dir.join("sub/run.sh").get_mode().unwrap(), 0o644);
+    /// # fs::remove_dir_all(&dir).unwrap();
+    /// ```
+    fn normalize_mode(&self, file_mode: u16, dir_mode: u16) -> io::Result<bool>;
+
+    /// 递归地按类型规范化目录下所有路径的权限，规则同 normalize_mode，跳过符号链接
+    /// 遇到错误时继续处理其余路径，最后返回遇到的第一个错误；
+    /// 全部成功时返回实际变更了权限的路径数量
+    fn normalize_tree(&self, file_mode: u16, dir_mode: u16) -> io::Result<usize>;
+
+    /// 清除路径上危险的位：S_ISUID、S_ISGID，以及所属组和其他用户的写权限（0o6022），
+    /// 如用于处理解压自不可信归档的文件；返回是否变更了权限
+    /// 不跟随符号链接，路径为符号链接时不变更。所清除的位见 ChmodOptions::strip_bits。
+    ///
+    /// ```
+    /// # use std::fs;
+    /// # use path_permission::{ChmodOptions, Mode, PathPermission};
+    /// # let dir = std::env::temp_dir().join(format!("pp-sanitize-{}", std::process::id()));
+    /// # fs::create_dir_all(dir.join("sub")).unwrap();
+    /// let file = dir.join("sub/file");
+    /// fs::write(&file, b"").unwrap();
+    /// file.set_mode(Mode::new(0o6777)).unwrap();
+    /// assert!(file.sanitize().unwrap());
+    /// assert_eq!(file.get_mode().unwrap(), 0o755);
+    /// assert!(! file.sanitize().unwrap());
+    ///
+    /// file.set_mode(Mode::new(0o6777)).unwrap();
+    /// dir.join("sub").set_mode(Mode::new(0o777)).unwrap();
+    /// dir.set_mode(Mode::new(0o755)).unwrap();
+    /// assert_eq!(dir.sanitize_tree().unwrap(), 2);
+    /// assert_eq!(dir.join("sub").get_mode().unwrap(), 0o755);
+    ///
+    /// // 只清除 S_ISUID 及其他用户的写权限
+    /// file.set_mode(Mode::new(0o6777)).unwrap();
+    /// let mut options = ChmodOptions::new();
+    /// options.strip_bits(0o4002);
+    /// assert!(options.sanitize(&file).unwrap());
+    /// assert_eq!(file.get_mode().unwrap(), 0o2775);
+    /// # fs::remove_dir_all(&dir).unwrap();
+    /// ```
+    fn sanitize(&self) -> io::Result<bool>;
+
+    /// 递归地清除目录下所有路径上危险的位，规则同 sanitize，跳过符号链接
+    /// 遇到错误时继续处理其余路径，最后返回遇到的第一个错误；
+    /// 全部成功时返回实际变更了权限的路径数量
+    fn sanitize_tree(&self) -> io::Result<usize>;
+
+    /// 判断路径是否为隐藏文件，即文件名以'.'开头（"."及".."除外）
+    /// 只检查路径本身，无需访问文件系统；在 macOS 上，设置了 UF_HIDDEN 标志的
+    /// 文件同样视为隐藏（无法获取标志时忽略）。
+    ///
+    /// ```
+    /// # use std::path::Path;
+    /// # use path_permission::PathPermission;
+    /// assert!(Path::new("/home/user/.bashrc").is_hidden());
+    /// assert!(! Path::new("README").is_hidden());
+    /// assert!(! Path::new("..").is_hidden());
+    /// ```
+    fn is_hidden(&self) -> bool;
+
+    /// 判断路径能否被重命名（移动）为 dest，并指出不满足的条件
+    /// 包括源、目标目录的写和执行权限，S_ISVTX 下的所属用户，目标已存在时能否
+    /// 将其替换，以及两者是否位于同一文件系统（否则 rename(2) 返回 EXDEV）。
+    /// 将目录移动至其他目录时，还需对该目录本身有写的权限，以更新其中的 ".."。
+    ///
+    /// ```
+    /// # use std::{fs, path::Path};
+    /// # use path_permission::{Mode, PathPermission, RenameCheck};
+    /// # let dir = std::env::temp_dir().join(format!("pp-renamable-{}", std::process::id()));
+    /// # fs::create_dir_all(&dir).unwrap();
+    /// let from = dir.join("from");
+    /// fs::write(&from, b"").unwrap();
+    /// fs::write(dir.join("existing"), b"").unwrap();
+    ///
+    /// assert_eq!(from.is_renamable_to(&dir.join("to")).unwrap(), RenameCheck::Allowed);
+    /// assert_eq!(from.is_renamable_to(&dir.join("existing")).unwrap(), RenameCheck::Allowed);
+    /// assert_eq!(dir.join("missing").is_renamable_to(&dir.join("to")).unwrap(),
+    ///            RenameCheck::SourceMissing);
+    /// assert_eq!(from.is_renamable_to(&dir.join("missing/to")).unwrap(),
+    ///            RenameCheck::DestinationParentMissing);
+    ///
+    /// let (a, b) = (dir.join("a"), dir.join("b"));
+    /// let sub = a.join("sub");
+    /// fs::create_dir_all(&sub).unwrap();
+    /// fs::create_dir(&b).unwrap();
+    /// a.set_mode(Mode::new(0o777)).unwrap();
+    /// b.set_mode(Mode::new(0o777)).unwrap();
+    /// sub.set_mode(Mode::new(0o555)).unwrap();
+    /// // 以 root 运行时，暂时将有效 uid 设为 65534，否则总能写入
+    /// let root = unsafe { libc::geteuid() } == 0;
+    /// if root {
+    ///     assert_eq!(unsafe { libc::seteuid(65534) }, 0);
+    /// }
+    /// let moved = sub.is_renamable_to(&b.join("sub"));
+    /// let renamed = sub.is_renamable_to(&a.join("renamed"));
+    /// if root {
+    ///     assert_eq!(unsafe { libc::seteuid(0) }, 0);
+    /// }
+    /// // 只在同一目录下改名时，无需对目录本身有写的权限
+    /// assert_eq!(moved.unwrap(), RenameCheck::SourceDirectoryNotWritable);
+    /// assert_eq!(renamed.unwrap(), RenameCheck::Allowed);
+    /// # fs::remove_dir_all(&dir).unwrap();
+    /// ```
+    fn is_renamable_to(&self, dest: &Path) -> io::Result<RenameCheck>;
+
+    /// 检查是否拥有 amode 中的全部权限，与 access 相同
+    fn access_all(&self, amode: AccessMode) -> io::Result<bool>;
+
+    /// 检查是否拥有 amode 中的任一权限，逐个调用 access(2) 检查
+    ///
+    /// ```
+    /// # use std::fs;
+    /// # use path_permission::{AccessMode, Mode, PathPermission};
+    /// # let path = std::env::temp_dir().join(format!("pp-access-any-{}", std::process::id()));
+    /// fs::write(&path, b"").unwrap();
+    /// path.set_mode(Mode::new(0o444)).unwrap();
+    /// assert!(path.access_any(AccessMode::READ | AccessMode::WRITE).unwrap());
+    /// assert!(! path.access_any(AccessMode::EXECUTE).unwrap());
+    ///
+    /// // root 可绕过写权限的检查
+    /// if unsafe { libc::geteuid() } != 0 {
+    ///     assert!(! path.access_all(AccessMode::READ | AccessMode::WRITE).unwrap());
+    /// }
+    /// # fs::remove_file(&path).unwrap();
+    /// ```
+    fn access_any(&self, amode: AccessMode) -> io::Result<bool>;
+
+    /// 判断能否以 newpath 为路径创建指向此路径的硬链接
+    /// 需 newpath 尚不存在，对其父级目录有写和执行的权限，且与此路径位于同一
+    /// 文件系统；此路径不能是目录。在 Linux 上，开启 protected_hardlinks 时，
+    /// 还需为此路径的所有者，或此路径是可读写的普通文件（非 setuid 等）。
+    ///
+    /// ```
+    /// # use std::fs;
+    /// # use path_permission::PathPermission;
+    /// # let dir = std::env::temp_dir().join(format!("pp-hardlink-{}", std::process::id()));
+    /// # fs::create_dir_all(&dir).unwrap();
+    /// let file = dir.join("file");
+    /// fs::write(&file, b"").unwrap();
+    ///
+    /// assert!(file.is_hardlinkable_to(&dir.join("link")).unwrap());
+    /// // 已存在
+    /// assert!(! file.is_hardlinkable_to(&file).unwrap());
+    /// // 父级目录不存在
+    /// assert!(! file.is_hardlinkable_to(&dir.join("missing/link")).unwrap());
+    /// // 目录不能被硬链接
+    /// assert!(! dir.is_hardlinkable_to(&dir.join("link")).unwrap());
+    /// # fs::remove_dir_all(&dir).unwrap();
+    /// ```
+    fn is_hardlinkable_to(&self, newpath: &Path) -> io::Result<bool>;
+
+    /// 判断能否在此路径创建符号链接
+    /// 需此路径尚不存在，且对其父级目录有写和执行的权限
+    fn is_symlinkable_at(&self) -> io::Result<bool>;
+
+    /// 判断能否在此路径创建符号链接，同 is_symlinkable_at
+    /// 注意：能创建不代表之后能被其他用户跟随，见 symlink_would_be_followable
+    fn is_symlinkable(&self) -> io::Result<bool>;
+
+    /// 判断此符号链接能否被用户 by_uid 跟随，路径不是符号链接时总是返回 true
+    /// 在 Linux 上开启 fs.protected_symlinks 时，位于所有人可写且设置了 S_ISVTX
+    /// 的目录（如 /tmp）中的符号链接，只有跟随者为链接的所有者，或目录的所有者
+    /// 即链接的所有者时，才能被跟随。其它系统上总是返回 true。
+    ///
+    /// ```
+    /// # use std::{fs, os::unix::fs::symlink};
+    /// # use path_permission::{Mode, PathPermission};
+    /// # let dir = std::env::temp_dir().join(format!("pp-follow-{}", std::process::id()));
+    /// fs::create_dir_all(&dir).unwrap();
+    /// dir.set_mode(Mode::new(0o1777)).unwrap();
+    /// let link = dir.join("link");
+    /// assert!(link.is_symlinkable().unwrap());
+    /// symlink("/", &link).unwrap();
+    /// assert!(! link.is_symlinkable().unwrap());
+    ///
+    /// let uid = unsafe { libc::geteuid() };
+    /// assert!(link.symlink_would_be_followable(uid).unwrap());
+    /// // 目录所有者即链接所有者
+    /// assert!(link.symlink_would_be_followable(uid + 1).unwrap());
+    ///
+    /// let protected = fs::read_to_string("/proc/sys/fs/protected_symlinks")
+    ///     .map(|value| value.trim() == "1").unwrap_or(false);
+    /// // 仅以 root 运行时检查
+    /// if protected && uid == 0 {
+    ///     std::os::unix::fs::chown(&dir, Some(65534), None).unwrap();
+    ///     assert!(link.symlink_would_be_followable(0).unwrap());
+    ///     assert!(! link.symlink_would_be_followable(65534).unwrap());
+    ///     // 目录非所有人可写时不受限制
+    ///     dir.set_mode(Mode::new(0o1775)).unwrap();
+    ///     assert!(link.symlink_would_be_followable(65534).unwrap());
+    /// }
+    /// # fs::remove_dir_all(&dir).unwrap();
+    /// ```
+    fn symlink_would_be_followable(&self, by_uid: u32) -> io::Result<bool>;
+
+    /// 判断路径是否存在且可读，路径不存在时返回 Ok(false) 而非错误
+    ///
+    /// ```
+    /// # use std::fs;
+    /// # use path_permission::{Mode, PathPermission};
+    /// # let path = std::env::temp_dir().join(format!("pp-exists-and-{}", std::process::id()));
+    /// assert_eq!(path.exists_and_readable().unwrap(), false);
+    /// assert_eq!(path.join("child").exists_and_writable().unwrap(), false);
+    /// assert_eq!(path.exists_and_executable().unwrap(), false);
+    ///
+    /// fs::write(&path, b"").unwrap();
+    /// path.set_mode(Mode::new(0o600)).unwrap();
+    /// assert_eq!(path.exists_and_readable().unwrap(), true);
+    /// assert_eq!(path.exists_and_executable().unwrap(), false);
+    ///
+    /// // root 可绕过读权限的检查
+    /// if unsafe { libc::geteuid() } != 0 {
+    ///     path.set_mode(Mode::new(0o200)).unwrap();
+    ///     assert_eq!(path.exists_and_readable().unwrap(), false);
+    /// }
+    /// # fs::remove_file(&path).unwrap();
+    /// ```
+    fn exists_and_readable(&self) -> io::Result<bool>;
+
+    /// 判断路径是否存在且可写，路径不存在时返回 Ok(false) 而非错误
+    fn exists_and_writable(&self) -> io::Result<bool>;
+
+    /// 判断路径是否存在且可执行，路径不存在时返回 Ok(false) 而非错误
+    fn exists_and_executable(&self) -> io::Result<bool>;
+
+    /// 判断路径能否被截断，即对文件本身（而非父级目录）有写权限，目录总是返回 false
+    /// FIFO、套接字等只根据权限位判断，不会尝试打开。
+    /// 在 macOS 上，设置了 uchg/schg 或 uappnd/sappnd 标志的文件不能被截断；
+    /// 在 Linux 上，设置了 chattr +a、+i 属性的同样不能（statx 不可用时不检查）。
+    ///
+    /// ```
+    /// # use std::{fs, ffi::CString, os::unix::ffi::OsStrExt};
+    /// # use path_permission::{Mode, PathPermission};
+    /// # let dir = std::env::temp_dir().join(format!("pp-truncatable-{}", std::process::id()));
+    /// # fs::create_dir_all(&dir).unwrap();
+    /// let file = dir.join("file");
+    /// fs::write(&file, b"").unwrap();
+    /// file.set_mode(Mode::new(0o644)).unwrap();
+    /// assert!(file.is_truncatable().unwrap());
+    /// assert!(file.is_appendable().unwrap());
+    ///
+    /// assert!(! dir.is_truncatable().unwrap());
+    /// assert!(! dir.is_appendable().unwrap());
+    ///
+    /// let fifo = dir.join("fifo");
+    /// let c_fifo = CString::new(fifo.as_os_str().as_bytes()).unwrap();
+    /// assert_eq!(unsafe { libc::mkfifo(c_fifo.as_ptr(), 0o644) }, 0);
+    /// assert!(fifo.is_truncatable().unwrap());
+    /// assert!(fifo.is_appendable().unwrap());
+    /// # fs::remove_dir_all(&dir).unwrap();
+    /// ```
+    fn is_truncatable(&self) -> io::Result<bool>;
+
+    /// 判断能否向路径追加写入，即对文件本身有写权限，目录总是返回 false
+    /// 与 is_truncatable 不同，设置了只可追加标志的文件仍可追加写入。
+    /// 在 macOS 上，设置了 uchg/schg 标志的文件不能追加写入，
+    /// Linux 上设置了 chattr +i 属性的同样不能。
+    fn is_appendable(&self) -> io::Result<bool>;
+
+    /// 判断路径所在的文件系统是否以 noexec 挂载
+    /// Linux 上通过 statvfs 的 ST_NOEXEC，macOS 上通过 statfs 的 MNT_NOEXEC；
+    /// 其它系统上总是返回 false。
+    fn is_on_noexec_mount(&self) -> io::Result<bool>;
+
+    /// 判断路径是否真正可执行：在 is_excutable 的基础上，还需所在的文件系统未以
+    /// noexec 挂载，否则 execve 会以 EACCES 失败
+    /// is_excutable 保持原有的行为，只根据权限位（通过 access(2)）判断。
+    ///
+    /// ```
+    /// # use std::fs;
+    /// # use path_permission::{Mode, PathPermission};
+    /// # let path = std::env::temp_dir().join(format!("pp-truly-exec-{}", std::process::id()));
+    /// fs::write(&path, b"").unwrap();
+    /// path.set_mode(Mode::new(0o755)).unwrap();
+    /// assert_eq!(path.is_truly_executable().unwrap(), ! path.is_on_noexec_mount().unwrap());
+    /// path.set_mode(Mode::new(0o644)).unwrap();
+    /// assert_eq!(path.is_truly_executable().unwrap(), false);
+    /// # fs::remove_file(&path).unwrap();
+    /// ```
+    fn is_truly_executable(&self) -> io::Result<bool>;
+
+    /// 判断目录能否被列出（读取其中的条目），即对目录有读权限
+    /// 路径不是目录（或指向目录的符号链接）时返回 Ok(false)。
+    /// 注意：只有读权限（如 0o444）时可以列出条目名称，但无法获取条目的信息。
+    ///
+    /// ```
+    /// # use std::fs;
+    /// # use path_permission::{Mode, PathPermission};
+    /// # let dir = std::env::temp_dir().join(format!("pp-listable-{}", std::process::id()));
+    /// fs::create_dir_all(&dir).unwrap();
+    /// let file = dir.join("file");
+    /// fs::write(&file, b"").unwrap();
+    /// assert!(! file.is_listable().unwrap());
+    /// assert!(! file.is_traversable().unwrap());
+    ///
+    /// // 常见的网站根目录，对所有者而言可列出及进入
+    /// dir.set_mode(Mode::new(0o711)).unwrap();
+    /// assert!(dir.is_listable().unwrap());
+    /// assert!(dir.is_traversable().unwrap());
+    ///
+    /// // root 可绕过目录的读、执行权限检查
+    /// if unsafe { libc::geteuid() } != 0 {
+    ///     dir.set_mode(Mode::new(0o444)).unwrap();
+    ///     assert!(dir.is_listable().unwrap());
+    ///     assert!(! dir.is_traversable().unwrap());
+    ///     dir.set_mode(Mode::new(0o311)).unwrap();
+    ///     assert!(! dir.is_listable().unwrap());
+    ///     assert!(dir.is_traversable().unwrap());
+    /// }
+    /// # dir.set_mode(Mode::new(0o755)).unwrap();
+    /// # fs::remove_dir_all(&dir).unwrap();
+    /// ```
+    fn is_listable(&self) -> io::Result<bool>;
+
+    /// 判断目录能否被进入（访问其中的条目），即对目录有执行权限
+    /// 路径不是目录（或指向目录的符号链接）时返回 Ok(false)。
+    /// 注意：只有执行权限（如 0o311）时可以访问已知名称的条目，但无法列出。
+    fn is_traversable(&self) -> io::Result<bool>;
+
+    /// 判断能否在此目录中创建名为 name 的条目，条目已存在时返回 false
+    /// 具体的原因见 check_create_in
+    fn can_create_in(&self, name: &OsStr) -> io::Result<bool>;
+
+    /// 判断能否在此目录中创建名为 name 的条目，并指出不满足的条件
+    /// 包括此路径是否为目录、名称是否合法（不含 '/'、NUL，不为 "."、".."，且不超过
+    /// NAME_MAX）、对目录的写和执行权限，以及条目是否已存在。overwrite 为 true 时
+    /// 允许替换已存在的条目，此时需满足将其删除的条件（如 S_ISVTX）。
+    ///
+    /// ```
+    /// # use std::{ffi::OsStr, fs};
+    /// # use path_permission::{CreateCheck, PathPermission};
+    /// # let dir = std::env::temp_dir().join(format!("pp-create-in-{}", std::process::id()));
+    /// # fs::create_dir_all(&dir).unwrap();
+    /// fs::write(dir.join("existing"), b"").unwrap();
+    ///
+    /// assert!(dir.can_create_in(OsStr::new("new")).unwrap());
+    /// assert!(! dir.can_create_in(OsStr::new("existing")).unwrap());
+    /// assert_eq!(dir.check_create_in(OsStr::new("existing"), false).unwrap(),
+    ///            CreateCheck::AlreadyExists);
+    /// assert_eq!(dir.check_create_in(OsStr::new("existing"), true).unwrap(),
+    ///            CreateCheck::Allowed);
+    /// assert_eq!(dir.check_create_in(OsStr::new("a/b"), false).unwrap(),
+    ///            CreateCheck::InvalidName);
+    /// assert_eq!(dir.check_create_in(OsStr::new(".."), false).unwrap(),
+    ///            CreateCheck::InvalidName);
+    /// assert_eq!(dir.check_create_in(OsStr::new(&"x".repeat(4096)), false).unwrap(),
+    ///            CreateCheck::NameTooLong);
+    /// assert_eq!(dir.join("existing").check_create_in(OsStr::new("new"), false).unwrap(),
+    ///            CreateCheck::NotADirectory);
+    /// # fs::remove_dir_all(&dir).unwrap();
+    /// ```
+    fn check_create_in(&self, name: &OsStr, overwrite: bool) -> io::Result<CreateCheck>;
+
+    /// 将路径的权限临时变更为 mode，返回的 PermGuard 离开作用域时还原
+    ///
+    /// ```
+    /// # use std::fs;
+    /// # use path_permission::{Mode, PathPermission};
+    /// # let path = std::env::temp_dir().join(format!("pp-guard-{}", std::process::id()));
+    /// fs::write(&path, b"").unwrap();
+    /// path.set_mode(Mode::new(0o400)).unwrap();
+    /// {
+    ///     let _guard = path.with_mode(0o600).unwrap();
+    ///     assert_eq!(path.get_mode().unwrap(), 0o600);
+    /// }
+    /// assert_eq!(path.get_mode().unwrap(), 0o400);
+    ///
+    /// // 需要得知还原是否成功时
+    /// let guard = path.with_mode(0o644).unwrap();
+    /// guard.close().unwrap();
+    /// assert_eq!(path.get_mode().unwrap(), 0o400);
+    /// # fs::remove_file(&path).unwrap();
+    /// ```
+    fn with_mode(&self, mode: u16) -> io::Result<PermGuard>;
+
+    /// 判断路径能否作为程序运行：需为普通文件（跟随符号链接），可执行，且所在的
+    /// 文件系统未以 noexec 挂载
+    ///
+    /// ```
+    /// # use std::{fs, os::unix::fs::symlink};
+    /// # use path_permission::{Mode, PathPermission};
+    /// # let dir = std::env::temp_dir().join(format!("pp-exec-file-{}", std::process::id()));
+    /// # fs::create_dir_all(&dir).unwrap();
+    /// let program = dir.join("program");
+    /// fs::write(&program, b"").unwrap();
+    /// program.set_mode(Mode::new(0o755)).unwrap();
+    /// symlink(&program, dir.join("link")).unwrap();
+    ///
+    /// let noexec = program.is_on_noexec_mount().unwrap();
+    /// assert_eq!(program.is_executable_file().unwrap(), ! noexec);
+    /// assert_eq!(dir.join("link").is_executable_file().unwrap(), ! noexec);
+    /// // 目录可进入，但不能运行
+    /// assert!(dir.is_excutable().unwrap());
+    /// assert!(! dir.is_executable_file().unwrap());
+    /// # fs::remove_dir_all(&dir).unwrap();
+    /// ```
+    fn is_executable_file(&self) -> io::Result<bool>;
+
+    /// 返回路径所有者的 uid，跟随符号链接
+    ///
+    /// ```
+    /// # use std::{fs, os::unix::fs::{symlink, MetadataExt}};
+    /// # use path_permission::PathPermission;
+    /// # let dir = std::env::temp_dir().join(format!("pp-owner-{}", std::process::id()));
+    /// # fs::create_dir_all(&dir).unwrap();
+    /// let path = dir.join("file");
+    /// fs::write(&path, b"").unwrap();
+    /// let metadata = path.metadata().unwrap();
+    /// assert_eq!(path.owner_uid().unwrap(), metadata.uid());
+    /// assert_eq!(path.owner_gid().unwrap(), metadata.gid());
+    /// assert_eq!(path.device_id().unwrap(), metadata.dev());
+    /// assert_eq!(path.inode().unwrap(), metadata.ino());
+    ///
+    /// let link = dir.join("link");
+    /// symlink(&path, &link).unwrap();
+    /// let link_metadata = link.symlink_metadata().unwrap();
+    /// assert_eq!(link.owner_uid_nofollow().unwrap(), link_metadata.uid());
+    /// assert_eq!(link.owner_gid_nofollow().unwrap(), link_metadata.gid());
+    /// assert_eq!(link.inode().unwrap(), metadata.ino());
+    /// # fs::remove_dir_all(&dir).unwrap();
+    /// ```
+    fn owner_uid(&self) -> io::Result<u32>;
+
+    /// 返回路径所有者的 uid，不跟随符号链接
+    fn owner_uid_nofollow(&self) -> io::Result<u32>;
+
+    /// 返回路径所属组的 gid，跟随符号链接
+    fn owner_gid(&self) -> io::Result<u32>;
+
+    /// 返回路径所属组的 gid，不跟随符号链接
+    fn owner_gid_nofollow(&self) -> io::Result<u32>;
+
+    /// 当前进程是否属于路径的所属组，跟随符号链接
+    /// 即检查权限时，所属组的权限位是否可能适用于当前进程（所有者除外）。
+    ///
+    /// ```
+    /// # use std::{fs, path::Path};
+    /// # use path_permission::{in_group, PathPermission};
+    /// # let path = std::env::temp_dir().join(format!("pp-shares-group-{}", std::process::id()));
+    /// // 新建的文件属于当前进程的有效 gid（所在目录设置了 S_ISGID 时除外）
+    /// fs::write(&path, b"").unwrap();
+    /// if std::env::temp_dir().get_mode().unwrap() & 0o2000 == 0 {
+    ///     assert!(path.shares_group_with().unwrap());
+    /// }
+    /// let root = Path::new("/");
+    /// assert_eq!(root.shares_group_with().unwrap(), in_group(root.owner_gid().unwrap()).unwrap());
+    /// # fs::remove_file(&path).unwrap();
+    /// ```
+    fn shares_group_with(&self) -> io::Result<bool>;
+
+    /// 路径的所有者是否为当前进程的有效 uid（geteuid），跟随符号链接
+    /// 内核检查权限及判断能否 chmod 等时，均以有效 uid 而非实际 uid（getuid）判断所有者。
+    ///
+    /// ```
+    /// # use std::{fs, os::unix::fs::{symlink, MetadataExt}, path::Path};
+    /// # use path_permission::PathPermission;
+    /// # let dir = std::env::temp_dir().join(format!("pp-owned-{}", std::process::id()));
+    /// # fs::create_dir_all(&dir).unwrap();
+    /// let file = dir.join("file");
+    /// fs::write(&file, b"").unwrap();
+    /// let metadata = file.metadata().unwrap();
+    /// assert!(file.is_owned_by_current_user().unwrap());
+    /// assert!(file.is_owned_by(metadata.uid()).unwrap());
+    /// assert!(! file.is_owned_by(metadata.uid() + 1).unwrap());
+    /// assert!(file.is_group_owned_by(metadata.gid()).unwrap());
+    /// assert!(file.is_group_owned_by_current(true).unwrap());
+    /// assert_eq!(Path::new("/").is_owned_by_current_user().unwrap(), unsafe { libc::geteuid() } == 0);
+    ///
+    /// // 不跟随时检查的是符号链接本身
+    /// let link = dir.join("link");
+    /// symlink("/", &link).unwrap();
+    /// assert!(link.is_owned_by_current_user_nofollow().unwrap());
+    /// assert!(link.is_owned_by_nofollow(metadata.uid()).unwrap());
+    /// assert!(link.is_group_owned_by_current_nofollow(true).unwrap());
+    /// assert!(link.is_owned_by(0).unwrap());
+    /// assert!(link.is_group_owned_by(Path::new("/").owner_gid().unwrap()).unwrap());
+    /// # fs::remove_dir_all(&dir).unwrap();
+    /// ```
+    fn is_owned_by_current_user(&self) -> io::Result<bool>;
+
+    /// 同 is_owned_by_current_user，不跟随符号链接
+    fn is_owned_by_current_user_nofollow(&self) -> io::Result<bool>;
+
+    /// 路径的所有者是否为 uid，跟随符号链接
+    fn is_owned_by(&self, uid: u32) -> io::Result<bool>;
+
+    /// 路径的所有者是否为 uid，不跟随符号链接
+    fn is_owned_by_nofollow(&self, uid: u32) -> io::Result<bool>;
+
+    /// 路径的所属组是否为 gid，跟随符号链接
+    fn is_group_owned_by(&self, gid: u32) -> io::Result<bool>;
+
+    /// 路径的所属组是否为 gid，不跟随符号链接
+    fn is_group_owned_by_nofollow(&self, gid: u32) -> io::Result<bool>;
+
+    /// 路径的所属组是否为当前进程的有效 gid（getegid），跟随符号链接
+    /// include_supplementary 为 true 时，所属组为附加组之一同样返回 true，同 shares_group_with。
+    fn is_group_owned_by_current(&self, include_supplementary: bool) -> io::Result<bool>;
+
+    /// 同 is_group_owned_by_current，不跟随符号链接
+    fn is_group_owned_by_current_nofollow(&self, include_supplementary: bool) -> io::Result<bool>;
+
+    /// 返回路径所在设备的编号（st_dev），跟随符号链接
+    fn device_id(&self) -> io::Result<u64>;
+
+    /// 返回路径的 inode 编号（st_ino），跟随符号链接
+    fn inode(&self) -> io::Result<u64>;
+
+    /// 返回路径的大小（st_size，字节），跟随符号链接
+    ///
+    /// ```
+    /// # use std::{fs, os::unix::fs::MetadataExt};
+    /// # use path_permission::PathPermission;
+    /// # let path = std::env::temp_dir().join(format!("pp-size-{}", std::process::id()));
+    /// fs::write(&path, vec![0u8; 10000]).unwrap();
+    /// let metadata = path.metadata().unwrap();
+    /// assert_eq!(path.size().unwrap(), 10000);
+    /// assert_eq!(path.size().unwrap(), metadata.len());
+    /// assert_eq!(path.blocks().unwrap(), metadata.blocks());
+    /// // 稀疏文件的大小可能远大于实际占用的空间
+    /// fs::File::options().write(true).open(&path).unwrap().set_len(1 << 30).unwrap();
+    /// assert!(path.blocks().unwrap() * 512 <= path.size().unwrap());
+    /// # fs::remove_file(&path).unwrap();
+    /// ```
+    fn size(&self) -> io::Result<u64>;
+
+    /// 返回路径实际占用的磁盘空间（st_blocks），跟随符号链接
+    /// 按 POSIX，以 512 字节为单位，而非文件系统的块大小（st_blksize）。
+    fn blocks(&self) -> io::Result<u64>;
+
+    /// 判断与 other 是否为同一个文件，即 (st_dev, st_ino) 相同，跟随符号链接
+    /// 可用于识别硬链接，或在遍历时识别已访问过的文件。
+    ///
+    /// ```
+    /// # use std::{fs, os::unix::fs::symlink};
+    /// # use path_permission::PathPermission;
+    /// # let dir = std::env::temp_dir().join(format!("pp-same-file-{}", std::process::id()));
+    /// # fs::create_dir_all(&dir).unwrap();
+    /// let file = dir.join("file");
+    /// fs::write(&file, b"").unwrap();
+    /// fs::hard_link(&file, dir.join("hardlink")).unwrap();
+    /// fs::copy(&file, dir.join("copy")).unwrap();
+    /// symlink(&file, dir.join("symlink")).unwrap();
+    ///
+    /// assert!(file.is_same_file_as(&dir.join("hardlink")).unwrap());
+    /// assert!(file.is_same_file_as(&dir.join("symlink")).unwrap());
+    /// assert!(! file.is_same_file_as(&dir.join("copy")).unwrap());
+    /// # fs::remove_dir_all(&dir).unwrap();
+    /// ```
+    fn is_same_file_as(&self, other: &Path) -> io::Result<bool>;
+
+    /// 返回字符设备或块设备的主、次设备号（st_rdev），跟随符号链接
+    /// 不是设备文件时返回 None。
+    ///
+    /// ```
+    /// # use std::path::Path;
+    /// # use path_permission::PathPermission;
+    /// let null = Path::new("/dev/null");
+    /// if null.exists() {
+    ///     let numbers = null.device_numbers().unwrap();
+    ///     assert!(numbers.is_some());
+    ///     // Linux 上为 1, 3
+    ///     if cfg!(target_os = "linux") {
+    ///         assert_eq!(numbers, Some((1, 3)));
+    ///     }
+    /// }
+    /// assert_eq!(Path::new("/").device_numbers().unwrap(), None);
+    /// ```
+    fn device_numbers(&self) -> io::Result<Option<(u32, u32)>>;
+
+    /// 返回脚本 #! 行中解释器的路径，不是脚本（不以 "#!" 开头）时返回 None
+    /// 只读取第一行的前 256 个字节，容许 "#!" 后的空白及 CRLF 换行，忽略解释器之后的参数。
+    fn script_interpreter(&self) -> io::Result<Option<PathBuf>>;
+
+    /// 判断路径能否被 execve 运行：需满足 is_executable_file，且为非空的原生程序，或
+    /// 其解释器同样满足 is_executable_file 的脚本
+    /// 注意：未检查 ELF 等格式，也未考虑 binfmt_misc。
+    ///
+    /// ```
+    /// # use std::fs;
+    /// # use path_permission::{Mode, PathPermission};
+    /// # let dir = std::env::temp_dir().join(format!("pp-runnable-{}", std::process::id()));
+    /// # fs::create_dir_all(&dir).unwrap();
+    /// let interpreter = dir.join("interpreter");
+    /// fs::write(&interpreter, b"\x7fELF").unwrap();
+    /// interpreter.set_mode(Mode::new(0o755)).unwrap();
+    /// let noexec = interpreter.is_on_noexec_mount().unwrap();
+    /// assert_eq!(interpreter.script_interpreter().unwrap(), None);
+    /// assert_eq!(interpreter.is_runnable().unwrap(), ! noexec);
+    ///
+    /// let script = dir.join("script");
+    /// fs::write(&script, format!("#! {} -e\r\necho\n", interpreter.display())).unwrap();
+    /// script.set_mode(Mode::new(0o755)).unwrap();
+    /// assert_eq!(script.script_interpreter().unwrap(), Some(interpreter.clone()));
+    /// assert_eq!(script.is_runnable().unwrap(), ! noexec);
+    ///
+    /// // 解释器不存在
+    /// fs::write(&script, format!("#!{}\n", dir.join("missing").display())).unwrap();
+    /// assert!(! script.is_runnable().unwrap());
+    ///
+    /// // 有执行位的空文件
+    /// fs::write(&script, b"").unwrap();
+    /// assert!(! script.is_runnable().unwrap());
+    /// # fs::remove_dir_all(&dir).unwrap();
+    /// ```
+    fn is_runnable(&self) -> io::Result<bool>;
+
+    /// 判断能否以追加模式打开此路径写入，同 is_appendable
+    /// 对普通文件而言与 is_writable 相同；在 macOS 上，设置了 uappnd/sappnd 标志的文件
+    /// 仍可追加写入（但不能截断，见 is_truncatable），设置了 uchg/schg 标志的则不能。
+    ///
+    /// ```
+    /// # use std::fs;
+    /// # use path_permission::{Mode, PathPermission};
+    /// # let path = std::env::temp_dir().join(format!("pp-can-append-{}", std::process::id()));
+    /// fs::write(&path, b"").unwrap();
+    /// path.set_mode(Mode::new(0o644)).unwrap();
+    /// assert!(path.can_append().unwrap());
+    /// assert_eq!(path.can_append().unwrap(), path.is_writable().unwrap());
+    /// # fs::remove_file(&path).unwrap();
+    /// ```
+    fn can_append(&self) -> io::Result<bool>;
+
+    /// 判断能否列出目录中的条目名称（读权限），同 is_listable
+    /// 路径不是目录时返回 Ok(false)：对普通文件而言读权限表示读取内容，而非列出，
+    /// 以 is_readable 判断目录则容易混淆这两种能力。
+    ///
+    /// ```
+    /// # use std::fs;
+    /// # use path_permission::{Mode, PathPermission};
+    /// # let dir = std::env::temp_dir().join(format!("pp-can-list-{}", std::process::id()));
+    /// fs::create_dir_all(&dir).unwrap();
+    /// fs::write(dir.join("file"), b"").unwrap();
+    /// assert!(! dir.join("file").can_list().unwrap());
+    /// assert!(! dir.join("file").can_enter().unwrap());
+    ///
+    /// // 只有执行权限：可以访问已知名称的条目，但无法列出（root 可绕过读权限检查）
+    /// dir.set_mode(Mode::new(0o100)).unwrap();
+    /// assert!(dir.can_enter().unwrap());
+    /// if unsafe { libc::geteuid() } != 0 {
+    ///     assert!(! dir.can_list().unwrap());
+    /// }
+    /// # dir.set_mode(Mode::new(0o755)).unwrap();
+    /// # fs::remove_dir_all(&dir).unwrap();
+    /// ```
+    fn can_list(&self) -> io::Result<bool>;
+
+    /// 判断能否进入目录、访问其中的条目（执行权限），同 is_traversable
+    /// 路径不是目录时返回 Ok(false)：对普通文件而言执行权限表示运行，而非进入。
+    fn can_enter(&self) -> io::Result<bool>;
+
+    /// 判断当前进程能否变更路径的权限（跟随符号链接），不会实际调用 chmod
+    /// 规则同内核：有效 uid 为文件所有者，或拥有 CAP_FOWNER（非 Linux 上为 root）时
+    /// 返回 true。未考虑只读挂载、不可变（immutable）属性等导致 chmod 失败的情况。
+    ///
+    /// ```
+    /// # use std::{fs, path::Path};
+    /// # use path_permission::PathPermission;
+    /// # let path = std::env::temp_dir().join(format!("pp-can-chmod-{}", std::process::id()));
+    /// fs::write(&path, b"").unwrap();
+    /// assert!(path.can_chmod().unwrap());
+    /// if unsafe { libc::geteuid() } != 0 {
+    ///     assert!(! Path::new("/").can_chmod().unwrap());
+    /// }
+    /// # fs::remove_file(&path).unwrap();
+    /// ```
+    fn can_chmod(&self) -> io::Result<bool>;
+
+    /// 判断路径是否为只有当前用户可访问的机密文件（如 SSH 私钥），跟随符号链接
+    /// 即为普通文件，所有者为当前进程的有效 uid 或 root，且 mode & 0o077 == 0。
+    /// 允许所属组可读、拒绝符号链接等见 PrivateOptions。
+    fn is_private(&self) -> io::Result<bool>;
+
+    /// 同 is_private，不通过时返回 PermissionDenied 错误，说明原因，如多余的权限位
+    fn ensure_private(&self) -> io::Result<()>;
+
+    /// 检查路径本身及其各级父级目录是否可信，规则同 sshd 的 StrictModes
+    /// 先解析为绝对路径（同 realpath），再检查至根目录的每一级：所有者须为 root 或当前
+    /// 进程的有效 uid，且所属组及其他用户不可写。设置了 S_ISVTX 的目录（如 /tmp）可写
+    /// 也视为可信，但当前用户的主目录除外：与 OpenSSH 一致，0755 的主目录可信，0775 的
+    /// 不可信。机密文件位于他人可写的目录中时，可能被重命名后替换，见 is_private。
+    ///
+    /// ```
+    /// # use std::fs;
+    /// # use path_permission::{Mode, PathPermission};
+    /// # let dir = std::env::temp_dir().join(format!("pp-trusted-{}", std::process::id()));
+    /// # fs::create_dir_all(dir.join("config")).unwrap();
+    /// let config = dir.join("config");
+    /// dir.set_mode(Mode::new(0o755)).unwrap();
+    /// config.set_mode(Mode::new(0o700)).unwrap();
+    /// let trusted = std::env::temp_dir().ancestors_are_trusted().unwrap().is_trusted();
+    /// assert_eq!(config.ancestors_are_trusted().unwrap().is_trusted(), trusted);
+    ///
+    /// dir.set_mode(Mode::new(0o775)).unwrap();
+    /// let report = config.ancestors_are_trusted().unwrap();
+    /// let untrusted = &report.untrusted()[0];
+    /// assert_eq!((untrusted.mode, untrusted.writable_by_others, untrusted.bad_owner),
+    ///            (Mode::new(0o775), true, false));
+    /// assert!(report.to_string().contains("mode 0775"));
+    ///
+    /// // 设置了 S_ISVTX
+    /// dir.set_mode(Mode::new(0o1777)).unwrap();
+    /// assert_eq!(config.ancestors_are_trusted().unwrap().is_trusted(), trusted);
+    /// # fs::remove_dir_all(&dir).unwrap();
+    /// ```
+    fn ancestors_are_trusted(&self) -> io::Result<TrustReport>;
+
+    /// 判断目录能否安全地写入临时文件，跟随符号链接
+    /// 即为当前进程可进入及写入的目录，所有者为当前进程的有效 uid 或 root，且所属组或
+    /// 其他用户可写时设置了 S_ISVTX。需同时检查 noexec 挂载时见 TempDirOptions。
+    fn is_secure_tempdir(&self) -> io::Result<bool>;
+
+    /// 尝试以只读方式打开，无权限（EACCES、EPERM）时返回 Ok(None)，其它错误返回 Err
+    /// 对安全敏感的代码，应使用此方法代替先 is_readable 再打开的做法：检查与打开之间，
+    /// 路径可能已被替换。打开时使用 O_NONBLOCK，不会在 FIFO 上阻塞，打开后即还原。
+    ///
+    /// ```
+    /// # use std::{fs, io::Read};
+    /// # use path_permission::{Mode, PathPermission};
+    /// # let path = std::env::temp_dir().join(format!("pp-open-if-{}", std::process::id()));
+    /// fs::write(&path, b"content").unwrap();
+    /// let mut content = String::new();
+    /// path.open_if_readable().unwrap().unwrap().read_to_string(&mut content).unwrap();
+    /// assert_eq!(content, "content");
+    ///
+    /// // root 可绕过读权限的检查
+    /// if unsafe { libc::geteuid() } != 0 {
+    ///     path.set_mode(Mode::new(0o200)).unwrap();
+    ///     assert!(path.open_if_readable().unwrap().is_none());
+    /// }
+    /// # path.set_mode(Mode::new(0o600)).unwrap();
+    /// let mut opts = fs::OpenOptions::new();
+    /// opts.append(true);
+    /// assert!(path.open_if_writable(&opts).unwrap().is_some());
+    ///
+    /// let link = path.with_extension("link");
+    /// std::os::unix::fs::symlink(&path, &link).unwrap();
+    /// assert!(link.open_if_readable().unwrap().is_some());
+    /// assert!(link.open_if_readable_nofollow().is_err());
+    /// # fs::remove_file(&link).unwrap();
+    /// # fs::remove_file(&path).unwrap();
+    /// assert!(path.open_if_readable().is_err());
+    /// ```
+    fn open_if_readable(&self) -> io::Result<Option<File>>;
+
+    /// 同 open_if_readable，但使用 O_NOFOLLOW，路径为符号链接时返回 Err（ELOOP）
+    /// 适用于以特权运行、不应跟随其他用户所创建的符号链接的场景。
+    fn open_if_readable_nofollow(&self) -> io::Result<Option<File>>;
+
+    /// 以 opts（需包含写或追加）打开，无权限（EACCES、EPERM）时返回 Ok(None)，其它错误返回 Err
+    /// 用于代替先 is_writable 再打开的做法，同样使用 O_NONBLOCK 避免阻塞。
+    /// 注意：opts 中通过 custom_flags 设置的标志会被覆盖。
+    fn open_if_writable(&self, opts: &OpenOptions) -> io::Result<Option<File>>;
+
+    /// 同 open_if_writable，但使用 O_NOFOLLOW，路径为符号链接时返回 Err（ELOOP）
+    fn open_if_writable_nofollow(&self, opts: &OpenOptions) -> io::Result<Option<File>>;
+
+    /// 从根目录开始，依次列出路径的每一级及当前进程能否访问，用于找出阻碍访问的目录
+    /// 父级目录为是否有执行（搜索）权限；路径本身为目录时同样为执行权限，否则为读权限。
+    /// 相对路径以当前目录为基准，遇到第一个不存在的路径时停止（不包括它）。
+    ///
+    /// ```
+    /// # use std::{fs, path::PathBuf};
+    /// # use path_permission::{Mode, PathPermission};
+    /// # let dir = std::env::temp_dir().join(format!("pp-ancestor-{}", std::process::id()));
+    /// let file = dir.join("locked/file");
+    /// fs::create_dir_all(file.parent().unwrap()).unwrap();
+    /// fs::write(&file, b"").unwrap();
+    ///
+    /// let access = file.ancestor_access().unwrap();
+    /// assert_eq!(access.first().unwrap().0, PathBuf::from("/"));
+    /// assert_eq!(access.last().unwrap(), &(file.clone(), true));
+    /// assert!(dir.join("locked/missing/file").ancestor_access().unwrap()
+    ///     .ends_with(&[(dir.join("locked"), true)]));
+    ///
+    /// // root 可绕过目录的执行权限检查
+    /// if unsafe { libc::geteuid() } != 0 {
+    ///     dir.join("locked").set_mode(Mode::new(0o600)).unwrap();
+    ///     let access = file.ancestor_access().unwrap();
+    ///     assert!(access.ends_with(&[(dir.clone(), true),
+    ///                                (dir.join("locked"), false),
+    ///                                (file.clone(), false)]));
+    ///     dir.join("locked").set_mode(Mode::new(0o755)).unwrap();
+    /// }
+    /// # fs::remove_dir_all(&dir).unwrap();
+    /// ```
+    fn ancestor_access(&self) -> io::Result<Vec<(PathBuf, bool)>>;
+
+    /// 创建文件，并将其权限设置为 mode（不受 umask 影响），不存在权限过宽的时间窗口
+    /// overwrite 为 false 时，路径已存在则返回 AlreadyExists 错误；为 true 时，将截断
+    /// 已存在的文件。不会跟随路径上的符号链接。设置权限失败时，会删除刚创建的文件。
+    ///
+    /// ```
+    /// # use std::{fs, io::{ErrorKind, Write}};
+    /// # use path_permission::{with_umask, PathPermission};
+    /// # let path = std::env::temp_dir().join(format!("pp-create-mode-{}", std::process::id()));
+    /// with_umask(0o077, || {
+    ///     path.create_with_mode(0o640, false).unwrap().write_all(b"secret").unwrap();
+    /// });
+    /// assert_eq!(path.get_mode().unwrap(), 0o640);
+    /// assert_eq!(path.create_with_mode(0o600, false).unwrap_err().kind(), ErrorKind::AlreadyExists);
+    ///
+    /// path.create_with_mode(0o600, true).unwrap();
+    /// assert_eq!(path.get_mode().unwrap(), 0o600);
+    /// assert_eq!(fs::read(&path).unwrap(), b"");
+    ///
+    /// // 不跟随符号链接
+    /// let link = path.with_extension("link");
+    /// std::os::unix::fs::symlink(&path, &link).unwrap();
+    /// assert!(link.create_with_mode(0o644, true).is_err());
+    /// assert_eq!(path.get_mode().unwrap(), 0o600);
+    /// # fs::remove_file(&link).unwrap();
+    /// # fs::remove_file(&path).unwrap();
+    /// ```
+    fn create_with_mode(&self, mode: u16, overwrite: bool) -> io::Result<File>;
+
+    /// 变更路径本身的权限，不跟随符号链接
+    ///
+    /// | 系统                    | 行为                                   |
+    /// |-------------------------|----------------------------------------|
+    /// | macOS、FreeBSD、NetBSD  | 调用 lchmod(2)，可变更符号链接的权限   |
+    /// | Linux 及其它系统        | 符号链接的权限无法变更，返回 Unsupported 错误 |
+    ///
+    /// 对非符号链接的路径，与 chmod 相同（但在不支持的系统上同样返回错误）。
+    ///
+    /// ```
+    /// # use std::{fs, io::ErrorKind, os::unix::fs::symlink};
+    /// # use path_permission::{Mode, PathPermission};
+    /// # let path = std::env::temp_dir().join(format!("pp-lchmod-{}", std::process::id()));
+    /// fs::write(&path, b"").unwrap();
+    /// path.set_mode(Mode::new(0o644)).unwrap();
+    /// let link = path.with_extension("link");
+    /// symlink(&path, &link).unwrap();
+    ///
+    /// match link.lchmod(0o700) {
+    ///     Ok(changed) => assert!(changed),
+    ///     Err(err) => assert_eq!(err.kind(), ErrorKind::Unsupported),
+    /// }
+    /// // 链接目标不受影响
+    /// assert_eq!(path.get_mode().unwrap(), 0o644);
+    /// # fs::remove_file(&link).unwrap();
+    /// # fs::remove_file(&path).unwrap();
+    /// ```
+    fn lchmod(&self, mode: u16) -> io::Result<bool>;
+
+    /// 创建目录，并将其权限设置为 mode（不受 umask 影响，保留 S_ISGID 等位）
+    /// 设置权限失败时，会删除刚创建的目录。
+    fn create_dir_with_mode(&self, mode: u16) -> io::Result<()>;
+
+    /// 递归创建目录，路径本身的权限设置为 mode，中间目录的权限设置为 intermediate_mode；
+    /// intermediate_mode 为 None 时，中间目录以 0o755 创建并受 umask 影响
+    /// 已存在的目录（包括其它进程同时创建的）不受影响。返回实际创建的目录，由浅至深，
+    /// 以便之后的操作失败时删除。任一目录创建或设置权限失败时，先删除本次已创建的目录，
+    /// 再返回错误。
+    ///
+    /// ```
+    /// # use std::fs;
+    /// # use path_permission::{with_umask, PathPermission};
+    /// # let root = std::env::temp_dir().join(format!("pp-mkdir-mode-{}", std::process::id()));
+    /// # fs::create_dir_all(&root).unwrap();
+    /// let dir = root.join("a/b/c");
+    /// let created = with_umask(0o077, || {
+    ///     dir.create_dir_all_with_mode(0o2750, Some(0o711)).unwrap()
+    /// });
+    /// assert_eq!(created, vec![root.join("a"), root.join("a/b"), dir.clone()]);
+    /// assert_eq!(dir.get_mode().unwrap(), 0o2750);
+    /// assert_eq!(root.join("a/b").get_mode().unwrap(), 0o711);
+    ///
+    /// // 已存在
+    /// assert!(dir.create_dir_all_with_mode(0o700, None).unwrap().is_empty());
+    /// assert_eq!(dir.get_mode().unwrap(), 0o2750);
+    ///
+    /// with_umask(0o022, || root.join("a/d").create_dir_with_mode(0o770).unwrap());
+    /// assert_eq!(root.join("a/d").get_mode().unwrap(), 0o770);
+    /// assert!(root.join("a/d").create_dir_with_mode(0o770).is_err());
+    /// # fs::remove_dir_all(&root).unwrap();
+    /// ```
+    ///
+    /// ```
+    /// # use std::fs;
+    /// # use path_permission::PathPermission;
+    /// # let root = std::env::temp_dir().join(format!("pp-mkdir-rollback-{}", std::process::id()));
+    /// # fs::create_dir_all(&root).unwrap();
+    /// # #[cfg(all(target_os = "linux", target_arch = "x86_64"))]
+    /// # {
+    /// // 以 seccomp 令 chmod(2)、fchmodat(2) 返回 EPERM，模拟路径本身设置权限失败
+    /// # let filter = [
+    /// #     // 载入系统调用号（seccomp_data.nr）
+    /// #     libc::sock_filter { code: 0x20, jt: 0, jf: 0, k: 0 },
+    /// #     libc::sock_filter { code: 0x15, jt: 2, jf: 0, k: libc::SYS_chmod as u32 },
+    /// #     libc::sock_filter { code: 0x15, jt: 1, jf: 0, k: libc::SYS_fchmodat as u32 },
+    /// #     // SECCOMP_RET_ALLOW
+    /// #     libc::sock_filter { code: 0x06, jt: 0, jf: 0, k: 0x7fff_0000 },
+    /// #     // SECCOMP_RET_ERRNO | EPERM
+    /// #     libc::sock_filter { code: 0x06, jt: 0, jf: 0, k: 0x0005_0000 | libc::EPERM as u32 },
+    /// # ];
+    /// # let prog = libc::sock_fprog { len: filter.len() as u16, filter: filter.as_ptr() as *mut _ };
+    /// # unsafe {
+    /// #     assert_eq!(libc::prctl(libc::PR_SET_NO_NEW_PRIVS, 1, 0, 0, 0), 0);
+    /// #     assert_eq!(libc::prctl(libc::PR_SET_SECCOMP, 2, &prog as *const libc::sock_fprog), 0);
+    /// # }
+    /// // 中间目录未指定权限，无需 chmod，只有 a/b/c 的 chmod 失败
+    /// let err = root.join("a/b/c").create_dir_all_with_mode(0o750, None).unwrap_err();
+    /// assert_eq!(err.raw_os_error(), Some(libc::EPERM));
+    /// // 已创建的 a、a/b 及 a/b/c 均被删除
+    /// assert!(! root.join("a").exists());
+    /// # }
+    /// # fs::remove_dir_all(&root).unwrap();
+    /// ```
+    fn create_dir_all_with_mode(&self, mode: u16, intermediate_mode: Option<u16>) -> io::Result<Vec<PathBuf>>;
+
+    /// 获取一次路径的 stat，返回可回答多个权限问题的 PermissionInspector
+    /// 之后的查询基于此时的快照，详见 PermissionInspector。
+    fn inspect(&self) -> io::Result<PermissionInspector>;
+
+    /// 以一次 stat 获取路径的类型、权限、所有者、大小及硬链接数，跟随符号链接
+    /// 所有者、所属组的名称在首次需要时查询。详见 PathDescription。
+    fn describe(&self) -> io::Result<PathDescription>;
+
+    /// 与 describe 相同，但不跟随符号链接
+    ///
+    /// ```
+    /// # use std::{fs, os::unix::fs::symlink};
+    /// # use path_permission::PathPermission;
+    /// # let dir = std::env::temp_dir().join(format!("pp-describe-nofollow-{}", std::process::id()));
+    /// # fs::create_dir_all(&dir).unwrap();
+    /// symlink(&dir, dir.join("link")).unwrap();
+    /// assert_eq!(dir.join("link").describe().unwrap().type_char(), 'd');
+    /// let desc = dir.join("link").describe_nofollow().unwrap();
+    /// assert_eq!(desc.type_char(), 'l');
+    /// assert_eq!(desc.file_mode().to_string(), dir.join("link").long_format_nofollow().unwrap());
+    /// # fs::remove_dir_all(&dir).unwrap();
+    /// ```
+    fn describe_nofollow(&self) -> io::Result<PathDescription>;
+
+    /// 判断两个路径的权限（0o7777，包括 S_ISUID 等）是否相同，跟随符号链接
+    ///
+    /// ```
+    /// # use std::fs;
+    /// # use path_permission::{Mode, PathPermission};
+    /// # let dir = std::env::temp_dir().join(format!("pp-same-mode-{}", std::process::id()));
+    /// # fs::create_dir_all(&dir).unwrap();
+    /// let (a, b) = (dir.join("a"), dir.join("b"));
+    /// fs::write(&a, b"").unwrap();
+    /// fs::write(&b, b"").unwrap();
+    /// a.set_mode(Mode::new(0o750)).unwrap();
+    /// b.set_mode(Mode::new(0o750)).unwrap();
+    /// assert!(a.same_mode_as(&b).unwrap());
+    /// assert!(a.same_permissions_as(&b).unwrap());
+    ///
+    /// // 只有特殊位不同
+    /// b.set_mode(Mode::new(0o4750)).unwrap();
+    /// assert!(! a.same_mode_as(&b).unwrap());
+    /// # fs::remove_dir_all(&dir).unwrap();
+    /// ```
+    fn same_mode_as(&self, other: &Path) -> io::Result<bool>;
+
+    /// 判断两个路径的权限及所有者、所属组是否均相同，跟随符号链接
+    fn same_permissions_as(&self, other: &Path) -> io::Result<bool>;
+
+    /// 判断两个路径的所有者是否相同，跟随符号链接，用法见 OwnershipAspect
+    /// 任一路径无法获取 stat 时返回错误（而非 false），错误信息中包含该路径。
+    fn same_owner_as(&self, other: &Path) -> io::Result<bool>;
+
+    /// 同 same_owner_as，不跟随符号链接
+    fn same_owner_as_nofollow(&self, other: &Path) -> io::Result<bool>;
+
+    /// 判断两个路径的所属组是否相同，跟随符号链接，错误同 same_owner_as
+    fn same_group_as(&self, other: &Path) -> io::Result<bool>;
+
+    /// 同 same_group_as，不跟随符号链接
+    fn same_group_as_nofollow(&self, other: &Path) -> io::Result<bool>;
+
+    /// 判断两个路径在 aspect 中的各方面（所有者、所属组）是否均相同，跟随符号链接
+    /// 两个路径各只获取一次 stat；错误同 same_owner_as。
+    fn ownership_matches(&self, other: &Path, aspect: OwnershipAspect) -> io::Result<bool>;
+
+    /// 同 ownership_matches，不跟随符号链接
+    fn ownership_matches_nofollow(&self, other: &Path, aspect: OwnershipAspect) -> io::Result<bool>;
+
+    /// 以 chmod(1) 所接受的形式变更权限：八进制（如 "0644"、"755"），或符号形式
+    /// （如 "u+rwx,go-w"，语法见 Mode::apply_symbolic，省略 ugoa 时受当前 umask 影响）
+    /// 无法解析时返回 InvalidInput 错误。
+    ///
+    /// ```
+    /// # use std::fs;
+    /// # use path_permission::{Mode, PathPermission};
+    /// # let dir = std::env::temp_dir().join(format!("pp-chmod-str-{}", std::process::id()));
+    /// # fs::create_dir_all(&dir).unwrap();
+    /// let (a, b) = (dir.join("a"), dir.join("b"));
+    /// fs::write(&a, b"").unwrap();
+    /// fs::write(&b, b"").unwrap();
+    /// b.set_mode(Mode::new(0o644)).unwrap();
+    ///
+    /// a.chmod_str("0750").unwrap();
+    /// b.chmod_str("u+x,g+x,o-r").unwrap();
+    /// assert_eq!(a.get_mode().unwrap(), 0o750);
+    /// assert!(a.same_mode_as(&b).unwrap());
+    ///
+    /// b.chmod_str("755").unwrap();
+    /// assert_eq!(b.get_mode().unwrap(), 0o755);
+    /// assert_eq!(b.chmod_str("u+z").unwrap_err().kind(), std::io::ErrorKind::InvalidInput);
+    /// assert_eq!(b.chmod_str("9").unwrap_err().kind(), std::io::ErrorKind::InvalidInput);
+    /// # fs::remove_dir_all(&dir).unwrap();
+    /// ```
+    fn chmod_str(&self, spec: &str) -> io::Result<bool>;
+
+    /// 在当前权限的基础上增加 bits 中的位，返回变更后的权限，即 `chmod +x`
+    /// 未指明的位保持不变，包括 S_ISUID、S_ISGID、S_ISVTX；权限已包含 bits 时不调用 chmod。
+    /// 普通文件及目录通过已打开的文件描述符获取及变更权限，避免读取与写入之间路径被替换。
+    /// 设备文件、FIFO、套接字等不会被打开（打开设备会触发驱动的副作用），无读权限而
+    /// 无法打开的文件（如 0o000、只写的文件）同样如此，均按路径获取及变更：读取与写入
+    /// 之间路径仍可能被替换。
+    ///
+    /// ```
+    /// # use std::fs;
+    /// # use path_permission::{Mode, PathPermission};
+    /// # let path = std::env::temp_dir().join(format!("pp-add-mode-{}", std::process::id()));
+    /// # fs::write(&path, b"").unwrap();
+    /// path.set_mode(Mode::new(0o2640)).unwrap();
+    /// assert_eq!(path.add_mode(0o111).unwrap(), 0o2751);
+    /// assert_eq!(path.add_mode(0o100).unwrap(), 0o2751);
+    /// assert_eq!(path.remove_mode(0o2011).unwrap(), 0o740);
+    /// assert_eq!(path.get_mode().unwrap(), 0o740);
+    /// # fs::remove_file(&path).unwrap();
+    ///
+    /// // 套接字无法打开，按路径变更
+    /// let socket = path.with_extension("sock");
+    /// let _listener = std::os::unix::net::UnixListener::bind(&socket).unwrap();
+    /// socket.set_mode(Mode::new(0o600)).unwrap();
+    /// assert_eq!(socket.add_mode(0o060).unwrap(), 0o660);
+    /// assert_eq!(socket.remove_mode(0o600).unwrap(), 0o060);
+    /// # fs::remove_file(&socket).unwrap();
+    /// ```
+    fn add_mode(&self, bits: u16) -> io::Result<u16>;
+
+    /// 在当前权限的基础上去除 bits 中的位，返回变更后的权限，即 `chmod -w`
+    /// 与 add_mode 相同，未指明的位保持不变。
+    fn remove_mode(&self, bits: u16) -> io::Result<u16>;
 }
 
 impl PathPermission for Path {
@@ -54,165 +2299,1647 @@ impl PathPermission for Path {
     }
 
     fn is_writable(&self) -> io::Result<bool> {
+        // uchg/schg 标志会阻止写入，与权限位无关
+        #[cfg(target_os = "macos")]
+        {
+            if self.exists() && self.bsd_flags()?.is_immutable() {
+                return Ok(false)
+            }
+        }
         self.access(libc::W_OK)
     }
 
     fn is_excutable(&self) -> io::Result<bool> {
+        // 部分系统上，root 对没有任何执行位的文件，access(X_OK) 也会成功，
+        // 但实际上至少需要有一个执行位才能执行
+        if unsafe { libc::geteuid() } == 0 {
+            let stat = stat::stat(self, true)?;
+            if ! stat.is_dir() && stat.perm() & modes::ALL_X == 0 {
+                return Ok(false)
+            }
+        }
         self.access(libc::X_OK)
     }
 
     fn is_creatable(&self) -> io::Result<bool> {
-        let parent = match self.parent() {
-            // 此时已无父级目录，则此路径为相对路径，其起始位置为当前目录。
-            // 不建议使用相对路径
-            None => Path::new("./"),
-            Some(parent) => parent,
-        };
-        if ! parent.exists() {
-            parent.is_creatable()
-        } else {
-            // parent 一定存在，可直接使用unwrap()获取结果
-            // 需要对父级目录有写和读的权限（1 + 2 = 3)
-            parent.access(libc::X_OK + libc::W_OK)
-        }
+        rules::is_creatable(&rules::RealFs, self)
     }
 
     fn is_removable(&self) -> io::Result<bool> {
-        // 文件不存在时，返回Ok(false)
-        if ! self.exists() {
-            return Ok(false)
-        }
-        let parent = match self.parent() {
-            None => Path::new("./"),
-            Some(parent) => parent,
-        };
+        rules::is_removable(&rules::RealFs, self, true)
+    }
 
-        // 如果父级目录没有设置 S_ISVTX
-        // 需要对父级目录有写和读的权限（1 + 2 = 3)
-        if ! parent.check_access(0o1000).unwrap() {
-            parent.access(libc::X_OK + libc::W_OK)
-        } else {
-            // 需进行是否为本用户所属文件判断
-            unsafe {
-                if libc::getuid() == self.metadata().unwrap().uid() {
-                    parent.access(libc::X_OK + libc::W_OK)
-                } else {
-                    Ok(false)
-                }
-            }
-        }
+    fn is_removable_nofollow(&self) -> io::Result<bool> {
+        rules::is_removable(&rules::RealFs, self, false)
     }
 
     fn check_access(&self, mode: u16) -> io::Result<bool> {
-        if let Ok(metadata) = self.metadata() {
-            if metadata.mode() as u16 & mode == mode {
-                Ok(true)
-            } else {
-                Ok(false)
-            }
-        } else {
-            Err(io::Error::last_os_error())
-        }
+        self.check_mode(Mode::new(mode))
+    }
+
+    fn check_mode(&self, mode: Mode) -> io::Result<bool> {
+        Ok(Mode::new(stat::stat(self, true)?.perm()).contains(mode))
     }
 
     fn get_access(&self) -> io::Result<String> {
-        if let Ok(metadata) = self.metadata() {
-            Ok(format!("{:o}{:o}",
-                       metadata.mode() as u16 & 0o7000,
-                       metadata.mode() as u16 & 0o777))
-        } else {
-            Err(io::Error::last_os_error())
-        }
+        Ok(format!("{:04o}", stat::stat(self, true)?.perm()))
+    }
+
+    fn get_access_symbolic(&self) -> io::Result<String> {
+        Ok(Mode::new(stat::stat(self, true)?.perm()).symbolic())
     }
 
     fn chmod(&self, mode: u16) -> io::Result<bool> {
-        chmod(self, mode)
+        self.set_mode(Mode::new(mode))
     }
-}
 
-impl PathPermission for PathBuf {
-    fn access(&self, amode: c_int) -> io::Result<bool> {
-        self.as_path().access(amode)
+    fn set_mode(&self, mode: Mode) -> io::Result<bool> {
+        if stat::stat(self, true)?.perm() == mode.bits() {
+            return Ok(false)
+        }
+        chmod(self, libc::mode_t::from(mode.bits()))?;
+        Ok(true)
     }
 
-    fn is_readable(&self) -> io::Result<bool> {
-        self.as_path().is_readable()
+    fn chmod_preserving_special(&self, mode: u16) -> io::Result<bool> {
+        let special = stat::stat(self, true)?.perm() & modes::SPECIAL;
+        self.set_mode(Mode::new(mode | special))
     }
 
-    fn is_writable(&self) -> io::Result<bool> {
-        self.as_path().is_writable()
+    fn set_permissions_via_std(&self, mode: Mode) -> io::Result<()> {
+        std::fs::set_permissions(self, mode.to_fs_permissions())
     }
 
-    fn is_excutable(&self) -> io::Result<bool> {
-        self.as_path().is_excutable()
+    fn can_rename(&self, to: &Path) -> io::Result<bool> {
+        Ok(self.is_renamable_to(to)?.is_allowed())
     }
 
-    fn is_creatable(&self) -> io::Result<bool> {
-        self.as_path().is_creatable()
+    #[cfg(target_os = "macos")]
+    fn bsd_flags(&self) -> io::Result<BsdFlags> {
+        darwin::bsd_flags(self)
     }
 
-    fn is_removable(&self) -> io::Result<bool> {
-        self.as_path().is_removable()
+    #[cfg(target_os = "macos")]
+    fn set_bsd_flags(&self, flags: BsdFlags) -> io::Result<bool> {
+        darwin::set_bsd_flags(self, flags)
     }
 
-    fn check_access(&self, mode: u16) -> io::Result<bool> {
-        self.as_path().check_access(mode)
+    fn predicted_create_mode(&self, requested: u16) -> io::Result<u16> {
+        if ! self.is_creatable()? {
+            return Err(io::Error::from(io::ErrorKind::PermissionDenied))
+        }
+        Ok(requested & ! current_umask() as u16)
     }
 
-    fn get_access(&self) -> io::Result<String> {
-        self.as_path().get_access()
+    #[cfg(all(target_os = "linux", feature = "selinux"))]
+    fn selinux_context(&self) -> io::Result<Option<String>> {
+        selinux::selinux_context(self)
     }
 
-    fn chmod(&self, mode: u16) -> io::Result<bool> {
-        self.as_path().chmod(mode)
+    #[cfg(all(target_os = "linux", feature = "selinux"))]
+    fn set_selinux_context(&self, context: &str) -> io::Result<bool> {
+        selinux::set_selinux_context(self, context)
     }
-}
 
-fn access(path: &Path, mod_mask: c_int) ->io::Result<bool> {
-    let mut buf = Vec::new();
-    let buf_ptr;
+    fn access_for(&self, amode: AccessMode) -> io::Result<bool> {
+        let metadata = self.metadata()?;
+        let uid = unsafe { libc::geteuid() };
+        let gids = compute::current_gids()?;
+        Ok(compute::mode_allows(&metadata, uid, &gids, amode,
+                                compute::Privilege::current()))
+    }
 
-    // 在C中，char的最后一位是'\0'或ASCII码值为0
-    buf.extend(path.as_os_str().as_bytes());
-    buf.push(0);
+    fn is_accessible_by(&self, uid: u32, gid: u32, amode: AccessMode) -> io::Result<bool> {
+        self.is_accessible_by_groups(uid, &[gid], amode)
+    }
 
-    buf_ptr = buf.as_ptr() as *const libc::c_char;
+    fn access_detailed(&self, amode: c_int) -> io::Result<AccessResult> {
+        access_detailed(self, amode)
+    }
 
-    let result = unsafe {
-        libc::access(buf_ptr, mod_mask)
-    };
+    fn snapshot(&self) -> io::Result<PermSnapshot> {
+        Ok(PermSnapshot::from_stat(&stat::stat(self, true)?))
+    }
 
-    match result {
-        0 => Ok(true),
-        _ => {
-            let err = io::Error::last_os_error();
-            if err.raw_os_error().unwrap() == libc::EACCES {
-                Ok(false)  // 无查看此路径的权限（无法确认路径是否存在）
-            } else {
-                Err(err)  // 其它错误，如路径不存在等
-            }
+    fn restore(&self, snap: &PermSnapshot) -> io::Result<()> {
+        // chown 会清除 S_ISUID、S_ISGID，需先于 chmod
+        let stat = stat::stat(self, true)?;
+        if stat.uid != snap.uid || stat.gid != snap.gid {
+            chown(self, snap.uid, snap.gid)?;
         }
+        chmod(self, libc::mode_t::from(snap.mode))
     }
-}
 
-fn chmod(path: &Path, mode: u16) -> io::Result<bool> {
-    let mut buf = Vec::new();
-    let buf_ptr;
+    fn snapshot_tree(&self) -> io::Result<PermissionSnapshot> {
+        PermissionSnapshot::new(self)
+    }
 
-    // 在C中，char的最后一位是'\0'或ASCII码值为0
-    buf.extend(path.as_os_str().as_bytes());
-    buf.push(0);
+    #[cfg(all(target_os = "linux", feature = "watch"))]
+    fn watch_permissions(&self) -> io::Result<PermissionWatcher> {
+        PermissionWatcher::new(self)
+    }
 
-    buf_ptr = buf.as_ptr() as *const libc::c_char;
+    fn export_manifest(&self, writer: &mut dyn Write, format: ManifestFormat) -> io::Result<u64> {
+        manifest::export_manifest(self, writer, format)
+    }
 
-    let result = unsafe {
-        libc::chmod(buf_ptr, mode)
-    };
+    #[cfg(target_os = "linux")]
+    fn file_capabilities(&self) -> io::Result<Option<FileCaps>> {
+        file_caps::file_capabilities(self)
+    }
 
-    match result {
+    #[cfg(target_os = "linux")]
+    fn file_attributes(&self) -> io::Result<FileAttributes> {
+        attributes::file_attributes(self)
+    }
+
+    fn audit_tree(&self) -> io::Result<Vec<AuditEntry>> {
+        audit::audit_tree(self)
+    }
+
+    fn find_matching(&self, predicate: &dyn Fn(Mode) -> bool) -> io::Result<Vec<AuditEntry>> {
+        audit::find_matching(self, false, predicate)
+    }
+
+    fn find_setuid(&self) -> io::Result<Vec<PathBuf>> {
+        let found = audit::find_matching(self, true, &|mode| mode.bits() & modes::SETUID != 0)?;
+        Ok(found.into_iter().map(|entry| entry.path).collect())
+    }
+
+    fn find_setgid(&self) -> io::Result<Vec<PathBuf>> {
+        let found = audit::find_matching(self, true, &|mode| mode.bits() & modes::SETGID != 0)?;
+        Ok(found.into_iter().map(|entry| entry.path).collect())
+    }
+
+    fn chmod_recursive(&self, mode: u16) -> io::Result<usize> {
+        tree::chmod_recursive(self, mode)
+    }
+
+    fn chown_recursive(&self, uid: Option<u32>, gid: Option<u32>, keep_going: bool)
+        -> io::Result<ChownReport> {
+        tree::chown_recursive(self, uid, gid, keep_going)
+    }
+
+    #[cfg(feature = "rayon")]
+    fn par_audit_tree(&self) -> io::Result<Vec<AuditEntry>> {
+        audit::par_audit_tree(self)
+    }
+
+    #[cfg(feature = "rayon")]
+    fn par_chmod_recursive(&self, mode: u16) -> io::Result<usize> {
+        tree::par_chmod_recursive(self, mode)
+    }
+
+    fn predicted_dir_mode(&self, requested: u16) -> io::Result<u16> {
+        let mode = self.predicted_create_mode(requested)?;
+        let ancestor = self.ancestors()
+            .skip(1)
+            .find(|ancestor| ancestor.exists());
+        let inherit = match ancestor {
+            Some(ancestor) => stat::stat(ancestor, true)?.perm() & modes::SETGID != 0,
+            None => false,
+        };
+        Ok(if inherit { mode | modes::SETGID } else { mode })
+    }
+
+    fn is_readonly(&self) -> io::Result<bool> {
+        Ok(stat::stat(self, true)?.perm() & modes::ALL_W == 0)
+    }
+
+    fn set_readonly(&self, readonly: bool) -> io::Result<bool> {
+        let mode = Mode::new(stat::stat(self, true)?.perm());
+        if readonly {
+            self.set_mode(mode - Mode::new(modes::ALL_W))
+        } else {
+            self.set_mode(mode | Mode::OWNER_WRITE)
+        }
+    }
+
+    fn get_mode(&self) -> io::Result<u16> {
+        Ok(stat::stat(self, true)?.perm())
+    }
+
+    fn get_mode_nofollow(&self) -> io::Result<u16> {
+        Ok(stat::stat(self, false)?.perm())
+    }
+
+    fn long_format(&self) -> io::Result<String> {
+        Ok(FileMode::new(stat::stat(self, true)?.mode).to_string())
+    }
+
+    fn long_format_nofollow(&self) -> io::Result<String> {
+        Ok(FileMode::new(stat::stat(self, false)?.mode).to_string())
+    }
+
+    fn full_mode(&self) -> io::Result<u32> {
+        Ok(stat::stat(self, true)?.mode)
+    }
+
+    fn full_mode_nofollow(&self) -> io::Result<u32> {
+        Ok(stat::stat(self, false)?.mode)
+    }
+
+    fn file_kind(&self) -> io::Result<Option<FileKind>> {
+        Ok(stat::stat(self, true)?.kind())
+    }
+
+    fn file_kind_nofollow(&self) -> io::Result<Option<FileKind>> {
+        Ok(stat::stat(self, false)?.kind())
+    }
+
+    fn stat_summary(&self) -> io::Result<StatSummary> {
+        Ok(StatSummary::from_stat(&stat::stat(self, true)?))
+    }
+
+    fn stat_summary_nofollow(&self) -> io::Result<StatSummary> {
+        Ok(StatSummary::from_stat(&stat::stat(self, false)?))
+    }
+
+    fn is_creatable_exclusive(&self) -> io::Result<bool> {
+        // 不跟随符号链接，悬空的符号链接同样视为已存在
+        if self.symlink_metadata().is_ok() {
+            return Ok(false)
+        }
+        self.is_creatable()
+    }
+
+    fn creatable_with_parents(&self) -> io::Result<CreationPlan> {
+        let path = if self.is_relative() {
+            std::env::current_dir()?.join(self)
+        } else {
+            self.to_path_buf()
+        };
+
+        let mut missing = Vec::new();
+        let mut ancestor = path.parent();
+        while let Some(dir) = ancestor {
+            // 不跟随符号链接，悬空的符号链接同样视为已存在
+            if dir.symlink_metadata().is_ok() {
+                break
+            }
+            missing.push(dir.to_path_buf());
+            ancestor = dir.parent();
+        }
+        missing.reverse();
+
+        let ancestor = match ancestor {
+            Some(ancestor) => ancestor.to_path_buf(),
+            // 一直到根目录都不存在，只可能是根目录本身
+            None => return Ok(CreationPlan {
+                missing,
+                ancestor: PathBuf::from("/"),
+                creatable: false,
+            }),
+        };
+        let creatable = match ancestor.metadata() {
+            Ok(metadata) if metadata.is_dir() => ancestor.access(libc::X_OK + libc::W_OK)?,
+            _ => false,
+        };
+
+        Ok(CreationPlan { missing, ancestor, creatable })
+    }
+
+    fn diff_mode(&self, other: &Path) -> io::Result<ModeDiff> {
+        let from = Mode::new(self.get_mode()?);
+        let to = Mode::new(other.get_mode()?);
+        Ok(ModeDiff::between(from, to))
+    }
+
+    fn diff_access(&self, other: &Path) -> io::Result<Option<AccessDiff>> {
+        diff::diff_access(self, other)
+    }
+
+    fn is_removable_dir(&self, must_be_empty: bool) -> io::Result<bool> {
+        if ! self.is_dir() {
+            return self.is_removable()
+        }
+        if ! self.is_removable()? || ! self.access(libc::R_OK + libc::X_OK)? {
+            return Ok(false)
+        }
+        if must_be_empty {
+            return Ok(std::fs::read_dir(self)?.next().is_none())
+        }
+        Ok(true)
+    }
+
+    fn is_tree_removable(&self) -> io::Result<bool> {
+        Ok(self.tree_removal_blocker()?.is_none())
+    }
+
+    fn tree_removal_blocker(&self) -> io::Result<Option<PathBuf>> {
+        tree::tree_removal_blocker(self)
+    }
+
+    fn is_accessible_by_groups(&self, uid: u32, gids: &[u32], amode: AccessMode) -> io::Result<bool> {
+        let metadata = self.metadata()?;
+        Ok(compute::mode_allows(&metadata, uid, gids, amode,
+                                compute::Privilege::of_uid(uid)))
+    }
+
+    fn required_mode_for(&self, who: &UserContext, op: Operation) -> io::Result<Remedy> {
+        remedy::required_mode_for(self, who, op)
+    }
+
+    fn grant_plan(&self, uid: u32, gids: &[u32], desired: AccessMode) -> io::Result<Option<u16>> {
+        let stat = stat::stat(self, true)?;
+        if ! chmod_permitted(&stat, compute::Privilege::current()) {
+            return Ok(None)
+        }
+        Ok(Some(compute::grant_bits(&stat, uid, gids, desired, compute::Privilege::of_uid(uid))))
+    }
+
+    fn is_readable_by_user(&self, name: &str) -> io::Result<bool> {
+        accessible_by_user(self, name, AccessMode::READ)
+    }
+
+    fn is_writable_by_user(&self, name: &str) -> io::Result<bool> {
+        accessible_by_user(self, name, AccessMode::WRITE)
+    }
+
+    fn is_executable_by_user(&self, name: &str) -> io::Result<bool> {
+        accessible_by_user(self, name, AccessMode::EXECUTE)
+    }
+
+    fn chmod_recursive_dry_run(&self, mode: u16) -> io::Result<Vec<(PathBuf, u16, u16)>> {
+        tree::chmod_recursive_dry_run(self, mode)
+    }
+
+    fn normalize_mode(&self, file_mode: u16, dir_mode: u16) -> io::Result<bool> {
+        ChmodOptions::new().normalize_mode(self, file_mode, dir_mode)
+    }
+
+    fn normalize_tree(&self, file_mode: u16, dir_mode: u16) -> io::Result<usize> {
+        ChmodOptions::new().normalize_tree(self, file_mode, dir_mode)
+    }
+
+    fn sanitize(&self) -> io::Result<bool> {
+        ChmodOptions::new().sanitize(self)
+    }
+
+    fn sanitize_tree(&self) -> io::Result<usize> {
+        ChmodOptions::new().sanitize_tree(self)
+    }
+
+    fn is_hidden(&self) -> bool {
+        let dotted = match self.file_name() {
+            Some(name) => name.as_bytes().first() == Some(&b'.'),
+            None => false,
+        };
+        #[cfg(target_os = "macos")]
+        {
+            if ! dotted {
+                return match self.bsd_flags() {
+                    Ok(flags) => flags.contains(BsdFlags::UF_HIDDEN),
+                    Err(_) => false,
+                }
+            }
+        }
+        dotted
+    }
+
+    fn is_renamable_to(&self, dest: &Path) -> io::Result<RenameCheck> {
+        rename::is_renamable_to(self, dest)
+    }
+
+    fn access_all(&self, amode: AccessMode) -> io::Result<bool> {
+        self.access(amode.bits())
+    }
+
+    fn access_any(&self, amode: AccessMode) -> io::Result<bool> {
+        for single in &[AccessMode::READ, AccessMode::WRITE, AccessMode::EXECUTE] {
+            if amode.contains(*single) && self.access(single.bits())? {
+                return Ok(true)
+            }
+        }
+        Ok(false)
+    }
+
+    fn is_hardlinkable_to(&self, newpath: &Path) -> io::Result<bool> {
+        link::is_hardlinkable_to(self, newpath)
+    }
+
+    fn is_symlinkable_at(&self) -> io::Result<bool> {
+        link::is_symlinkable_at(self)
+    }
+
+    fn is_symlinkable(&self) -> io::Result<bool> {
+        self.is_symlinkable_at()
+    }
+
+    fn symlink_would_be_followable(&self, by_uid: u32) -> io::Result<bool> {
+        link::symlink_would_be_followable(self, by_uid)
+    }
+
+    fn exists_and_readable(&self) -> io::Result<bool> {
+        exists_and(self, libc::R_OK)
+    }
+
+    fn exists_and_writable(&self) -> io::Result<bool> {
+        if ! exists_and(self, libc::W_OK)? {
+            return Ok(false)
+        }
+        #[cfg(target_os = "macos")]
+        {
+            if self.bsd_flags()?.is_immutable() {
+                return Ok(false)
+            }
+        }
+        Ok(true)
+    }
+
+    fn exists_and_executable(&self) -> io::Result<bool> {
+        match self.is_excutable() {
+            Err(ref e) if is_missing(e) => Ok(false),
+            result => result,
+        }
+    }
+
+    fn is_truncatable(&self) -> io::Result<bool> {
+        if stat::stat(self, true)?.is_dir() {
+            return Ok(false)
+        }
+        #[cfg(target_os = "macos")]
+        {
+            let flags = self.bsd_flags()?;
+            if flags.is_immutable() || flags.is_append_only() {
+                return Ok(false)
+            }
+        }
+        #[cfg(target_os = "linux")]
+        {
+            let attrs = linux_attributes(self)?;
+            if attrs.is_immutable() || attrs.is_append_only() {
+                return Ok(false)
+            }
+        }
+        self.access(libc::W_OK)
+    }
+
+    fn is_appendable(&self) -> io::Result<bool> {
+        if stat::stat(self, true)?.is_dir() {
+            return Ok(false)
+        }
+        #[cfg(target_os = "macos")]
+        {
+            if self.bsd_flags()?.is_immutable() {
+                return Ok(false)
+            }
+        }
+        #[cfg(target_os = "linux")]
+        {
+            if linux_attributes(self)?.is_immutable() {
+                return Ok(false)
+            }
+        }
+        self.access(libc::W_OK)
+    }
+
+    fn is_on_noexec_mount(&self) -> io::Result<bool> {
+        mount::is_noexec(self)
+    }
+
+    fn is_truly_executable(&self) -> io::Result<bool> {
+        Ok(self.is_excutable()? && ! self.is_on_noexec_mount()?)
+    }
+
+    fn is_listable(&self) -> io::Result<bool> {
+        if ! stat::stat(self, true)?.is_dir() {
+            return Ok(false)
+        }
+        self.access(libc::R_OK)
+    }
+
+    fn is_traversable(&self) -> io::Result<bool> {
+        if ! stat::stat(self, true)?.is_dir() {
+            return Ok(false)
+        }
+        self.access(libc::X_OK)
+    }
+
+    fn can_create_in(&self, name: &OsStr) -> io::Result<bool> {
+        Ok(self.check_create_in(name, false)?.is_allowed())
+    }
+
+    fn check_create_in(&self, name: &OsStr, overwrite: bool) -> io::Result<CreateCheck> {
+        create::check_create_in(self, name, overwrite)
+    }
+
+    fn with_mode(&self, mode: u16) -> io::Result<PermGuard> {
+        PermGuard::new(self, mode)
+    }
+
+    fn is_executable_file(&self) -> io::Result<bool> {
+        if ! stat::stat(self, true)?.is_file() {
+            return Ok(false)
+        }
+        self.is_truly_executable()
+    }
+
+    fn owner_uid(&self) -> io::Result<u32> {
+        Ok(stat::stat(self, true)?.uid)
+    }
+
+    fn owner_uid_nofollow(&self) -> io::Result<u32> {
+        Ok(stat::stat(self, false)?.uid)
+    }
+
+    fn owner_gid(&self) -> io::Result<u32> {
+        Ok(stat::stat(self, true)?.gid)
+    }
+
+    fn owner_gid_nofollow(&self) -> io::Result<u32> {
+        Ok(stat::stat(self, false)?.gid)
+    }
+
+    fn shares_group_with(&self) -> io::Result<bool> {
+        compute::in_group(stat::stat(self, true)?.gid)
+    }
+
+    fn is_owned_by_current_user(&self) -> io::Result<bool> {
+        Ok(compute::is_current_user(stat::stat(self, true)?.uid))
+    }
+
+    fn is_owned_by_current_user_nofollow(&self) -> io::Result<bool> {
+        Ok(compute::is_current_user(stat::stat(self, false)?.uid))
+    }
+
+    fn is_owned_by(&self, uid: u32) -> io::Result<bool> {
+        Ok(stat::stat(self, true)?.uid == uid)
+    }
+
+    fn is_owned_by_nofollow(&self, uid: u32) -> io::Result<bool> {
+        Ok(stat::stat(self, false)?.uid == uid)
+    }
+
+    fn is_group_owned_by(&self, gid: u32) -> io::Result<bool> {
+        Ok(stat::stat(self, true)?.gid == gid)
+    }
+
+    fn is_group_owned_by_nofollow(&self, gid: u32) -> io::Result<bool> {
+        Ok(stat::stat(self, false)?.gid == gid)
+    }
+
+    fn is_group_owned_by_current(&self, include_supplementary: bool) -> io::Result<bool> {
+        group_owned_by_current(stat::stat(self, true)?.gid, include_supplementary)
+    }
+
+    fn is_group_owned_by_current_nofollow(&self, include_supplementary: bool) -> io::Result<bool> {
+        group_owned_by_current(stat::stat(self, false)?.gid, include_supplementary)
+    }
+
+    fn device_id(&self) -> io::Result<u64> {
+        Ok(self.metadata()?.dev())
+    }
+
+    fn inode(&self) -> io::Result<u64> {
+        Ok(self.metadata()?.ino())
+    }
+
+    fn size(&self) -> io::Result<u64> {
+        Ok(self.metadata()?.size())
+    }
+
+    fn blocks(&self) -> io::Result<u64> {
+        Ok(self.metadata()?.blocks())
+    }
+
+    fn is_same_file_as(&self, other: &Path) -> io::Result<bool> {
+        let (a, b) = (self.metadata()?, other.metadata()?);
+        Ok(a.dev() == b.dev() && a.ino() == b.ino())
+    }
+
+    fn device_numbers(&self) -> io::Result<Option<(u32, u32)>> {
+        let metadata = self.metadata()?;
+        let file_type = metadata.file_type();
+        if ! (file_type.is_char_device() || file_type.is_block_device()) {
+            return Ok(None)
+        }
+        Ok(Some(split_rdev(metadata.rdev())))
+    }
+
+    fn script_interpreter(&self) -> io::Result<Option<PathBuf>> {
+        script::script_interpreter(self)
+    }
+
+    fn is_runnable(&self) -> io::Result<bool> {
+        script::is_runnable(self)
+    }
+
+    fn can_append(&self) -> io::Result<bool> {
+        self.is_appendable()
+    }
+
+    fn can_list(&self) -> io::Result<bool> {
+        self.is_listable()
+    }
+
+    fn can_enter(&self) -> io::Result<bool> {
+        self.is_traversable()
+    }
+
+    fn can_chmod(&self) -> io::Result<bool> {
+        Ok(chmod_permitted(&stat::stat(self, true)?, compute::Privilege::current()))
+    }
+
+    fn is_private(&self) -> io::Result<bool> {
+        PrivateOptions::new().check(self)
+    }
+
+    fn ensure_private(&self) -> io::Result<()> {
+        PrivateOptions::new().ensure(self)
+    }
+
+    fn ancestors_are_trusted(&self) -> io::Result<TrustReport> {
+        trust::ancestors_are_trusted(self)
+    }
+
+    fn is_secure_tempdir(&self) -> io::Result<bool> {
+        TempDirOptions::new().check(self)
+    }
+
+    fn open_if_readable(&self) -> io::Result<Option<File>> {
+        open::open_if_readable(self, false)
+    }
+
+    fn open_if_readable_nofollow(&self) -> io::Result<Option<File>> {
+        open::open_if_readable(self, true)
+    }
+
+    fn open_if_writable(&self, opts: &OpenOptions) -> io::Result<Option<File>> {
+        open::open_if_permitted(self, opts, false)
+    }
+
+    fn open_if_writable_nofollow(&self, opts: &OpenOptions) -> io::Result<Option<File>> {
+        open::open_if_permitted(self, opts, true)
+    }
+
+    fn ancestor_access(&self) -> io::Result<Vec<(PathBuf, bool)>> {
+        let path = if self.is_absolute() {
+            self.to_path_buf()
+        } else {
+            std::env::current_dir()?.join(self)
+        };
+
+        // 父级目录均检查执行权限，路径本身不是目录时检查读权限
+        let own_amode = match stat::stat(&path, true) {
+            Ok(stat) if ! stat.is_dir() => libc::R_OK,
+            _ => libc::X_OK,
+        };
+
+        let mut result = Vec::new();
+        let ancestors = path.ancestors().collect::<Vec<_>>();
+        for (i, ancestor) in ancestors.into_iter().rev().enumerate() {
+            let amode = if ancestor == path { own_amode } else { libc::X_OK };
+            match ancestor.access(amode) {
+                Ok(granted) => result.push((ancestor.to_path_buf(), granted)),
+                Err(ref e) if i > 0 && is_missing(e) => break,
+                Err(e) => return Err(e),
+            }
+        }
+        Ok(result)
+    }
+
+    fn create_with_mode(&self, mode: u16, overwrite: bool) -> io::Result<File> {
+        open::create_with_mode(self, mode, overwrite)
+    }
+
+    fn lchmod(&self, mode: u16) -> io::Result<bool> {
+        lchmod(self, mode)
+    }
+
+    fn create_dir_with_mode(&self, mode: u16) -> io::Result<()> {
+        mkdir::create_dir_with_mode(self, mode)
+    }
+
+    fn create_dir_all_with_mode(&self, mode: u16, intermediate_mode: Option<u16>) -> io::Result<Vec<PathBuf>> {
+        mkdir::create_dir_all_with_mode(self, mode, intermediate_mode)
+    }
+
+    fn inspect(&self) -> io::Result<PermissionInspector> {
+        PermissionInspector::new(self)
+    }
+
+    fn describe(&self) -> io::Result<PathDescription> {
+        PathDescription::new(self, true)
+    }
+
+    fn describe_nofollow(&self) -> io::Result<PathDescription> {
+        PathDescription::new(self, false)
+    }
+
+    fn same_mode_as(&self, other: &Path) -> io::Result<bool> {
+        Ok(stat::stat(self, true)?.perm() == stat::stat(other, true)?.perm())
+    }
+
+    fn same_permissions_as(&self, other: &Path) -> io::Result<bool> {
+        let (this, other) = (stat::stat(self, true)?, stat::stat(other, true)?);
+        Ok(this.perm() == other.perm() && this.uid == other.uid && this.gid == other.gid)
+    }
+
+    fn same_owner_as(&self, other: &Path) -> io::Result<bool> {
+        ownership::ownership_matches(self, other, OwnershipAspect::OWNER, true)
+    }
+
+    fn same_owner_as_nofollow(&self, other: &Path) -> io::Result<bool> {
+        ownership::ownership_matches(self, other, OwnershipAspect::OWNER, false)
+    }
+
+    fn same_group_as(&self, other: &Path) -> io::Result<bool> {
+        ownership::ownership_matches(self, other, OwnershipAspect::GROUP, true)
+    }
+
+    fn same_group_as_nofollow(&self, other: &Path) -> io::Result<bool> {
+        ownership::ownership_matches(self, other, OwnershipAspect::GROUP, false)
+    }
+
+    fn ownership_matches(&self, other: &Path, aspect: OwnershipAspect) -> io::Result<bool> {
+        ownership::ownership_matches(self, other, aspect, true)
+    }
+
+    fn ownership_matches_nofollow(&self, other: &Path, aspect: OwnershipAspect) -> io::Result<bool> {
+        ownership::ownership_matches(self, other, aspect, false)
+    }
+
+    fn chmod_str(&self, spec: &str) -> io::Result<bool> {
+        let invalid = |err: ParseError| io::Error::new(io::ErrorKind::InvalidInput, err);
+        let mode = if spec.bytes().all(|b| b.is_ascii_digit()) {
+            Mode::from_octal(spec).map_err(invalid)?
+        } else {
+            let stat = stat::stat(self, true)?;
+            Mode::new(stat.perm())
+                .apply_symbolic(spec, stat.is_dir(), current_umask() as u16)
+                .map_err(invalid)?
+        };
+        self.set_mode(mode)
+    }
+
+    fn add_mode(&self, bits: u16) -> io::Result<u16> {
+        adjust::adjust_mode(self, bits, 0)
+    }
+
+    fn remove_mode(&self, bits: u16) -> io::Result<u16> {
+        adjust::adjust_mode(self, 0, bits)
+    }
+}
+
+impl PathPermission for PathBuf {
+    fn access(&self, amode: c_int) -> io::Result<bool> {
+        self.as_path().access(amode)
+    }
+
+    fn is_readable(&self) -> io::Result<bool> {
+        self.as_path().is_readable()
+    }
+
+    fn is_writable(&self) -> io::Result<bool> {
+        self.as_path().is_writable()
+    }
+
+    fn is_excutable(&self) -> io::Result<bool> {
+        self.as_path().is_excutable()
+    }
+
+    fn is_creatable(&self) -> io::Result<bool> {
+        self.as_path().is_creatable()
+    }
+
+    fn is_removable(&self) -> io::Result<bool> {
+        self.as_path().is_removable()
+    }
+
+    fn is_removable_nofollow(&self) -> io::Result<bool> {
+        self.as_path().is_removable_nofollow()
+    }
+
+    fn check_access(&self, mode: u16) -> io::Result<bool> {
+        self.as_path().check_mode(Mode::new(mode))
+    }
+
+    fn check_mode(&self, mode: Mode) -> io::Result<bool> {
+        self.as_path().check_mode(mode)
+    }
+
+    fn get_access(&self) -> io::Result<String> {
+        self.as_path().get_access()
+    }
+
+    fn get_access_symbolic(&self) -> io::Result<String> {
+        self.as_path().get_access_symbolic()
+    }
+
+    fn chmod(&self, mode: u16) -> io::Result<bool> {
+        self.as_path().set_mode(Mode::new(mode))
+    }
+
+    fn set_mode(&self, mode: Mode) -> io::Result<bool> {
+        self.as_path().set_mode(mode)
+    }
+
+    fn chmod_preserving_special(&self, mode: u16) -> io::Result<bool> {
+        self.as_path().chmod_preserving_special(mode)
+    }
+
+    fn set_permissions_via_std(&self, mode: Mode) -> io::Result<()> {
+        self.as_path().set_permissions_via_std(mode)
+    }
+
+    fn can_rename(&self, to: &Path) -> io::Result<bool> {
+        self.as_path().can_rename(to)
+    }
+
+    #[cfg(target_os = "macos")]
+    fn bsd_flags(&self) -> io::Result<BsdFlags> {
+        self.as_path().bsd_flags()
+    }
+
+    #[cfg(target_os = "macos")]
+    fn set_bsd_flags(&self, flags: BsdFlags) -> io::Result<bool> {
+        self.as_path().set_bsd_flags(flags)
+    }
+
+    fn predicted_create_mode(&self, requested: u16) -> io::Result<u16> {
+        self.as_path().predicted_create_mode(requested)
+    }
+
+    #[cfg(all(target_os = "linux", feature = "selinux"))]
+    fn selinux_context(&self) -> io::Result<Option<String>> {
+        self.as_path().selinux_context()
+    }
+
+    #[cfg(all(target_os = "linux", feature = "selinux"))]
+    fn set_selinux_context(&self, context: &str) -> io::Result<bool> {
+        self.as_path().set_selinux_context(context)
+    }
+
+    fn access_for(&self, amode: AccessMode) -> io::Result<bool> {
+        self.as_path().access_for(amode)
+    }
+
+    fn is_accessible_by(&self, uid: u32, gid: u32, amode: AccessMode) -> io::Result<bool> {
+        self.as_path().is_accessible_by(uid, gid, amode)
+    }
+
+    fn access_detailed(&self, amode: c_int) -> io::Result<AccessResult> {
+        self.as_path().access_detailed(amode)
+    }
+
+    fn snapshot(&self) -> io::Result<PermSnapshot> {
+        self.as_path().snapshot()
+    }
+
+    fn restore(&self, snap: &PermSnapshot) -> io::Result<()> {
+        self.as_path().restore(snap)
+    }
+
+    fn snapshot_tree(&self) -> io::Result<PermissionSnapshot> {
+        self.as_path().snapshot_tree()
+    }
+
+    #[cfg(all(target_os = "linux", feature = "watch"))]
+    fn watch_permissions(&self) -> io::Result<PermissionWatcher> {
+        self.as_path().watch_permissions()
+    }
+
+    fn export_manifest(&self, writer: &mut dyn Write, format: ManifestFormat) -> io::Result<u64> {
+        self.as_path().export_manifest(writer, format)
+    }
+
+    #[cfg(target_os = "linux")]
+    fn file_capabilities(&self) -> io::Result<Option<FileCaps>> {
+        self.as_path().file_capabilities()
+    }
+
+    #[cfg(target_os = "linux")]
+    fn file_attributes(&self) -> io::Result<FileAttributes> {
+        self.as_path().file_attributes()
+    }
+
+    fn audit_tree(&self) -> io::Result<Vec<AuditEntry>> {
+        self.as_path().audit_tree()
+    }
+
+    fn find_matching(&self, predicate: &dyn Fn(Mode) -> bool) -> io::Result<Vec<AuditEntry>> {
+        self.as_path().find_matching(predicate)
+    }
+
+    fn find_setuid(&self) -> io::Result<Vec<PathBuf>> {
+        self.as_path().find_setuid()
+    }
+
+    fn find_setgid(&self) -> io::Result<Vec<PathBuf>> {
+        self.as_path().find_setgid()
+    }
+
+    fn chmod_recursive(&self, mode: u16) -> io::Result<usize> {
+        self.as_path().chmod_recursive(mode)
+    }
+
+    fn chown_recursive(&self, uid: Option<u32>, gid: Option<u32>, keep_going: bool)
+        -> io::Result<ChownReport> {
+        self.as_path().chown_recursive(uid, gid, keep_going)
+    }
+
+    #[cfg(feature = "rayon")]
+    fn par_audit_tree(&self) -> io::Result<Vec<AuditEntry>> {
+        self.as_path().par_audit_tree()
+    }
+
+    #[cfg(feature = "rayon")]
+    fn par_chmod_recursive(&self, mode: u16) -> io::Result<usize> {
+        self.as_path().par_chmod_recursive(mode)
+    }
+
+    fn predicted_dir_mode(&self, requested: u16) -> io::Result<u16> {
+        self.as_path().predicted_dir_mode(requested)
+    }
+
+    fn is_readonly(&self) -> io::Result<bool> {
+        self.as_path().is_readonly()
+    }
+
+    fn set_readonly(&self, readonly: bool) -> io::Result<bool> {
+        self.as_path().set_readonly(readonly)
+    }
+
+    fn get_mode(&self) -> io::Result<u16> {
+        self.as_path().get_mode()
+    }
+
+    fn get_mode_nofollow(&self) -> io::Result<u16> {
+        self.as_path().get_mode_nofollow()
+    }
+
+    fn long_format(&self) -> io::Result<String> {
+        self.as_path().long_format()
+    }
+
+    fn long_format_nofollow(&self) -> io::Result<String> {
+        self.as_path().long_format_nofollow()
+    }
+
+    fn full_mode(&self) -> io::Result<u32> {
+        self.as_path().full_mode()
+    }
+
+    fn full_mode_nofollow(&self) -> io::Result<u32> {
+        self.as_path().full_mode_nofollow()
+    }
+
+    fn file_kind(&self) -> io::Result<Option<FileKind>> {
+        self.as_path().file_kind()
+    }
+
+    fn file_kind_nofollow(&self) -> io::Result<Option<FileKind>> {
+        self.as_path().file_kind_nofollow()
+    }
+
+    fn stat_summary(&self) -> io::Result<StatSummary> {
+        self.as_path().stat_summary()
+    }
+
+    fn stat_summary_nofollow(&self) -> io::Result<StatSummary> {
+        self.as_path().stat_summary_nofollow()
+    }
+
+    fn is_creatable_exclusive(&self) -> io::Result<bool> {
+        self.as_path().is_creatable_exclusive()
+    }
+
+    fn creatable_with_parents(&self) -> io::Result<CreationPlan> {
+        self.as_path().creatable_with_parents()
+    }
+
+    fn diff_mode(&self, other: &Path) -> io::Result<ModeDiff> {
+        self.as_path().diff_mode(other)
+    }
+
+    fn diff_access(&self, other: &Path) -> io::Result<Option<AccessDiff>> {
+        self.as_path().diff_access(other)
+    }
+
+    fn is_removable_dir(&self, must_be_empty: bool) -> io::Result<bool> {
+        self.as_path().is_removable_dir(must_be_empty)
+    }
+
+    fn is_tree_removable(&self) -> io::Result<bool> {
+        self.as_path().is_tree_removable()
+    }
+
+    fn tree_removal_blocker(&self) -> io::Result<Option<PathBuf>> {
+        self.as_path().tree_removal_blocker()
+    }
+
+    fn is_accessible_by_groups(&self, uid: u32, gids: &[u32], amode: AccessMode) -> io::Result<bool> {
+        self.as_path().is_accessible_by_groups(uid, gids, amode)
+    }
+
+    fn required_mode_for(&self, who: &UserContext, op: Operation) -> io::Result<Remedy> {
+        self.as_path().required_mode_for(who, op)
+    }
+
+    fn grant_plan(&self, uid: u32, gids: &[u32], desired: AccessMode) -> io::Result<Option<u16>> {
+        self.as_path().grant_plan(uid, gids, desired)
+    }
+
+    fn is_readable_by_user(&self, name: &str) -> io::Result<bool> {
+        self.as_path().is_readable_by_user(name)
+    }
+
+    fn is_writable_by_user(&self, name: &str) -> io::Result<bool> {
+        self.as_path().is_writable_by_user(name)
+    }
+
+    fn is_executable_by_user(&self, name: &str) -> io::Result<bool> {
+        self.as_path().is_executable_by_user(name)
+    }
+
+    fn chmod_recursive_dry_run(&self, mode: u16) -> io::Result<Vec<(PathBuf, u16, u16)>> {
+        self.as_path().chmod_recursive_dry_run(mode)
+    }
+
+    fn normalize_mode(&self, file_mode: u16, dir_mode: u16) -> io::Result<bool> {
+        self.as_path().normalize_mode(file_mode, dir_mode)
+    }
+
+    fn normalize_tree(&self, file_mode: u16, dir_mode: u16) -> io::Result<usize> {
+        self.as_path().normalize_tree(file_mode, dir_mode)
+    }
+
+    fn sanitize(&self) -> io::Result<bool> {
+        self.as_path().sanitize()
+    }
+
+    fn sanitize_tree(&self) -> io::Result<usize> {
+        self.as_path().sanitize_tree()
+    }
+
+    fn is_hidden(&self) -> bool {
+        self.as_path().is_hidden()
+    }
+
+    fn is_renamable_to(&self, dest: &Path) -> io::Result<RenameCheck> {
+        self.as_path().is_renamable_to(dest)
+    }
+
+    fn access_all(&self, amode: AccessMode) -> io::Result<bool> {
+        self.as_path().access_all(amode)
+    }
+
+    fn access_any(&self, amode: AccessMode) -> io::Result<bool> {
+        self.as_path().access_any(amode)
+    }
+
+    fn is_hardlinkable_to(&self, newpath: &Path) -> io::Result<bool> {
+        self.as_path().is_hardlinkable_to(newpath)
+    }
+
+    fn is_symlinkable_at(&self) -> io::Result<bool> {
+        self.as_path().is_symlinkable_at()
+    }
+
+    fn is_symlinkable(&self) -> io::Result<bool> {
+        self.as_path().is_symlinkable()
+    }
+
+    fn symlink_would_be_followable(&self, by_uid: u32) -> io::Result<bool> {
+        self.as_path().symlink_would_be_followable(by_uid)
+    }
+
+    fn exists_and_readable(&self) -> io::Result<bool> {
+        self.as_path().exists_and_readable()
+    }
+
+    fn exists_and_writable(&self) -> io::Result<bool> {
+        self.as_path().exists_and_writable()
+    }
+
+    fn exists_and_executable(&self) -> io::Result<bool> {
+        self.as_path().exists_and_executable()
+    }
+
+    fn is_truncatable(&self) -> io::Result<bool> {
+        self.as_path().is_truncatable()
+    }
+
+    fn is_appendable(&self) -> io::Result<bool> {
+        self.as_path().is_appendable()
+    }
+
+    fn is_on_noexec_mount(&self) -> io::Result<bool> {
+        self.as_path().is_on_noexec_mount()
+    }
+
+    fn is_truly_executable(&self) -> io::Result<bool> {
+        self.as_path().is_truly_executable()
+    }
+
+    fn is_listable(&self) -> io::Result<bool> {
+        self.as_path().is_listable()
+    }
+
+    fn is_traversable(&self) -> io::Result<bool> {
+        self.as_path().is_traversable()
+    }
+
+    fn can_create_in(&self, name: &OsStr) -> io::Result<bool> {
+        self.as_path().can_create_in(name)
+    }
+
+    fn check_create_in(&self, name: &OsStr, overwrite: bool) -> io::Result<CreateCheck> {
+        self.as_path().check_create_in(name, overwrite)
+    }
+
+    fn with_mode(&self, mode: u16) -> io::Result<PermGuard> {
+        self.as_path().with_mode(mode)
+    }
+
+    fn is_executable_file(&self) -> io::Result<bool> {
+        self.as_path().is_executable_file()
+    }
+
+    fn owner_uid(&self) -> io::Result<u32> {
+        self.as_path().owner_uid()
+    }
+
+    fn owner_uid_nofollow(&self) -> io::Result<u32> {
+        self.as_path().owner_uid_nofollow()
+    }
+
+    fn owner_gid(&self) -> io::Result<u32> {
+        self.as_path().owner_gid()
+    }
+
+    fn owner_gid_nofollow(&self) -> io::Result<u32> {
+        self.as_path().owner_gid_nofollow()
+    }
+
+    fn shares_group_with(&self) -> io::Result<bool> {
+        self.as_path().shares_group_with()
+    }
+
+    fn is_owned_by_current_user(&self) -> io::Result<bool> {
+        self.as_path().is_owned_by_current_user()
+    }
+
+    fn is_owned_by_current_user_nofollow(&self) -> io::Result<bool> {
+        self.as_path().is_owned_by_current_user_nofollow()
+    }
+
+    fn is_owned_by(&self, uid: u32) -> io::Result<bool> {
+        self.as_path().is_owned_by(uid)
+    }
+
+    fn is_owned_by_nofollow(&self, uid: u32) -> io::Result<bool> {
+        self.as_path().is_owned_by_nofollow(uid)
+    }
+
+    fn is_group_owned_by(&self, gid: u32) -> io::Result<bool> {
+        self.as_path().is_group_owned_by(gid)
+    }
+
+    fn is_group_owned_by_nofollow(&self, gid: u32) -> io::Result<bool> {
+        self.as_path().is_group_owned_by_nofollow(gid)
+    }
+
+    fn is_group_owned_by_current(&self, include_supplementary: bool) -> io::Result<bool> {
+        self.as_path().is_group_owned_by_current(include_supplementary)
+    }
+
+    fn is_group_owned_by_current_nofollow(&self, include_supplementary: bool) -> io::Result<bool> {
+        self.as_path().is_group_owned_by_current_nofollow(include_supplementary)
+    }
+
+    fn device_id(&self) -> io::Result<u64> {
+        self.as_path().device_id()
+    }
+
+    fn inode(&self) -> io::Result<u64> {
+        self.as_path().inode()
+    }
+
+    fn size(&self) -> io::Result<u64> {
+        self.as_path().size()
+    }
+
+    fn blocks(&self) -> io::Result<u64> {
+        self.as_path().blocks()
+    }
+
+    fn is_same_file_as(&self, other: &Path) -> io::Result<bool> {
+        self.as_path().is_same_file_as(other)
+    }
+
+    fn device_numbers(&self) -> io::Result<Option<(u32, u32)>> {
+        self.as_path().device_numbers()
+    }
+
+    fn script_interpreter(&self) -> io::Result<Option<PathBuf>> {
+        self.as_path().script_interpreter()
+    }
+
+    fn is_runnable(&self) -> io::Result<bool> {
+        self.as_path().is_runnable()
+    }
+
+    fn can_append(&self) -> io::Result<bool> {
+        self.as_path().can_append()
+    }
+
+    fn can_list(&self) -> io::Result<bool> {
+        self.as_path().can_list()
+    }
+
+    fn can_enter(&self) -> io::Result<bool> {
+        self.as_path().can_enter()
+    }
+
+    fn can_chmod(&self) -> io::Result<bool> {
+        self.as_path().can_chmod()
+    }
+
+    fn is_private(&self) -> io::Result<bool> {
+        self.as_path().is_private()
+    }
+
+    fn ensure_private(&self) -> io::Result<()> {
+        self.as_path().ensure_private()
+    }
+
+    fn ancestors_are_trusted(&self) -> io::Result<TrustReport> {
+        self.as_path().ancestors_are_trusted()
+    }
+
+    fn is_secure_tempdir(&self) -> io::Result<bool> {
+        self.as_path().is_secure_tempdir()
+    }
+
+    fn open_if_readable(&self) -> io::Result<Option<File>> {
+        self.as_path().open_if_readable()
+    }
+
+    fn open_if_readable_nofollow(&self) -> io::Result<Option<File>> {
+        self.as_path().open_if_readable_nofollow()
+    }
+
+    fn open_if_writable(&self, opts: &OpenOptions) -> io::Result<Option<File>> {
+        self.as_path().open_if_writable(opts)
+    }
+
+    fn open_if_writable_nofollow(&self, opts: &OpenOptions) -> io::Result<Option<File>> {
+        self.as_path().open_if_writable_nofollow(opts)
+    }
+
+    fn ancestor_access(&self) -> io::Result<Vec<(PathBuf, bool)>> {
+        self.as_path().ancestor_access()
+    }
+
+    fn create_with_mode(&self, mode: u16, overwrite: bool) -> io::Result<File> {
+        self.as_path().create_with_mode(mode, overwrite)
+    }
+
+    fn lchmod(&self, mode: u16) -> io::Result<bool> {
+        self.as_path().lchmod(mode)
+    }
+
+    fn create_dir_with_mode(&self, mode: u16) -> io::Result<()> {
+        self.as_path().create_dir_with_mode(mode)
+    }
+
+    fn create_dir_all_with_mode(&self, mode: u16, intermediate_mode: Option<u16>) -> io::Result<Vec<PathBuf>> {
+        self.as_path().create_dir_all_with_mode(mode, intermediate_mode)
+    }
+
+    fn inspect(&self) -> io::Result<PermissionInspector> {
+        self.as_path().inspect()
+    }
+
+    fn describe(&self) -> io::Result<PathDescription> {
+        self.as_path().describe()
+    }
+
+    fn describe_nofollow(&self) -> io::Result<PathDescription> {
+        self.as_path().describe_nofollow()
+    }
+
+    fn same_mode_as(&self, other: &Path) -> io::Result<bool> {
+        self.as_path().same_mode_as(other)
+    }
+
+    fn same_permissions_as(&self, other: &Path) -> io::Result<bool> {
+        self.as_path().same_permissions_as(other)
+    }
+
+    fn same_owner_as(&self, other: &Path) -> io::Result<bool> {
+        self.as_path().same_owner_as(other)
+    }
+
+    fn same_owner_as_nofollow(&self, other: &Path) -> io::Result<bool> {
+        self.as_path().same_owner_as_nofollow(other)
+    }
+
+    fn same_group_as(&self, other: &Path) -> io::Result<bool> {
+        self.as_path().same_group_as(other)
+    }
+
+    fn same_group_as_nofollow(&self, other: &Path) -> io::Result<bool> {
+        self.as_path().same_group_as_nofollow(other)
+    }
+
+    fn ownership_matches(&self, other: &Path, aspect: OwnershipAspect) -> io::Result<bool> {
+        self.as_path().ownership_matches(other, aspect)
+    }
+
+    fn ownership_matches_nofollow(&self, other: &Path, aspect: OwnershipAspect) -> io::Result<bool> {
+        self.as_path().ownership_matches_nofollow(other, aspect)
+    }
+
+    fn chmod_str(&self, spec: &str) -> io::Result<bool> {
+        self.as_path().chmod_str(spec)
+    }
+
+    fn add_mode(&self, bits: u16) -> io::Result<u16> {
+        self.as_path().add_mode(bits)
+    }
+
+    fn remove_mode(&self, bits: u16) -> io::Result<u16> {
+        self.as_path().remove_mode(bits)
+    }
+}
+
+// 返回路径的父级目录
+// 根目录（及空路径）没有父级目录，返回 None：根目录无法被删除或创建；
+// 只有文件名的相对路径，如 "x"，其父级目录为当前目录，与 "./x" 相同
+pub(crate) fn parent_dir(path: &Path) -> io::Result<Option<PathBuf>> {
+    match path.parent() {
+        None => Ok(None),
+        Some(parent) if parent.as_os_str().is_empty() => {
+            Ok(Some(std::env::current_dir()?))
+        }
+        Some(parent) => Ok(Some(parent.to_path_buf())),
+    }
+}
+
+// 判断所有者为 owner 的路径，能否从目录 parent 中删除，见 rules::removable_in
+pub(crate) fn removable_in(parent: &Path, owner: u32, privilege: compute::Privilege) -> io::Result<bool> {
+    rules::removable_in(&rules::RealFs, parent, owner, privilege)
+}
+
+// 较短的路径直接放在栈上，避免每次调用都分配内存
+const C_PATH_INLINE: usize = 256;
+
+// 以 '\0' 结尾的路径，用于传给 libc
+// 只在调用期间存在于栈上，Inline 较大正是为了避免分配
+#[allow(clippy::large_enum_variant)]
+pub(crate) enum CPath {
+    Inline([u8; C_PATH_INLINE]),
+    Heap(CString),
+}
+
+impl CPath {
+    pub fn as_ptr(&self) -> *const u8 {
+        match self {
+            CPath::Inline(buf) => buf.as_ptr(),
+            CPath::Heap(buf) => buf.as_ptr() as *const u8,
+        }
+    }
+}
+
+// 在C中，char的最后一位是'\0'或ASCII码值为0
+// 路径中间含有 '\0' 时，C 会将其截断，返回 InvalidInput 错误
+pub(crate) fn c_path(path: &Path) -> io::Result<CPath> {
+    let bytes = path.as_os_str().as_bytes();
+    if bytes.contains(&0) {
+        return Err(io::Error::new(io::ErrorKind::InvalidInput,
+                                  "path contains an interior NUL byte"))
+    }
+
+    if bytes.len() < C_PATH_INLINE {
+        let mut buf = [0; C_PATH_INLINE];
+        buf[..bytes.len()].copy_from_slice(bytes);
+        Ok(CPath::Inline(buf))
+    } else {
+        CString::new(bytes)
+            .map(CPath::Heap)
+            .map_err(|_| io::Error::from(io::ErrorKind::InvalidInput))
+    }
+}
+
+fn access(path: &Path, mod_mask: c_int) ->io::Result<bool> {
+    faccessat(path, mod_mask, 0)
+}
+
+// 以有效 uid/gid 检查（AT_EACCESS）。内核在删除、创建、链接、重命名时以有效 uid/gid
+// 判断，推断这些操作能否成功时应使用此函数，而非 access(2)
+pub(crate) fn effective_access(path: &Path, mod_mask: c_int) -> io::Result<bool> {
+    faccessat(path, mod_mask, libc::AT_EACCESS)
+}
+
+// flags 为 AT_SYMLINK_NOFOLLOW、AT_EACCESS 的组合，为 0 时同 access(2)
+// flags 不为 0 时，Linux 上优先使用 faccessat2(2)，见 faccessat2
+fn faccessat(path: &Path, mod_mask: c_int, flags: c_int) -> io::Result<bool> {
+    let buf = c_path(path)?;
+    let buf_ptr = buf.as_ptr() as *const libc::c_char;
+
+    #[cfg(all(target_os = "linux", any(target_env = "gnu", target_env = "musl")))]
+    let result = match faccessat2(buf_ptr, mod_mask, flags) {
+        Some(result) => result,
+        None => libc_faccessat(buf_ptr, mod_mask, flags),
+    };
+    #[cfg(not(all(target_os = "linux", any(target_env = "gnu", target_env = "musl"))))]
+    let result = libc_faccessat(buf_ptr, mod_mask, flags);
+
+    match result {
+        Ok(()) => Ok(true),
+        // 无查看此路径的权限（无法确认路径是否存在）
+        Err(ref err) if err.raw_os_error() == Some(libc::EACCES) => Ok(false),
+        Err(err) => Err(err),  // 其它错误，如路径不存在等
+    }
+}
+
+fn libc_faccessat(path: *const libc::c_char, mod_mask: c_int, flags: c_int) -> io::Result<()> {
+    match unsafe { libc::faccessat(libc::AT_FDCWD, path, mod_mask, flags) } {
+        0 => Ok(()),
+        _ => Err(io::Error::last_os_error()),
+    }
+}
+
+// 直接调用 faccessat2(2)（Linux 5.8+），由内核处理 flags
+// faccessat(2) 系统调用没有 flags 参数，libc 只能在用户空间根据 stat 模拟 AT_EACCESS 及
+// AT_SYMLINK_NOFOLLOW，不会考虑 ACL 等；较旧的 libc 甚至对二者的组合返回 EINVAL。
+// flags 为 0 时无需 faccessat2；内核不支持（ENOSYS）时返回 None，由调用者退回至 faccessat，
+// 之后不再尝试。
+// EPERM 既可能是正常的结果（如对不可变的文件检查 W_OK），也可能是 seccomp 拒绝了此系统调用：
+// 首次遇到时以 F_OK 检查 "/" 加以区分，后者同样视为不支持，前者则将 EPERM 返回给调用者。
+#[cfg(all(target_os = "linux", any(target_env = "gnu", target_env = "musl")))]
+fn faccessat2(path: *const libc::c_char, mod_mask: c_int, flags: c_int) -> Option<io::Result<()>> {
+    use std::sync::atomic::{AtomicU8, Ordering};
+
+    const UNKNOWN: u8 = 0;
+    const AVAILABLE: u8 = 1;
+    const UNAVAILABLE: u8 = 2;
+    static STATE: AtomicU8 = AtomicU8::new(UNKNOWN);
+
+    let state = STATE.load(Ordering::Relaxed);
+    if flags == 0 || state == UNAVAILABLE {
+        return None
+    }
+
+    let syscall = |path: *const libc::c_char, mod_mask: c_int, flags: c_int| {
+        match unsafe { libc::syscall(libc::SYS_faccessat2, libc::AT_FDCWD, path, mod_mask, flags) } {
+            0 => Ok(()),
+            _ => Err(io::Error::last_os_error()),
+        }
+    };
+
+    let result = syscall(path, mod_mask, flags);
+    let available = match result {
+        Err(ref err) if err.raw_os_error() == Some(libc::ENOSYS) => false,
+        Err(ref err) if err.raw_os_error() == Some(libc::EPERM) && state == UNKNOWN => {
+            let root = b"/\0".as_ptr() as *const libc::c_char;
+            match syscall(root, libc::F_OK, libc::AT_EACCESS) {
+                Err(ref err) => ! matches!(err.raw_os_error(), Some(libc::ENOSYS) | Some(libc::EPERM)),
+                Ok(()) => true,
+            }
+        }
+        _ => true,
+    };
+    STATE.store(if available { AVAILABLE } else { UNAVAILABLE }, Ordering::Relaxed);
+    if available { Some(result) } else { None }
+}
+
+fn chown(path: &Path, uid: u32, gid: u32) -> io::Result<()> {
+    let buf = c_path(path)?;
+    let buf_ptr = buf.as_ptr() as *const libc::c_char;
+
+    let result = unsafe {
+        libc::chown(buf_ptr, uid, gid)
+    };
+
+    match result {
+        0 => Ok(()),
+        _ => Err(io::Error::last_os_error()),
+    }
+}
+
+// 不跟随符号链接，变更的是链接本身
+fn lchown(path: &Path, uid: u32, gid: u32) -> io::Result<()> {
+    let buf = c_path(path)?;
+    let buf_ptr = buf.as_ptr() as *const libc::c_char;
+
+    let result = unsafe {
+        libc::lchown(buf_ptr, uid, gid)
+    };
+
+    match result {
+        0 => Ok(()),
+        _ => Err(io::Error::last_os_error()),
+    }
+}
+
+// 以用户名查找其 uid 及所属的全部组，再按权限位计算
+fn accessible_by_user(path: &Path, name: &str, amode: AccessMode) -> io::Result<bool> {
+    let (uid, gids) = users::user_groups(name)?;
+    path.is_accessible_by_groups(uid, &gids, amode)
+}
+
+// 路径不存在时返回 Ok(false)，只调用一次 access(2)
+fn exists_and(path: &Path, mod_mask: c_int) -> io::Result<bool> {
+    match access_detailed(path, mod_mask)? {
+        AccessResult::Granted => Ok(true),
+        AccessResult::Other(ref err) if is_missing(err) => Ok(false),
+        AccessResult::Other(err) => Err(err),
+        _ => Ok(false),
+    }
+}
+
+// 分解为主、次设备号；libc::major、minor 在 Linux 上返回 c_uint，macOS 上返回 i32
+#[allow(clippy::unnecessary_cast)]
+fn split_rdev(rdev: u64) -> (u32, u32) {
+    let rdev = rdev as libc::dev_t;
+    (libc::major(rdev) as u32, libc::minor(rdev) as u32)
+}
+
+// statx 不可用时视为未设置任何属性
+#[cfg(target_os = "linux")]
+fn linux_attributes(path: &Path) -> io::Result<FileAttributes> {
+    match attributes::file_attributes(path) {
+        Err(ref e) if e.kind() == io::ErrorKind::Unsupported => Ok(FileAttributes::default()),
+        result => result,
+    }
+}
+
+// gid 是否为当前进程的有效 gid，include_supplementary 为 true 时也可为附加组之一
+fn group_owned_by_current(gid: u32, include_supplementary: bool) -> io::Result<bool> {
+    if include_supplementary {
+        compute::in_group(gid)
+    } else {
+        Ok(gid == compute::effective_gid())
+    }
+}
+
+// 当前进程能否 chmod：为文件所有者，或拥有 CAP_FOWNER
+fn chmod_permitted(stat: &stat::Stat, privilege: compute::Privilege) -> bool {
+    compute::is_current_user(stat.uid) || privilege.fowner
+}
+
+// ENOENT 或 ENOTDIR（路径中的某一级不是目录），均表示路径不存在
+fn is_missing(err: &io::Error) -> bool {
+    err.kind() == io::ErrorKind::NotFound || err.raw_os_error() == Some(libc::ENOTDIR)
+}
+
+fn access_detailed(path: &Path, mod_mask: c_int) -> io::Result<AccessResult> {
+    let buf = c_path(path)?;
+    let buf_ptr = buf.as_ptr() as *const libc::c_char;
+
+    let result = unsafe {
+        libc::access(buf_ptr, mod_mask)
+    };
+
+    if result == 0 {
+        return Ok(AccessResult::Granted)
+    }
+    let err = io::Error::last_os_error();
+    Ok(match err.raw_os_error() {
+        Some(libc::EACCES) => AccessResult::Denied,
+        Some(libc::ENOENT) => AccessResult::NotFound,
+        Some(libc::EROFS) => AccessResult::ReadOnlyFs,
+        _ => AccessResult::Other(err),
+    })
+}
+
+// mode 为 mode_t，避免在其为 32 位的系统上截断
+fn chmod(path: &Path, mode: libc::mode_t) -> io::Result<()> {
+    let buf = c_path(path)?;
+    let buf_ptr = buf.as_ptr() as *const libc::c_char;
+
+    // 去除文件类型等无关的位
+    let result = unsafe {
+        libc::chmod(buf_ptr, mode & libc::mode_t::from(modes::ALL_BITS))
+    };
+
+    match result {
+        0 => Ok(()),
+        _ => Err(io::Error::last_os_error()),
+    }
+}
+
+#[cfg(any(target_os = "macos", target_os = "freebsd", target_os = "netbsd"))]
+fn lchmod(path: &Path, mode: u16) -> io::Result<bool> {
+    let buf = c_path(path)?;
+    let buf_ptr = buf.as_ptr() as *const libc::c_char;
+
+    let result = unsafe {
+        libc::lchmod(buf_ptr, libc::mode_t::from(mode & modes::ALL_BITS))
+    };
+
+    match result {
         0 => Ok(true),
-        // 1: PermissionDenied, 2: No such file or directory
-        1 | 2 => Ok(false),
         _ => Err(io::Error::last_os_error()),
     }
 }
+
+// Linux 等系统上，符号链接的权限总是 0o777，无法变更
+#[cfg(not(any(target_os = "macos", target_os = "freebsd", target_os = "netbsd")))]
+fn lchmod(_path: &Path, _mode: u16) -> io::Result<bool> {
+    Err(io::Error::new(io::ErrorKind::Unsupported, "lchmod is not available on this platform"))
+}
@@ -0,0 +1,59 @@
+//! 在当前权限的基础上增加、去除部分权限位，即 `chmod +x`、`chmod -w`
+use std::{
+    fs::{File, OpenOptions, Permissions},
+    io,
+    path::Path,
+    os::unix::fs::{MetadataExt, OpenOptionsExt, PermissionsExt},
+};
+
+use crate::modes;
+
+// 为路径的权限增加 add 中的位、去除 remove 中的位，返回变更后的权限
+// 普通文件及目录通过已打开的文件描述符获取及变更权限（fstat、fchmod），避免两次解析路径
+// 之间被替换。其它类型的文件不会被打开：打开设备文件会触发驱动的副作用，打开 UNIX 套接字
+// 返回 ENXIO。这些文件，以及无法打开的文件（如没有读权限），退回至按路径获取及变更，
+// 两次解析路径之间仍可能被替换
+pub(crate) fn adjust_mode(path: &Path, add: u16, remove: u16) -> io::Result<u16> {
+    let stat = super::stat::stat(path, true)?;
+    if ! stat.is_file() && ! stat.is_dir() {
+        return adjust_by_path(path, stat.perm(), add, remove)
+    }
+    let file = match open(path) {
+        Ok(file) => file,
+        // 没有读权限（如 0o000、只写的文件），或 stat 之后被替换为套接字
+        Err(ref e) if matches!(e.raw_os_error(), Some(libc::EACCES) | Some(libc::ENXIO)) => {
+            return adjust_by_path(path, stat.perm(), add, remove)
+        }
+        Err(e) => return Err(e),
+    };
+
+    let current = (file.metadata()?.mode() & u32::from(modes::ALL_BITS)) as u16;
+    let mode = apply(current, add, remove);
+    if mode != current {
+        file.set_permissions(Permissions::from_mode(u32::from(mode)))?;
+    }
+    Ok(mode)
+}
+
+// 目录同样可以只读打开；stat 之后被替换时，O_NONBLOCK 避免在 FIFO 上阻塞，
+// O_NOCTTY 避免终端成为控制终端
+fn open(path: &Path) -> io::Result<File> {
+    OpenOptions::new()
+        .read(true)
+        .custom_flags(libc::O_NONBLOCK | libc::O_NOCTTY | libc::O_CLOEXEC)
+        .open(path)
+}
+
+// current 为按路径获取的权限
+fn adjust_by_path(path: &Path, current: u16, add: u16, remove: u16) -> io::Result<u16> {
+    let mode = apply(current, add, remove);
+    if mode != current {
+        super::chmod(path, libc::mode_t::from(mode))?;
+    }
+    Ok(mode)
+}
+
+// 未在 add、remove 中指明的位（包括 S_ISUID、S_ISGID、S_ISVTX）保持不变
+fn apply(current: u16, add: u16, remove: u16) -> u16 {
+    (current | (add & modes::ALL_BITS)) & ! (remove & modes::ALL_BITS)
+}
@@ -0,0 +1,97 @@
+//! Linux 上的文件属性（chattr），通过 statx(2) 的 stx_attributes 获取
+//!
+//! 与 macOS 的 BsdFlags 类似，这些属性独立于权限位，如设置了 +i 的文件，
+//! 即便权限为 0644，也无法写入或删除。无需像 lsattr 那样打开文件并调用
+//! FS_IOC_GETFLAGS ioctl。
+use std::{
+    io,
+    ops::BitOr,
+    path::Path,
+};
+
+/// 文件的属性，即 statx 中的 stx_attributes
+/// 帮助手册[statx(2)](https://man7.org/linux/man-pages/man2/statx.2.html)
+///
+/// 文件系统不支持的属性总是未设置，见 supported。
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub struct FileAttributes {
+    bits: u64,
+    supported: u64,
+}
+
+impl FileAttributes {
+    /// 由文件系统压缩（chattr +c）
+    pub const COMPRESSED: FileAttributes = FileAttributes::from_bits(libc::STATX_ATTR_COMPRESSED as u64);
+    /// 不可变更（chattr +i）
+    pub const IMMUTABLE: FileAttributes = FileAttributes::from_bits(libc::STATX_ATTR_IMMUTABLE as u64);
+    /// 仅可追加写入（chattr +a）
+    pub const APPEND: FileAttributes = FileAttributes::from_bits(libc::STATX_ATTR_APPEND as u64);
+    /// 不进行 dump 备份（chattr +d）
+    pub const NODUMP: FileAttributes = FileAttributes::from_bits(libc::STATX_ATTR_NODUMP as u64);
+    /// 由文件系统加密
+    pub const ENCRYPTED: FileAttributes = FileAttributes::from_bits(libc::STATX_ATTR_ENCRYPTED as u64);
+    /// 自动挂载点
+    pub const AUTOMOUNT: FileAttributes = FileAttributes::from_bits(libc::STATX_ATTR_AUTOMOUNT as u64);
+    /// 挂载点的根
+    pub const MOUNT_ROOT: FileAttributes = FileAttributes::from_bits(libc::STATX_ATTR_MOUNT_ROOT as u64);
+    /// 启用了 fs-verity
+    pub const VERITY: FileAttributes = FileAttributes::from_bits(libc::STATX_ATTR_VERITY as u64);
+
+    pub const fn from_bits(bits: u64) -> FileAttributes {
+        FileAttributes { bits, supported: 0 }
+    }
+
+    pub fn bits(&self) -> u64 {
+        self.bits
+    }
+
+    /// 文件系统支持（能够报告）的属性，即 stx_attributes_mask
+    pub fn supported(&self) -> FileAttributes {
+        FileAttributes::from_bits(self.supported)
+    }
+
+    /// 是否包含 other 中的全部属性
+    pub fn contains(&self, other: FileAttributes) -> bool {
+        self.bits & other.bits == other.bits
+    }
+
+    pub fn is_immutable(&self) -> bool {
+        self.contains(FileAttributes::IMMUTABLE)
+    }
+
+    pub fn is_append_only(&self) -> bool {
+        self.contains(FileAttributes::APPEND)
+    }
+}
+
+impl BitOr for FileAttributes {
+    type Output = FileAttributes;
+
+    fn bitor(self, rhs: FileAttributes) -> FileAttributes {
+        FileAttributes {
+            bits: self.bits | rhs.bits,
+            supported: self.supported | rhs.supported,
+        }
+    }
+}
+
+// statx 不可用时（旧内核、非 glibc/musl）返回 Unsupported
+pub(crate) fn file_attributes(path: &Path) -> io::Result<FileAttributes> {
+    #[cfg(any(target_env = "gnu", target_env = "musl"))]
+    {
+        let buf = super::c_path(path)?;
+        let buf_ptr = buf.as_ptr() as *const libc::c_char;
+        // stx_attributes 总是返回，无需在 mask 中请求
+        if let Some(stx) = super::stat::statx(libc::AT_FDCWD, buf_ptr, true, 0) {
+            let stx = stx?;
+            return Ok(FileAttributes {
+                bits: stx.stx_attributes & stx.stx_attributes_mask,
+                supported: stx.stx_attributes_mask,
+            })
+        }
+    }
+    #[cfg(not(any(target_env = "gnu", target_env = "musl")))]
+    let _ = path;
+
+    Err(io::Error::from(io::ErrorKind::Unsupported))
+}
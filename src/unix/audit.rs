@@ -0,0 +1,123 @@
+//! 目录树的权限审计
+use std::{
+    io,
+    path::{Path, PathBuf},
+};
+
+use crate::{modes, FileKind, Mode};
+
+use super::{
+    stat::Stat,
+    walk::walk,
+};
+
+/// audit_tree 中每个路径的审计结果
+#[derive(Debug)]
+pub struct AuditEntry {
+    pub path: PathBuf,
+    /// 文件类型（不跟随符号链接），无法 lstat 或类型未知时为 None
+    pub kind: Option<FileKind>,
+    /// 权限，包括 S_ISUID、S_ISGID、S_ISVTX
+    pub mode: u16,
+    pub uid: u32,
+    pub gid: u32,
+    /// 其他用户可写（符号链接除外）
+    pub world_writable: bool,
+    pub setuid: bool,
+    pub setgid: bool,
+    /// 是否设置了文件 capability，仅 Linux
+    #[cfg(target_os = "linux")]
+    pub file_capabilities: bool,
+    /// 检查此路径时遇到的错误
+    /// 无法 lstat 时，其余字段均为 None、0 或 false；无法读取目录时，其余字段有效
+    pub error: Option<io::Error>,
+}
+
+impl AuditEntry {
+    fn new(path: &Path, stat: io::Result<Stat>,
+           dir_err: Option<io::Error>) -> AuditEntry {
+        let mut entry = AuditEntry {
+            path: path.to_path_buf(),
+            kind: None,
+            mode: 0,
+            uid: 0,
+            gid: 0,
+            world_writable: false,
+            setuid: false,
+            setgid: false,
+            #[cfg(target_os = "linux")]
+            file_capabilities: false,
+            error: None,
+        };
+
+        let stat = match stat {
+            Ok(stat) => stat,
+            Err(err) => {
+                entry.error = Some(err);
+                return entry
+            }
+        };
+        let mode = stat.perm();
+        entry.kind = stat.kind();
+        entry.mode = mode;
+        entry.uid = stat.uid;
+        entry.gid = stat.gid;
+        entry.world_writable = entry.kind != Some(FileKind::Symlink) && mode & modes::OTHER_W != 0;
+        entry.setuid = mode & modes::SETUID != 0;
+        entry.setgid = mode & modes::SETGID != 0;
+        #[cfg(target_os = "linux")]
+        {
+            if stat.is_file() {
+                match super::file_caps::file_capabilities(path) {
+                    Ok(caps) => entry.file_capabilities = caps.is_some(),
+                    Err(err) => entry.error = Some(err),
+                }
+            }
+        }
+        if dir_err.is_some() {
+            entry.error = dir_err;
+        }
+        entry
+    }
+}
+
+pub(crate) fn audit_tree(root: &Path) -> io::Result<Vec<AuditEntry>> {
+    // 起始路径本身无法访问时，直接返回错误
+    root.symlink_metadata()?;
+
+    let mut entries = Vec::new();
+    walk(root, |path, stat, dir_err| {
+        entries.push(AuditEntry::new(path, stat, dir_err));
+    });
+
+    Ok(entries)
+}
+
+// 收集权限满足 predicate 的路径，跳过符号链接及无法 lstat 的路径
+// files_only 为 true 时只收集普通文件；无法读取的目录本身仍参与匹配，其下的路径被跳过
+pub(crate) fn find_matching(root: &Path, files_only: bool, predicate: &dyn Fn(Mode) -> bool)
+    -> io::Result<Vec<AuditEntry>> {
+    root.symlink_metadata()?;
+
+    let mut entries = Vec::new();
+    walk(root, |path, stat, dir_err| {
+        let matched = match stat {
+            Ok(ref stat) => ! stat.is_symlink() && (! files_only || stat.is_file())
+                && predicate(Mode::new(stat.perm())),
+            Err(_) => false,
+        };
+        if matched {
+            entries.push(AuditEntry::new(path, stat, dir_err));
+        }
+    });
+
+    Ok(entries)
+}
+
+// walk_parallel 保持先序，结果与 audit_tree 一样按路径排序
+#[cfg(feature = "rayon")]
+pub(crate) fn par_audit_tree(root: &Path) -> io::Result<Vec<AuditEntry>> {
+    root.symlink_metadata()?;
+
+    Ok(super::walk::walk_parallel(root, AuditEntry::new))
+}
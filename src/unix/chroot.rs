@@ -0,0 +1,198 @@
+//! 在另一个根目录（如 chroot 或待打包的目录）下解析路径及检查权限
+use std::{
+    ffi::OsString,
+    fs::{self, File},
+    io,
+    path::{Component, Path, PathBuf},
+};
+
+use super::{
+    compute::{self, AccessMode},
+    inspect::Credentials,
+    stat::{self, Stat},
+};
+
+// 解析时最多跟随的符号链接数，同 Linux 的 MAXSYMLINKS
+const MAX_SYMLINKS: usize = 40;
+
+/// 以 root 作为根目录解析路径，同 openat2(2) 的 RESOLVE_IN_ROOT
+///
+/// 绝对路径及符号链接的绝对目标均相对于 root 解析，".." 不会越过 root，因而待检查
+/// 目录中指向 /etc/passwd 的符号链接解析为 root 下的 etc/passwd，而不是主机上的文件。
+/// 相对路径同样相对于 root。
+///
+/// ```
+/// # use std::{fs, io::Read, os::unix::fs::symlink};
+/// # use path_permission::{AccessContext, AccessMode, Mode, PathPermission};
+/// # let root = std::env::temp_dir().join(format!("pp-chroot-{}", std::process::id()));
+/// # fs::create_dir_all(root.join("etc")).unwrap();
+/// # fs::create_dir_all(root.join("var")).unwrap();
+/// fs::write(root.join("etc/passwd"), b"staged").unwrap();
+/// symlink("/etc/passwd", root.join("var/passwd")).unwrap();
+/// symlink("../../../../etc", root.join("var/etc")).unwrap();
+///
+/// let context = AccessContext::new(&root);
+/// assert_eq!(context.resolve("/var/passwd").unwrap(), root.join("etc/passwd"));
+/// assert_eq!(context.resolve("var/etc/passwd").unwrap(), root.join("etc/passwd"));
+///
+/// let mut content = String::new();
+/// context.open("/var/passwd").unwrap().read_to_string(&mut content).unwrap();
+/// assert_eq!(content, "staged");
+///
+/// assert!(context.access("/var/passwd", AccessMode::READ).unwrap());
+/// assert!(! context.access("/var/missing", AccessMode::READ).unwrap());
+/// root.join("etc/passwd").set_mode(Mode::new(0o644)).unwrap();
+/// assert!(! context.access("/var/passwd", AccessMode::EXECUTE).unwrap());
+/// # fs::remove_dir_all(&root).unwrap();
+/// ```
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct AccessContext {
+    root: PathBuf,
+}
+
+impl AccessContext {
+    pub fn new<P: AsRef<Path>>(root: P) -> AccessContext {
+        AccessContext {
+            root: root.as_ref().to_path_buf(),
+        }
+    }
+
+    pub fn root(&self) -> &Path {
+        &self.root
+    }
+
+    /// 在 root 下解析 path，跟随各级符号链接，返回实际的路径（以 root 开头，不含符号链接）
+    /// 路径的任一部分不存在时返回 NotFound 错误，符号链接过多时返回 ELOOP。
+    pub fn resolve<P: AsRef<Path>>(&self, path: P) -> io::Result<PathBuf> {
+        // search 总是返回 true，结果不会为 None
+        Ok(self.resolve_with(path.as_ref(), |_| true)?.unwrap_or_default())
+    }
+
+    /// 在 root 下以只读方式打开 path
+    /// Linux 5.6+ 上通过 openat2(2) 的 RESOLVE_IN_ROOT 由内核解析，解析与打开之间路径
+    /// 不会被替换；其它情况下先以 resolve 解析，再打开。
+    pub fn open<P: AsRef<Path>>(&self, path: P) -> io::Result<File> {
+        #[cfg(all(target_os = "linux", any(target_env = "gnu", target_env = "musl")))]
+        {
+            if let Some(file) = openat2_in_root(&self.root, path.as_ref())? {
+                return Ok(file)
+            }
+        }
+        File::open(self.resolve(path)?)
+    }
+
+    /// 在 root 下解析 path，按权限位计算当前进程（有效 uid/gid 及附加组）是否有 amode 权限
+    /// 解析时搜索的每一个目录都需要执行权限，规则同 access_for；路径不存在时返回 Ok(false)。
+    pub fn access<P: AsRef<Path>>(&self, path: P, amode: AccessMode) -> io::Result<bool> {
+        let credentials = Credentials::current()?;
+        let allows = |stat: &Stat, amode| {
+            compute::stat_allows(stat, credentials.uid, &credentials.gids, amode,
+                                 credentials.privilege)
+        };
+        let resolved = match self.resolve_with(path.as_ref(), |dir| allows(dir, AccessMode::EXECUTE)) {
+            Ok(Some(resolved)) => resolved,
+            Ok(None) => return Ok(false),
+            Err(ref err) if super::is_missing(err) => return Ok(false),
+            Err(err) => return Err(err),
+        };
+        Ok(allows(&stat::stat(&resolved, false)?, amode))
+    }
+
+    // 解析 path，对每个被搜索的目录调用 search，返回 false 时停止解析并返回 Ok(None)
+    fn resolve_with<F>(&self, path: &Path, mut search: F) -> io::Result<Option<PathBuf>>
+    where
+        F: FnMut(&Stat) -> bool,
+    {
+        let mut resolved = self.root.clone();
+        // resolved 在 root 之下的层数
+        let mut depth = 0;
+        // 待解析的部分，逆序存放
+        let mut pending = Vec::new();
+        push_components(&mut pending, path);
+        let mut links = 0;
+
+        while let Some(name) = pending.pop() {
+            if name == ".." {
+                if depth > 0 {
+                    resolved.pop();
+                    depth -= 1;
+                }
+                continue
+            }
+            if ! search(&stat::stat(&resolved, true)?) {
+                return Ok(None)
+            }
+            let next = resolved.join(&name);
+            if ! stat::stat(&next, false)?.is_symlink() {
+                resolved = next;
+                depth += 1;
+                continue
+            }
+
+            links += 1;
+            if links > MAX_SYMLINKS {
+                return Err(io::Error::from_raw_os_error(libc::ELOOP))
+            }
+            let target = fs::read_link(&next)?;
+            if target.is_absolute() {
+                resolved = self.root.clone();
+                depth = 0;
+            }
+            push_components(&mut pending, &target);
+        }
+        Ok(Some(resolved))
+    }
+}
+
+// 将路径的各部分逆序压入 pending，忽略根目录及 "."
+fn push_components(pending: &mut Vec<OsString>, path: &Path) {
+    let start = pending.len();
+    for component in path.components() {
+        match component {
+            Component::Normal(name) => pending.push(name.to_os_string()),
+            Component::ParentDir => pending.push(OsString::from("..")),
+            Component::RootDir | Component::CurDir | Component::Prefix(_) => {}
+        }
+    }
+    pending[start..].reverse();
+}
+
+// 通过 openat2(2) 以 RESOLVE_IN_ROOT 打开，内核不支持（ENOSYS，或被 seccomp 以 EPERM 拒绝）
+// 时返回 None，由调用者退回至手动解析，之后不再尝试
+#[cfg(all(target_os = "linux", any(target_env = "gnu", target_env = "musl")))]
+fn openat2_in_root(root: &Path, path: &Path) -> io::Result<Option<File>> {
+    use std::{
+        os::unix::{fs::OpenOptionsExt, io::{AsRawFd, FromRawFd}},
+        sync::atomic::{AtomicBool, Ordering},
+    };
+
+    static UNAVAILABLE: AtomicBool = AtomicBool::new(false);
+    if UNAVAILABLE.load(Ordering::Relaxed) {
+        return Ok(None)
+    }
+
+    let dir = fs::OpenOptions::new()
+        .read(true)
+        .custom_flags(libc::O_DIRECTORY | libc::O_PATH)
+        .open(root)?;
+    let buf = super::c_path(path)?;
+    let mut how: libc::open_how = unsafe { std::mem::zeroed() };
+    how.flags = (libc::O_RDONLY | libc::O_CLOEXEC) as u64;
+    how.resolve = libc::RESOLVE_IN_ROOT;
+
+    let fd = unsafe {
+        libc::syscall(libc::SYS_openat2, dir.as_raw_fd(), buf.as_ptr() as *const libc::c_char,
+                      &how as *const libc::open_how, std::mem::size_of::<libc::open_how>())
+    };
+    if fd < 0 {
+        let err = io::Error::last_os_error();
+        return match err.raw_os_error() {
+            Some(libc::ENOSYS) | Some(libc::EPERM) => {
+                UNAVAILABLE.store(true, Ordering::Relaxed);
+                Ok(None)
+            }
+            _ => Err(err),
+        }
+    }
+    Ok(Some(unsafe { File::from_raw_fd(fd as libc::c_int) }))
+}
@@ -0,0 +1,300 @@
+//! 根据 stat 的结果计算访问权限，而非调用 access(2)
+//!
+//! 计算的规则与内核一致：依次匹配所有者、所属组、其他用户，只检查第一个
+//! 匹配到的分类；特权（root 或相应的 capability）可绕过部分检查。
+//! 注意：未考虑 ACL 及 SELinux 等强制访问控制。
+use std::{
+    fs::Metadata,
+    io,
+    ops::BitOr,
+    os::raw::c_int,
+};
+
+use crate::modes;
+
+use super::stat::Stat;
+
+/// 访问权限，可通过 `|` 组合，如：`AccessMode::READ | AccessMode::WRITE`
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct AccessMode(c_int);
+
+impl AccessMode {
+    /// 读，即 R_OK
+    pub const READ: AccessMode = AccessMode(libc::R_OK);
+    /// 写，即 W_OK
+    pub const WRITE: AccessMode = AccessMode(libc::W_OK);
+    /// 执行（对目录而言为进入），即 X_OK
+    pub const EXECUTE: AccessMode = AccessMode(libc::X_OK);
+
+    /// 返回可直接用于 access(2) 的值
+    pub fn bits(&self) -> c_int {
+        self.0
+    }
+
+    /// 是否包含 other 中的全部权限
+    pub fn contains(&self, other: AccessMode) -> bool {
+        self.0 & other.0 == other.0
+    }
+}
+
+impl BitOr for AccessMode {
+    type Output = AccessMode;
+
+    fn bitor(self, rhs: AccessMode) -> AccessMode {
+        AccessMode(self.0 | rhs.0)
+    }
+}
+
+/// 当前进程的 capability 集合，仅 Linux
+/// 帮助手册[capabilities(7)](https://man7.org/linux/man-pages/man7/capabilities.7.html)
+#[cfg(target_os = "linux")]
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct Capabilities {
+    pub effective: u64,
+    pub permitted: u64,
+    pub inheritable: u64,
+}
+
+#[cfg(target_os = "linux")]
+impl Capabilities {
+    pub const CAP_CHOWN: u32 = 0;
+    /// 绕过文件的读、写、执行权限检查
+    pub const CAP_DAC_OVERRIDE: u32 = 1;
+    /// 绕过文件的读权限及目录的读、执行权限检查
+    pub const CAP_DAC_READ_SEARCH: u32 = 2;
+    /// 绕过要求为文件所有者的检查，如 chmod
+    pub const CAP_FOWNER: u32 = 3;
+
+    /// 有效集合中是否包含 cap，cap 超出范围（64 及以上）时返回 false
+    pub fn has_effective(&self, cap: u32) -> bool {
+        1u64.checked_shl(cap).is_some_and(|bit| self.effective & bit != 0)
+    }
+}
+
+/// 获取当前线程的 capability 集合，通过 capget(2)
+/// capability 属于线程而非进程，各线程可能不同（如某个线程已放弃部分 capability），
+/// 因此返回的是调用此函数的线程的集合。
+/// 帮助手册[capget(2)](https://man7.org/linux/man-pages/man2/capget.2.html)
+///
+/// ```
+/// # use path_permission::process_capabilities;
+/// let caps = process_capabilities().unwrap();
+/// // 有效集合是允许集合的子集
+/// assert_eq!(caps.effective & ! caps.permitted, 0);
+/// assert!(! caps.has_effective(64));
+/// // 子线程中获取的是其自身的集合，未变更时与主线程相同
+/// let in_thread = std::thread::spawn(|| process_capabilities().unwrap()).join().unwrap();
+/// assert_eq!(in_thread, caps);
+/// ```
+#[cfg(target_os = "linux")]
+pub fn process_capabilities() -> io::Result<Capabilities> {
+    // linux/capability.h 中的 _LINUX_CAPABILITY_VERSION_3，集合为两个 32 位的值
+    const VERSION_3: u32 = 0x2008_0522;
+
+    #[repr(C)]
+    struct Header {
+        version: u32,
+        // 0 表示调用者所在的线程
+        pid: c_int,
+    }
+
+    #[repr(C)]
+    #[derive(Clone, Copy, Default)]
+    struct Data {
+        effective: u32,
+        permitted: u32,
+        inheritable: u32,
+    }
+
+    let mut header = Header { version: VERSION_3, pid: 0 };
+    let mut data = [Data::default(); 2];
+    let result = unsafe {
+        libc::syscall(libc::SYS_capget, &mut header as *mut Header, data.as_mut_ptr())
+    };
+    if result != 0 {
+        return Err(io::Error::last_os_error())
+    }
+
+    let join = |low: u32, high: u32| u64::from(high) << 32 | u64::from(low);
+    Ok(Capabilities {
+        effective: join(data[0].effective, data[1].effective),
+        permitted: join(data[0].permitted, data[1].permitted),
+        inheritable: join(data[0].inheritable, data[1].inheritable),
+    })
+}
+
+/// 获取当前进程的附加组（getgroups(2)），可能包含有效 gid
+pub fn current_supplementary_groups() -> io::Result<Vec<u32>> {
+    loop {
+        let count = unsafe { libc::getgroups(0, std::ptr::null_mut()) };
+        if count < 0 {
+            return Err(io::Error::last_os_error())
+        }
+
+        let mut groups = vec![0 as libc::gid_t; count as usize];
+        let count = unsafe { libc::getgroups(count, groups.as_mut_ptr()) };
+        if count < 0 {
+            let err = io::Error::last_os_error();
+            // 两次调用之间附加组增加了，重新获取
+            if err.raw_os_error() == Some(libc::EINVAL) {
+                continue
+            }
+            return Err(err)
+        }
+        groups.truncate(count as usize);
+        return Ok(groups)
+    }
+}
+
+/// 获取权限检查时当前进程所属的全部组，即有效 gid 及附加组，已排序、去重
+///
+/// ```
+/// # use path_permission::{current_groups, effective_gid, in_group};
+/// let groups = current_groups().unwrap();
+/// assert!(groups.contains(&effective_gid()));
+/// assert!(groups.windows(2).all(|w| w[0] < w[1]));
+/// for &gid in &groups {
+///     assert!(in_group(gid).unwrap());
+/// }
+/// ```
+pub fn current_groups() -> io::Result<Vec<u32>> {
+    let mut gids = current_gids()?;
+    gids.sort_unstable();
+    gids.dedup();
+    Ok(gids)
+}
+
+/// 当前进程的实际 gid（getgid(2)），即登录时的主组
+pub fn primary_gid() -> u32 {
+    unsafe { libc::getgid() }
+}
+
+/// 当前进程的有效 gid（getegid(2)），创建文件时作为其所属组，并用于权限检查
+pub fn effective_gid() -> u32 {
+    unsafe { libc::getegid() }
+}
+
+// uid 是否为当前进程的有效 uid：内核以有效 uid 判断是否为文件的所有者，而非实际 uid
+pub(crate) fn is_current_user(uid: u32) -> bool {
+    uid == unsafe { libc::geteuid() }
+}
+
+/// 当前进程是否属于 gid 组，即其为有效 gid 或附加组之一
+pub fn in_group(gid: u32) -> io::Result<bool> {
+    Ok(gid == effective_gid() || current_supplementary_groups()?.contains(&gid))
+}
+
+// 当前进程的有效 gid 及附加组
+pub(crate) fn current_gids() -> io::Result<Vec<u32>> {
+    let mut gids = current_supplementary_groups()?;
+    gids.push(effective_gid());
+    Ok(gids)
+}
+
+// 可绕过权限位检查的特权
+#[derive(Clone, Copy, Debug, Default)]
+pub(crate) struct Privilege {
+    pub dac_override: bool,
+    pub dac_read_search: bool,
+    // 绕过要求为文件所有者的检查
+    pub fowner: bool,
+}
+
+impl Privilege {
+    // 模拟某个用户时，只有 root 拥有特权
+    pub fn of_uid(uid: u32) -> Privilege {
+        Privilege {
+            dac_override: uid == 0,
+            dac_read_search: uid == 0,
+            fowner: uid == 0,
+        }
+    }
+
+    // 当前线程的特权，Linux 上以 capability 为准
+    // 需要一次系统调用，逐个条目检查时应在循环外获取一次
+    #[cfg(target_os = "linux")]
+    pub fn current() -> Privilege {
+        match process_capabilities() {
+            Ok(caps) => Privilege {
+                dac_override: caps.has_effective(Capabilities::CAP_DAC_OVERRIDE),
+                dac_read_search: caps.has_effective(Capabilities::CAP_DAC_READ_SEARCH),
+                fowner: caps.has_effective(Capabilities::CAP_FOWNER),
+            },
+            Err(_) => Privilege::of_uid(unsafe { libc::geteuid() }),
+        }
+    }
+
+    #[cfg(not(target_os = "linux"))]
+    pub fn current() -> Privilege {
+        Privilege::of_uid(unsafe { libc::geteuid() })
+    }
+}
+
+// 计算 uid（所属组为 gids）对 metadata 所描述的文件是否有 amode 权限
+pub(crate) fn mode_allows(metadata: &Metadata, uid: u32, gids: &[u32],
+                          amode: AccessMode, privilege: Privilege) -> bool {
+    stat_allows(&Stat::from_metadata(metadata), uid, gids, amode, privilege)
+}
+
+// 同 mode_allows，但基于 Stat，供 testing 模块等无真实文件的场景使用
+pub(crate) fn stat_allows(stat: &Stat, uid: u32, gids: &[u32],
+                          amode: AccessMode, privilege: Privilege) -> bool {
+    let mode = stat.mode;
+    let is_dir = stat.is_dir();
+
+    if privilege.dac_override {
+        // 可读写任意文件，但只能执行至少有一个执行位的文件
+        return ! amode.contains(AccessMode::EXECUTE) || is_dir || mode & u32::from(modes::ALL_X) != 0
+    }
+
+    let mut wanted = amode.bits() as u32;
+    if privilege.dac_read_search {
+        wanted &= ! libc::R_OK as u32;
+        if is_dir {
+            wanted &= ! libc::X_OK as u32;
+        }
+    }
+
+    // 只检查第一个匹配到的分类
+    let class = if stat.uid == uid {
+        (mode >> 6) & 0o7
+    } else if gids.contains(&stat.gid) {
+        (mode >> 3) & 0o7
+    } else {
+        mode & 0o7
+    };
+
+    class & wanted == wanted
+}
+
+// 需加入 stat 的权限中的最少的位，使 uid（所属组为 gids）得到 amode 权限，已有权限时为 0
+// 只在 uid 所匹配到的分类（所有者、所属组或其他用户）中增加
+pub(crate) fn grant_bits(stat: &Stat, uid: u32, gids: &[u32],
+                         amode: AccessMode, privilege: Privilege) -> u16 {
+    if stat_allows(stat, uid, gids, amode, privilege) {
+        return 0
+    }
+    if privilege.dac_override {
+        // 只可能是缺少执行位，任一执行位即可
+        return modes::OWNER_X
+    }
+
+    let mut wanted = amode.bits() as u32;
+    if privilege.dac_read_search {
+        wanted &= ! libc::R_OK as u32;
+    }
+    let shift = if stat.uid == uid {
+        6
+    } else if gids.contains(&stat.gid) {
+        3
+    } else {
+        0
+    };
+    ((wanted << shift) & ! stat.mode & 0o777) as u16
+}
+
+// 设置了 S_ISVTX 的目录中，只有文件所有者、目录所有者，或拥有特权（root 或
+// CAP_FOWNER）的进程才能删除或重命名其中的条目
+pub(crate) fn sticky_allows(euid: u32, owner: u32, dir_owner: u32, privilege: Privilege) -> bool {
+    euid == owner || euid == dir_owner || privilege.fowner
+}
@@ -0,0 +1,105 @@
+//! 在指定目录中创建条目的前提条件
+use std::{
+    ffi::OsStr,
+    io,
+    path::Path,
+    os::unix::{ffi::OsStrExt, fs::MetadataExt},
+};
+
+use super::{compute::Privilege, effective_access, removable_in, PathPermission};
+
+/// check_create_in 的结果，指出在目录中创建条目会因哪个条件而失败
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum CreateCheck {
+    /// 可以创建
+    Allowed,
+    /// 目录不存在
+    DirectoryMissing,
+    /// 路径存在，但不是目录
+    NotADirectory,
+    /// 名称为空、为 "." 或 ".."，或含有 '/'、NUL
+    InvalidName,
+    /// 名称超出文件系统的 NAME_MAX
+    NameTooLong,
+    /// 无目录的写和执行权限
+    PermissionDenied,
+    /// 条目已存在（且未允许覆盖）
+    AlreadyExists,
+    /// 条目已存在，但无法将其替换（删除），如 S_ISVTX 下不满足所属用户的条件
+    NotReplaceable,
+}
+
+impl CreateCheck {
+    pub fn is_allowed(&self) -> bool {
+        *self == CreateCheck::Allowed
+    }
+}
+
+/// 判断能否在目录 dir 中创建名为 name 的条目，同 `dir.can_create_in(name)`
+///
+/// ```
+/// # use std::{ffi::OsStr, fs};
+/// # use path_permission::can_create_in;
+/// # let dir = std::env::temp_dir().join(format!("pp-can-create-in-{}", std::process::id()));
+/// # fs::create_dir_all(&dir).unwrap();
+/// fs::write(dir.join("existing"), b"").unwrap();
+/// assert!(can_create_in(&dir, OsStr::new("fresh")).unwrap());
+/// assert!(! can_create_in(&dir, OsStr::new("existing")).unwrap());
+/// # fs::remove_dir_all(&dir).unwrap();
+/// ```
+pub fn can_create_in(dir: &Path, name: &OsStr) -> io::Result<bool> {
+    dir.can_create_in(name)
+}
+
+pub(crate) fn check_create_in(dir: &Path, name: &OsStr, overwrite: bool) -> io::Result<CreateCheck> {
+    match dir.metadata() {
+        Ok(metadata) if metadata.is_dir() => {}
+        Ok(_) => return Ok(CreateCheck::NotADirectory),
+        Err(ref e) if e.kind() == io::ErrorKind::NotFound => {
+            return Ok(CreateCheck::DirectoryMissing)
+        }
+        Err(e) => return Err(e),
+    }
+
+    let bytes = name.as_bytes();
+    if bytes.is_empty() || bytes == b"." || bytes == b".."
+        || bytes.iter().any(|&b| b == b'/' || b == 0) {
+        return Ok(CreateCheck::InvalidName)
+    }
+    if let Some(name_max) = name_max(dir) {
+        if bytes.len() > name_max {
+            return Ok(CreateCheck::NameTooLong)
+        }
+    }
+
+    if ! effective_access(dir, libc::X_OK + libc::W_OK)? {
+        return Ok(CreateCheck::PermissionDenied)
+    }
+
+    match dir.join(name).symlink_metadata() {
+        Ok(_) if ! overwrite => Ok(CreateCheck::AlreadyExists),
+        Ok(existing) => {
+            if removable_in(dir, existing.uid(), Privilege::current())? {
+                Ok(CreateCheck::Allowed)
+            } else {
+                Ok(CreateCheck::NotReplaceable)
+            }
+        }
+        Err(_) => Ok(CreateCheck::Allowed),
+    }
+}
+
+// 目录所在文件系统的 NAME_MAX，没有限制或无法获取时返回 None
+fn name_max(dir: &Path) -> Option<usize> {
+    let buf = super::c_path(dir).ok()?;
+    let buf_ptr = buf.as_ptr() as *const libc::c_char;
+
+    let result = unsafe {
+        libc::pathconf(buf_ptr, libc::_PC_NAME_MAX)
+    };
+    if result > 0 {
+        Some(result as usize)
+    } else {
+        None
+    }
+}
@@ -0,0 +1,100 @@
+//! macOS 上的文件标志（chflags）
+//!
+//! 这些标志独立于权限位，如设置了 uchg 的文件，即便权限为 0644，
+//! 也无法写入或删除。
+use std::{
+    io,
+    ops::BitOr,
+    path::Path,
+    os::macos::fs::MetadataExt,
+};
+
+use super::c_path;
+
+/// 文件的 BSD 标志，即 stat 中的 st_flags
+/// 帮助手册[chflags(2)](https://www.manpagez.com/man/2/chflags/)
+///
+/// UF_* 为用户级标志，文件所有者即可设置或清除；
+/// SF_* 为系统级标志，仅 root 可设置，且在 securelevel 大于 0 时
+/// （正常的多用户模式）即使是 root 也无法清除，需进入单用户模式。
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub struct BsdFlags(u32);
+
+impl BsdFlags {
+    /// 不进行 dump 备份
+    pub const UF_NODUMP: BsdFlags = BsdFlags(libc::UF_NODUMP);
+    /// 不可变更（uchg）
+    pub const UF_IMMUTABLE: BsdFlags = BsdFlags(libc::UF_IMMUTABLE);
+    /// 仅可追加写入（uappnd）
+    pub const UF_APPEND: BsdFlags = BsdFlags(libc::UF_APPEND);
+    /// 目录在 union 挂载中不透明
+    pub const UF_OPAQUE: BsdFlags = BsdFlags(libc::UF_OPAQUE);
+    /// 在 GUI 中隐藏
+    pub const UF_HIDDEN: BsdFlags = BsdFlags(libc::UF_HIDDEN);
+    /// 已归档（arch）
+    pub const SF_ARCHIVED: BsdFlags = BsdFlags(libc::SF_ARCHIVED);
+    /// 系统级不可变更（schg）
+    pub const SF_IMMUTABLE: BsdFlags = BsdFlags(libc::SF_IMMUTABLE);
+    /// 系统级仅可追加写入（sappnd）
+    pub const SF_APPEND: BsdFlags = BsdFlags(libc::SF_APPEND);
+
+    pub fn from_bits(bits: u32) -> BsdFlags {
+        BsdFlags(bits)
+    }
+
+    pub fn bits(&self) -> u32 {
+        self.0
+    }
+
+    /// 是否包含 other 中的全部标志
+    pub fn contains(&self, other: BsdFlags) -> bool {
+        self.0 & other.0 == other.0
+    }
+
+    /// 是否设置了 UF_IMMUTABLE 或 SF_IMMUTABLE
+    pub fn is_immutable(&self) -> bool {
+        self.0 & (libc::UF_IMMUTABLE | libc::SF_IMMUTABLE) != 0
+    }
+
+    /// 是否设置了 UF_APPEND 或 SF_APPEND
+    pub fn is_append_only(&self) -> bool {
+        self.0 & (libc::UF_APPEND | libc::SF_APPEND) != 0
+    }
+}
+
+impl BitOr for BsdFlags {
+    type Output = BsdFlags;
+
+    fn bitor(self, rhs: BsdFlags) -> BsdFlags {
+        BsdFlags(self.0 | rhs.0)
+    }
+}
+
+pub(crate) fn bsd_flags(path: &Path) -> io::Result<BsdFlags> {
+    Ok(BsdFlags(path.metadata()?.st_flags()))
+}
+
+// 不跟随符号链接
+pub(crate) fn bsd_flags_nofollow(path: &Path) -> io::Result<BsdFlags> {
+    Ok(BsdFlags(path.symlink_metadata()?.st_flags()))
+}
+
+pub(crate) fn set_bsd_flags(path: &Path, flags: BsdFlags) -> io::Result<bool> {
+    let buf = c_path(path)?;
+    let buf_ptr = buf.as_ptr() as *const libc::c_char;
+
+    let result = unsafe {
+        libc::chflags(buf_ptr, flags.0)
+    };
+
+    match result {
+        0 => Ok(true),
+        _ => {
+            let err = io::Error::last_os_error();
+            match err.raw_os_error() {
+                Some(libc::EPERM) | Some(libc::EACCES) => Ok(false),
+                _ => Err(err),
+            }
+        }
+    }
+}
@@ -0,0 +1,135 @@
+//! 以一次 stat 获取 ls -l 所需的全部信息
+use std::{
+    cell::OnceCell,
+    fs::Metadata,
+    io,
+    path::Path,
+    os::unix::fs::MetadataExt,
+};
+
+use crate::{modes, FileMode, Mode};
+
+use super::users;
+
+/// 路径的类型、权限、所有者等信息，由 PathPermission::describe 创建
+///
+/// 创建时只获取一次 stat；所有者、所属组的名称在首次需要时查询，之后不再查询。
+///
+/// ```
+/// # use std::fs;
+/// # use path_permission::{Mode, PathPermission};
+/// # let path = std::env::temp_dir().join(format!("pp-describe-{}", std::process::id()));
+/// fs::write(&path, b"hello").unwrap();
+/// path.set_mode(Mode::new(0o640)).unwrap();
+///
+/// let desc = path.describe().unwrap();
+/// assert_eq!(desc.mode().bits(), path.get_mode().unwrap());
+/// assert_eq!(desc.file_mode().to_string(), path.long_format().unwrap());
+/// assert_eq!(desc.uid(), path.owner_uid().unwrap());
+/// assert_eq!(desc.gid(), path.owner_gid().unwrap());
+/// assert_eq!(desc.size(), 5);
+/// assert_eq!(desc.blocks(), path.blocks().unwrap());
+/// assert_eq!(desc.nlink(), 1);
+/// assert_eq!(desc.type_char(), '-');
+/// if desc.uid() == 0 {
+///     assert_eq!(desc.owner_name().unwrap().as_deref(), Some("root"));
+/// }
+/// # fs::remove_file(&path).unwrap();
+/// ```
+#[derive(Debug)]
+pub struct PathDescription {
+    mode: u32,
+    uid: u32,
+    gid: u32,
+    size: u64,
+    blocks: u64,
+    nlink: u64,
+    owner_name: OnceCell<Option<String>>,
+    group_name: OnceCell<Option<String>>,
+}
+
+impl PathDescription {
+    pub(crate) fn new(path: &Path, follow: bool) -> io::Result<PathDescription> {
+        let metadata = if follow {
+            path.metadata()?
+        } else {
+            path.symlink_metadata()?
+        };
+        Ok(PathDescription::from_metadata(&metadata))
+    }
+
+    fn from_metadata(metadata: &Metadata) -> PathDescription {
+        PathDescription {
+            mode: metadata.mode(),
+            uid: metadata.uid(),
+            gid: metadata.gid(),
+            size: metadata.size(),
+            blocks: metadata.blocks(),
+            nlink: metadata.nlink(),
+            owner_name: OnceCell::new(),
+            group_name: OnceCell::new(),
+        }
+    }
+
+    /// 类型及权限，即完整的 st_mode
+    pub fn file_mode(&self) -> FileMode {
+        FileMode::new(self.mode)
+    }
+
+    /// 权限，包括 S_ISUID、S_ISGID、S_ISVTX
+    pub fn mode(&self) -> Mode {
+        Mode::new((self.mode & u32::from(modes::ALL_BITS)) as u16)
+    }
+
+    /// ls -l 中表示文件类型的字符，见 FileMode::type_char
+    pub fn type_char(&self) -> char {
+        self.file_mode().type_char()
+    }
+
+    /// 所有者的 uid
+    pub fn uid(&self) -> u32 {
+        self.uid
+    }
+
+    /// 所属组的 gid
+    pub fn gid(&self) -> u32 {
+        self.gid
+    }
+
+    /// 大小（字节）
+    pub fn size(&self) -> u64 {
+        self.size
+    }
+
+    /// 实际占用的磁盘空间，以 512 字节为单位（st_blocks），同 PathPermission::blocks
+    pub fn blocks(&self) -> u64 {
+        self.blocks
+    }
+
+    /// 硬链接数
+    pub fn nlink(&self) -> u64 {
+        self.nlink
+    }
+
+    /// 所有者的用户名，首次调用时查询；uid 没有对应的用户时返回 None
+    pub fn owner_name(&self) -> io::Result<Option<String>> {
+        cached(&self.owner_name, || users::user_name(self.uid))
+    }
+
+    /// 所属组的组名，首次调用时查询；gid 没有对应的组时返回 None
+    pub fn group_name(&self) -> io::Result<Option<String>> {
+        cached(&self.group_name, || users::group_name(self.gid))
+    }
+}
+
+// 查询失败时不缓存，下次调用时重试
+fn cached<F>(cell: &OnceCell<Option<String>>, lookup: F) -> io::Result<Option<String>>
+where
+    F: FnOnce() -> io::Result<Option<String>>,
+{
+    if let Some(name) = cell.get() {
+        return Ok(name.clone())
+    }
+    let name = lookup()?;
+    Ok(cell.get_or_init(|| name).clone())
+}
@@ -0,0 +1,159 @@
+//! 比较两个路径（或两个目录树）的权限、所有者及所属组
+use std::{
+    collections::BTreeMap,
+    fmt,
+    io,
+    path::{Path, PathBuf},
+};
+
+use super::{
+    stat,
+    walk::walk,
+    PermSnapshot,
+};
+
+/// 两个路径之间权限、所有者及所属组的差异，由 diff_access 及 diff_tree 返回
+///
+/// first、second 分别为两侧的权限及所有者，为 None 表示该侧不存在此路径（仅 diff_tree）。
+/// 以紧凑的形式输出，如："mode 0644 -> 0600, owner 0 -> 33"。
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct AccessDiff {
+    pub first: Option<PermSnapshot>,
+    pub second: Option<PermSnapshot>,
+}
+
+impl AccessDiff {
+    // 两侧相同时返回 None
+    fn between(first: Option<PermSnapshot>, second: Option<PermSnapshot>) -> Option<AccessDiff> {
+        if first == second {
+            return None
+        }
+        Some(AccessDiff { first, second })
+    }
+
+    /// 两侧均存在，且权限不同
+    pub fn mode_changed(&self) -> bool {
+        self.both().is_some_and(|(a, b)| a.mode != b.mode)
+    }
+
+    /// 两侧均存在，且所有者不同
+    pub fn owner_changed(&self) -> bool {
+        self.both().is_some_and(|(a, b)| a.uid != b.uid)
+    }
+
+    /// 两侧均存在，且所属组不同
+    pub fn group_changed(&self) -> bool {
+        self.both().is_some_and(|(a, b)| a.gid != b.gid)
+    }
+
+    fn both(&self) -> Option<(&PermSnapshot, &PermSnapshot)> {
+        match (&self.first, &self.second) {
+            (Some(a), Some(b)) => Some((a, b)),
+            _ => None,
+        }
+    }
+}
+
+impl fmt::Display for AccessDiff {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let (a, b) = match (&self.first, &self.second) {
+            (Some(a), Some(b)) => (a, b),
+            (Some(_), None) => return f.write_str("only in first"),
+            (None, Some(_)) => return f.write_str("only in second"),
+            (None, None) => return Ok(()),
+        };
+        let mut parts = Vec::new();
+        if a.mode != b.mode {
+            parts.push(format!("mode {:04o} -> {:04o}", a.mode, b.mode));
+        }
+        if a.uid != b.uid {
+            parts.push(format!("owner {} -> {}", a.uid, b.uid));
+        }
+        if a.gid != b.gid {
+            parts.push(format!("group {} -> {}", a.gid, b.gid));
+        }
+        f.write_str(&parts.join(", "))
+    }
+}
+
+// 比较两个路径本身，不跟随符号链接
+pub(crate) fn diff_access(first: &Path, second: &Path) -> io::Result<Option<AccessDiff>> {
+    let first = PermSnapshot::from_stat(&stat::stat(first, false)?);
+    let second = PermSnapshot::from_stat(&stat::stat(second, false)?);
+    Ok(AccessDiff::between(Some(first), Some(second)))
+}
+
+/// 比较目录树 first、second 下相同相对路径的权限、所有者及所属组，不跟随符号链接
+/// 返回存在差异的相对路径（起始路径本身为 ""）及其差异，按路径排序；
+/// 仅一侧存在的路径同样被列出。遍历时遇到错误则返回遇到的第一个错误。
+///
+/// ```
+/// # use std::{fs, path::PathBuf};
+/// # use path_permission::{diff_tree, Mode, PathPermission};
+/// # let root = std::env::temp_dir().join(format!("pp-diff-tree-{}", std::process::id()));
+/// let (a, b) = (root.join("a"), root.join("b"));
+/// for dir in &[&a, &b] {
+///     fs::create_dir_all(dir.join("sub")).unwrap();
+///     fs::write(dir.join("sub/file"), b"").unwrap();
+///     dir.set_mode(Mode::new(0o755)).unwrap();
+///     dir.join("sub").set_mode(Mode::new(0o755)).unwrap();
+///     dir.join("sub/file").set_mode(Mode::new(0o644)).unwrap();
+/// }
+/// assert!(diff_tree(&a, &b).unwrap().is_empty());
+///
+/// b.join("sub/file").set_mode(Mode::new(0o600)).unwrap();
+/// fs::write(a.join("only-a"), b"").unwrap();
+/// let diffs = diff_tree(&a, &b).unwrap();
+/// assert_eq!(diffs.len(), 2);
+/// assert_eq!(diffs[0].0, PathBuf::from("only-a"));
+/// assert_eq!(diffs[0].1.to_string(), "only in first");
+/// assert_eq!(diffs[1].0, PathBuf::from("sub/file"));
+/// assert_eq!(diffs[1].1.to_string(), "mode 0644 -> 0600");
+/// assert!(diffs[1].1.mode_changed() && ! diffs[1].1.owner_changed());
+/// # fs::remove_dir_all(&root).unwrap();
+/// ```
+pub fn diff_tree(first: &Path, second: &Path) -> io::Result<Vec<(PathBuf, AccessDiff)>> {
+    let mut first = collect(first)?;
+    let second = collect(second)?;
+
+    let mut diffs = Vec::new();
+    for (path, b) in second {
+        let a = first.remove(&path);
+        if let Some(diff) = AccessDiff::between(a, Some(b)) {
+            diffs.push((path, diff));
+        }
+    }
+    // 余下的只存在于 first 中
+    for (path, a) in first {
+        diffs.push((path, AccessDiff { first: Some(a), second: None }));
+    }
+    diffs.sort_by(|a, b| a.0.cmp(&b.0));
+    Ok(diffs)
+}
+
+// 以相对路径为键，收集 root 下各路径的权限及所有者
+fn collect(root: &Path) -> io::Result<BTreeMap<PathBuf, PermSnapshot>> {
+    let mut entries = BTreeMap::new();
+    let mut first_err = None;
+
+    walk(root, |path, stat, dir_err| {
+        match stat {
+            Ok(stat) => {
+                // walk 返回的路径都以 root 开头
+                let relative = path.strip_prefix(root).unwrap_or(path);
+                entries.insert(relative.to_path_buf(), PermSnapshot::from_stat(&stat));
+            }
+            Err(err) => {
+                first_err.get_or_insert(err);
+            }
+        }
+        if let Some(err) = dir_err {
+            first_err.get_or_insert(err);
+        }
+    });
+
+    match first_err {
+        Some(err) => Err(err),
+        None => Ok(entries),
+    }
+}
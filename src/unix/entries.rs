@@ -0,0 +1,102 @@
+//! 按当前用户的权限过滤目录项
+use std::{
+    fs::{self, DirEntry, ReadDir},
+    io,
+    path::Path,
+};
+
+use super::{compute::AccessMode, PathPermission};
+
+/// 为 std::fs::ReadDir 增加按权限过滤的方法
+///
+/// ```
+/// # use std::fs;
+/// # use path_permission::{AccessMode, Mode, PathPermission, ReadDirExt, readable_entries};
+/// # let dir = std::env::temp_dir().join(format!("pp-entries-{}", std::process::id()));
+/// # fs::create_dir_all(&dir).unwrap();
+/// for &(name, mode) in &[("a", 0o644), ("b", 0o000), ("c", 0o600)] {
+///     fs::write(dir.join(name), b"").unwrap();
+///     dir.join(name).set_mode(Mode::new(mode)).unwrap();
+/// }
+///
+/// let mut names: Vec<_> = readable_entries(&dir).unwrap()
+///     .map(|entry| entry.file_name().into_string().unwrap())
+///     .collect();
+/// names.sort();
+/// // root 可读取任意文件
+/// if unsafe { libc::geteuid() } == 0 {
+///     assert_eq!(names, ["a", "b", "c"]);
+/// } else {
+///     assert_eq!(names, ["a", "c"]);
+/// }
+///
+/// // 任何用户都没有执行权限
+/// assert_eq!(fs::read_dir(&dir).unwrap().filter_accessible(AccessMode::EXECUTE).count(), 0);
+/// assert!(fs::read_dir(&dir).unwrap()
+///     .try_filter_accessible(AccessMode::READ)
+///     .all(|entry| entry.is_ok()));
+/// # fs::remove_dir_all(&dir).unwrap();
+/// ```
+pub trait ReadDirExt {
+    /// 只保留当前用户（实际 uid/gid）有 amode 权限的目录项，同 access(2)，跟随符号链接
+    /// 注意：读取目录项或检查权限时出错的目录项（如悬空的符号链接）会被静默地跳过，
+    /// 需要得知时请使用 try_filter_accessible。
+    fn filter_accessible(self, amode: AccessMode) -> AccessibleEntries;
+
+    /// 同 filter_accessible，但读取目录项或检查权限时的错误作为 Err 返回，不会被跳过
+    fn try_filter_accessible(self, amode: AccessMode) -> TryAccessibleEntries;
+}
+
+impl ReadDirExt for ReadDir {
+    fn filter_accessible(self, amode: AccessMode) -> AccessibleEntries {
+        AccessibleEntries(TryAccessibleEntries { read_dir: self, amode })
+    }
+
+    fn try_filter_accessible(self, amode: AccessMode) -> TryAccessibleEntries {
+        TryAccessibleEntries { read_dir: self, amode }
+    }
+}
+
+/// 由 ReadDirExt::filter_accessible 返回，跳过出错的目录项
+#[derive(Debug)]
+pub struct AccessibleEntries(TryAccessibleEntries);
+
+impl Iterator for AccessibleEntries {
+    type Item = DirEntry;
+
+    fn next(&mut self) -> Option<DirEntry> {
+        self.0.by_ref().find_map(Result::ok)
+    }
+}
+
+/// 由 ReadDirExt::try_filter_accessible 返回
+#[derive(Debug)]
+pub struct TryAccessibleEntries {
+    read_dir: ReadDir,
+    amode: AccessMode,
+}
+
+impl Iterator for TryAccessibleEntries {
+    type Item = io::Result<DirEntry>;
+
+    fn next(&mut self) -> Option<io::Result<DirEntry>> {
+        for entry in &mut self.read_dir {
+            let entry = match entry {
+                Ok(entry) => entry,
+                Err(err) => return Some(Err(err)),
+            };
+            match entry.path().access(self.amode.bits()) {
+                Ok(true) => return Some(Ok(entry)),
+                Ok(false) => {}
+                Err(err) => return Some(Err(err)),
+            }
+        }
+        None
+    }
+}
+
+/// 列出目录中当前用户可读的目录项，即 `fs::read_dir(dir)?.filter_accessible(AccessMode::READ)`
+/// 出错的目录项会被静默地跳过，见 ReadDirExt::filter_accessible。
+pub fn readable_entries(dir: &Path) -> io::Result<AccessibleEntries> {
+    Ok(fs::read_dir(dir)?.filter_accessible(AccessMode::READ))
+}
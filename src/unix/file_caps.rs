@@ -0,0 +1,112 @@
+//! 文件的 capability（security.capability 扩展属性）
+//!
+//! 如 `setcap cap_net_raw+ep` 所设置的，权限位无法体现，但同样会提升执行者的权限。
+use std::{
+    convert::TryInto,
+    io,
+    path::Path,
+};
+
+use super::xattr;
+
+const XATTR_NAME: &str = "security.capability";
+
+const VFS_CAP_REVISION_MASK: u32 = 0xFF00_0000;
+const VFS_CAP_REVISION_1: u32 = 0x0100_0000;
+const VFS_CAP_REVISION_2: u32 = 0x0200_0000;
+const VFS_CAP_REVISION_3: u32 = 0x0300_0000;
+const VFS_CAP_FLAGS_EFFECTIVE: u32 = 0x0000_0001;
+
+/// 解码后的文件 capability
+/// 帮助手册[capabilities(7)](https://man7.org/linux/man-pages/man7/capabilities.7.html)
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct FileCaps {
+    /// 格式版本：1、2 或 3
+    pub version: u8,
+    pub permitted: u64,
+    pub inheritable: u64,
+    /// 执行时是否将 permitted 置入有效集合（即 +e）
+    pub effective: bool,
+    /// 版本 3 中，所属 user namespace 的 root uid
+    pub rootid: Option<u32>,
+}
+
+impl FileCaps {
+    /// 从 security.capability 的原始值解码
+    /// 长度与版本不符，或版本未知时返回 InvalidData 错误，而不会 panic
+    ///
+    /// ```
+    /// # use path_permission::FileCaps;
+    /// // cap_net_raw+ep，版本 2
+    /// let raw = [1, 0, 0, 2, 0, 0x20, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0];
+    /// let caps = FileCaps::from_xattr(&raw).unwrap();
+    /// assert_eq!(caps.version, 2);
+    /// assert_eq!(caps.permitted, 1 << 13);
+    /// assert!(caps.effective);
+    /// assert!(caps.has_permitted(13));
+    /// assert!(! caps.has_permitted(12));
+    /// assert!(! caps.has_permitted(64));
+    ///
+    /// assert!(FileCaps::from_xattr(&raw[..7]).is_err());
+    /// ```
+    pub fn from_xattr(raw: &[u8]) -> io::Result<FileCaps> {
+        let magic = read_u32(raw, 0)?;
+        let (version, expected) = match magic & VFS_CAP_REVISION_MASK {
+            VFS_CAP_REVISION_1 => (1, 12),
+            VFS_CAP_REVISION_2 => (2, 20),
+            VFS_CAP_REVISION_3 => (3, 24),
+            revision => {
+                return Err(invalid(format!("unknown revision {:#010x}", revision)))
+            }
+        };
+        if raw.len() != expected {
+            return Err(invalid(format!("expected {} bytes for revision {}, got {}",
+                                       expected, version, raw.len())))
+        }
+
+        let mut permitted = read_u32(raw, 4)? as u64;
+        let mut inheritable = read_u32(raw, 8)? as u64;
+        if version > 1 {
+            permitted |= (read_u32(raw, 12)? as u64) << 32;
+            inheritable |= (read_u32(raw, 16)? as u64) << 32;
+        }
+        let rootid = if version == 3 {
+            Some(read_u32(raw, 20)?)
+        } else {
+            None
+        };
+
+        Ok(FileCaps {
+            version,
+            permitted,
+            inheritable,
+            effective: magic & VFS_CAP_FLAGS_EFFECTIVE != 0,
+            rootid,
+        })
+    }
+
+    /// permitted 集合中是否包含 cap，cap 的取值见 Capabilities 中的常量
+    /// cap 超出范围（64 及以上）时返回 false
+    pub fn has_permitted(&self, cap: u32) -> bool {
+        1u64.checked_shl(cap).is_some_and(|bit| self.permitted & bit != 0)
+    }
+}
+
+pub(crate) fn file_capabilities(path: &Path) -> io::Result<Option<FileCaps>> {
+    match xattr::get(path, XATTR_NAME)? {
+        None => Ok(None),
+        Some(raw) => FileCaps::from_xattr(&raw).map(Some),
+    }
+}
+
+// 以小端序读取 u32
+fn read_u32(raw: &[u8], offset: usize) -> io::Result<u32> {
+    raw.get(offset..offset + 4)
+        .and_then(|bytes| bytes.try_into().ok())
+        .map(u32::from_le_bytes)
+        .ok_or_else(|| invalid(format!("truncated at byte {}", offset)))
+}
+
+fn invalid(msg: String) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, format!("{}: {}", XATTR_NAME, msg))
+}
@@ -0,0 +1,50 @@
+//! 临时变更权限，离开作用域时还原
+use std::{
+    io,
+    path::{Path, PathBuf},
+};
+
+use crate::Mode;
+
+use super::{PathPermission, PermSnapshot};
+
+/// 由 with_mode 返回，drop 时将路径的权限还原为变更前的值
+/// drop 时无法报告还原失败，需要得知时请调用 close。
+#[derive(Debug)]
+pub struct PermGuard {
+    path: PathBuf,
+    snapshot: PermSnapshot,
+    restored: bool,
+}
+
+impl PermGuard {
+    pub(crate) fn new(path: &Path, mode: u16) -> io::Result<PermGuard> {
+        let snapshot = path.snapshot()?;
+        path.set_mode(Mode::new(mode))?;
+        Ok(PermGuard {
+            path: path.to_path_buf(),
+            snapshot,
+            restored: false,
+        })
+    }
+
+    /// 变更前的权限及所有者
+    pub fn original(&self) -> &PermSnapshot {
+        &self.snapshot
+    }
+
+    /// 立即还原，并返回还原的结果
+    pub fn close(mut self) -> io::Result<()> {
+        self.restored = true;
+        self.path.restore(&self.snapshot)
+    }
+}
+
+impl Drop for PermGuard {
+    fn drop(&mut self) {
+        if ! self.restored {
+            // drop 中不能 panic，忽略还原的错误
+            let _ = self.path.restore(&self.snapshot);
+        }
+    }
+}
@@ -0,0 +1,168 @@
+//! 缓存 stat 的结果，以一次 stat 回答多个权限问题
+use std::{
+    cell::OnceCell,
+    io,
+    path::{Path, PathBuf},
+};
+
+use crate::{modes, Mode};
+
+use super::{
+    compute::{self, AccessMode, Privilege},
+    parent_dir,
+    stat::{self, Stat},
+};
+
+// 当前进程的有效 uid、gid 及附加组，以及特权
+#[derive(Clone, Debug)]
+pub(crate) struct Credentials {
+    pub uid: u32,
+    pub gids: Vec<u32>,
+    pub privilege: Privilege,
+}
+
+impl Credentials {
+    pub fn current() -> io::Result<Credentials> {
+        Ok(Credentials {
+            uid: unsafe { libc::geteuid() },
+            gids: compute::current_gids()?,
+            privilege: Privilege::current(),
+        })
+    }
+}
+
+/// 路径在某一时刻的权限信息，由 PathPermission::inspect 创建
+///
+/// 创建时获取一次路径的 stat（跟随符号链接）及当前进程的身份，父级目录的 stat 在首次
+/// 需要时获取一次，之后的查询不再访问文件系统。这是一个时间点上的快照：路径之后的
+/// 变化不会反映出来。权限均按权限位计算（同 access_for），未考虑 ACL 等。
+///
+/// 遍历目录树的接口中，只有需要按当前进程的身份判断权限的 tree_removal_blocker（即
+/// is_tree_removable 等）基于 PermissionInspector。audit_tree、find_matching、
+/// chmod_recursive、normalize_tree、sanitize_tree、snapshot_tree、diff_tree 及
+/// export_manifest 等只使用权限位、所有者及文件类型，与当前进程的身份无关；遍历时已在
+/// 读取目录时相对于其文件描述符为每个路径获取一次 stat，这些接口直接使用该 stat，
+/// 同样不会重复获取，经由 PermissionInspector 只会多出获取身份及复制路径的开销。
+///
+/// ```
+/// # use std::{fs, os::unix::fs::MetadataExt};
+/// # use path_permission::{Mode, PathPermission};
+/// # let path = std::env::temp_dir().join(format!("pp-inspect-{}", std::process::id()));
+/// fs::write(&path, b"").unwrap();
+/// path.set_mode(Mode::new(0o640)).unwrap();
+///
+/// let inspector = path.inspect().unwrap();
+/// assert_eq!(inspector.mode(), Mode::new(0o640));
+/// assert_eq!(inspector.owner(), path.metadata().unwrap().uid());
+/// assert!(inspector.is_readable_by_mode());
+/// assert!(! inspector.is_executable_by_mode());
+/// assert!(! inspector.is_world_writable());
+/// assert!(inspector.is_removable().unwrap());
+///
+/// // 快照不会反映之后的变化
+/// path.set_mode(Mode::new(0o666)).unwrap();
+/// assert_eq!(inspector.mode(), Mode::new(0o640));
+/// assert!(path.inspect().unwrap().is_world_writable());
+/// # fs::remove_file(&path).unwrap();
+/// ```
+#[derive(Debug)]
+pub struct PermissionInspector {
+    path: PathBuf,
+    stat: Stat,
+    // 外层为是否已获取，内层 None 表示没有父级目录（根目录）
+    parent: OnceCell<Option<Stat>>,
+    credentials: Credentials,
+}
+
+impl PermissionInspector {
+    pub(crate) fn new(path: &Path) -> io::Result<PermissionInspector> {
+        Ok(PermissionInspector {
+            path: path.to_path_buf(),
+            stat: stat::stat(path, true)?,
+            parent: OnceCell::new(),
+            credentials: Credentials::current()?,
+        })
+    }
+
+    // 由已获取的 stat 及父级目录的 stat 创建，供遍历目录树时使用
+    pub(crate) fn from_parts(path: &Path, stat: Stat, parent: Option<Stat>,
+                             credentials: Credentials) -> PermissionInspector {
+        PermissionInspector {
+            path: path.to_path_buf(),
+            stat,
+            parent: OnceCell::from(parent),
+            credentials,
+        }
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// 权限，包括 S_ISUID、S_ISGID、S_ISVTX
+    pub fn mode(&self) -> Mode {
+        Mode::new(self.stat.perm())
+    }
+
+    /// 所有者的 uid
+    pub fn owner(&self) -> u32 {
+        self.stat.uid
+    }
+
+    /// 所属组的 gid
+    pub fn group(&self) -> u32 {
+        self.stat.gid
+    }
+
+    pub fn is_dir(&self) -> bool {
+        self.stat.is_dir()
+    }
+
+    pub fn is_readable_by_mode(&self) -> bool {
+        self.allows(&self.stat, AccessMode::READ)
+    }
+
+    pub fn is_writable_by_mode(&self) -> bool {
+        self.allows(&self.stat, AccessMode::WRITE)
+    }
+
+    pub fn is_executable_by_mode(&self) -> bool {
+        self.allows(&self.stat, AccessMode::EXECUTE)
+    }
+
+    /// 其他用户是否可写
+    pub fn is_world_writable(&self) -> bool {
+        self.stat.perm() & modes::OTHER_W != 0
+    }
+
+    /// 判断路径能否被删除，规则同 is_removable（但按权限位计算）
+    /// 首次调用时获取父级目录的 stat。
+    pub fn is_removable(&self) -> io::Result<bool> {
+        let parent = match self.parent()? {
+            Some(parent) => parent,
+            None => return Ok(false),
+        };
+        if parent.perm() & modes::STICKY != 0
+            && ! compute::sticky_allows(self.credentials.uid, self.stat.uid, parent.uid,
+                                        self.credentials.privilege) {
+            return Ok(false)
+        }
+        Ok(self.allows(&parent, AccessMode::EXECUTE | AccessMode::WRITE))
+    }
+
+    fn parent(&self) -> io::Result<Option<Stat>> {
+        if let Some(parent) = self.parent.get() {
+            return Ok(*parent)
+        }
+        let parent = match parent_dir(&self.path)? {
+            Some(parent) => Some(stat::stat(&parent, true)?),
+            None => None,
+        };
+        Ok(*self.parent.get_or_init(|| parent))
+    }
+
+    fn allows(&self, stat: &Stat, amode: AccessMode) -> bool {
+        compute::stat_allows(stat, self.credentials.uid, &self.credentials.gids, amode,
+                             self.credentials.privilege)
+    }
+}
@@ -0,0 +1,146 @@
+//! 创建硬链接、符号链接的前提条件
+use std::{
+    fs::Metadata,
+    io,
+    path::Path,
+    os::unix::fs::MetadataExt,
+};
+
+use crate::modes;
+
+use super::{effective_access, parent_dir};
+
+#[cfg(target_os = "linux")]
+use std::sync::atomic::{AtomicU8, Ordering};
+
+#[cfg(target_os = "linux")]
+use super::compute::Privilege;
+
+pub(crate) fn is_hardlinkable_to(source: &Path, newpath: &Path) -> io::Result<bool> {
+    // link(2) 不跟随符号链接，链接的是路径本身
+    let source_stat = match source.symlink_metadata() {
+        Ok(metadata) => metadata,
+        Err(ref e) if e.kind() == io::ErrorKind::NotFound => return Ok(false),
+        Err(e) => return Err(e),
+    };
+    if source_stat.is_dir() {
+        return Ok(false)
+    }
+
+    let parent = match link_parent(newpath)? {
+        Some(parent) => parent,
+        None => return Ok(false),
+    };
+    // 硬链接不能跨文件系统（EXDEV）
+    if parent.dev() != source_stat.dev() {
+        return Ok(false)
+    }
+
+    #[cfg(target_os = "linux")]
+    {
+        if protected_hardlinks() && ! safe_hardlink_source(source, &source_stat, Privilege::current())? {
+            return Ok(false)
+        }
+    }
+
+    Ok(true)
+}
+
+pub(crate) fn is_symlinkable_at(newpath: &Path) -> io::Result<bool> {
+    Ok(link_parent(newpath)?.is_some())
+}
+
+// 检查 newpath 尚不存在，且可在其父级目录中创建，返回父级目录的 metadata
+fn link_parent(newpath: &Path) -> io::Result<Option<Metadata>> {
+    if newpath.symlink_metadata().is_ok() {
+        return Ok(None)
+    }
+    let parent = match parent_dir(newpath)? {
+        Some(parent) => parent,
+        None => return Ok(None),
+    };
+    let metadata = match parent.metadata() {
+        Ok(metadata) if metadata.is_dir() => metadata,
+        _ => return Ok(None),
+    };
+    if ! effective_access(&parent, libc::X_OK + libc::W_OK)? {
+        return Ok(None)
+    }
+    Ok(Some(metadata))
+}
+
+// 与内核的 may_linkat 一致：所有者（或拥有 CAP_FOWNER）可以链接任意文件；
+// 否则只能链接可读写、且非 setuid、非可执行的 setgid 的普通文件
+#[cfg(target_os = "linux")]
+fn safe_hardlink_source(source: &Path, metadata: &Metadata, privilege: Privilege) -> io::Result<bool> {
+    if super::compute::is_current_user(metadata.uid()) || privilege.fowner {
+        return Ok(true)
+    }
+
+    let mode = metadata.mode();
+    if ! metadata.is_file() || mode & u32::from(modes::SETUID) != 0
+        || mode & u32::from(modes::SETGID | modes::GROUP_X) == u32::from(modes::SETGID | modes::GROUP_X) {
+        return Ok(false)
+    }
+    effective_access(source, libc::R_OK + libc::W_OK)
+}
+
+// 与内核的 may_follow_link 一致：开启 protected_symlinks 时，位于所有人可写且
+// 设置了 S_ISVTX 的目录中的符号链接，只有其所有者，或目录所有者即链接所有者时，
+// 才能被跟随
+pub(crate) fn symlink_would_be_followable(link: &Path, by_uid: u32) -> io::Result<bool> {
+    let metadata = link.symlink_metadata()?;
+    if ! metadata.file_type().is_symlink() {
+        return Ok(true)
+    }
+
+    #[cfg(target_os = "linux")]
+    {
+        if ! protected_symlinks() || metadata.uid() == by_uid {
+            return Ok(true)
+        }
+        let parent = match parent_dir(link)? {
+            Some(parent) => parent.metadata()?,
+            None => return Ok(true),
+        };
+        if parent.mode() & u32::from(modes::STICKY | modes::OTHER_W) != u32::from(modes::STICKY | modes::OTHER_W) {
+            return Ok(true)
+        }
+        Ok(parent.uid() == metadata.uid())
+    }
+
+    #[cfg(not(target_os = "linux"))]
+    {
+        let _ = by_uid;
+        Ok(true)
+    }
+}
+
+#[cfg(target_os = "linux")]
+fn protected_hardlinks() -> bool {
+    static PROTECTED: AtomicU8 = AtomicU8::new(0);
+    protected_sysctl("/proc/sys/fs/protected_hardlinks", &PROTECTED)
+}
+
+#[cfg(target_os = "linux")]
+fn protected_symlinks() -> bool {
+    static PROTECTED: AtomicU8 = AtomicU8::new(0);
+    protected_sysctl("/proc/sys/fs/protected_symlinks", &PROTECTED)
+}
+
+// 读取 fs.protected_* 的值，结果在进程内缓存于 cache
+// 0：未读取，1：未开启，2：已开启；无法读取时视为未开启
+#[cfg(target_os = "linux")]
+fn protected_sysctl(path: &str, cache: &AtomicU8) -> bool {
+    match cache.load(Ordering::Relaxed) {
+        1 => return false,
+        2 => return true,
+        _ => {}
+    }
+
+    let protected = std::fs::read_to_string(path)
+        .map(|value| value.trim() == "1")
+        .unwrap_or(false);
+    cache.store(if protected { 2 } else { 1 }, Ordering::Relaxed);
+    protected
+}
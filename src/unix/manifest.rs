@@ -0,0 +1,481 @@
+//! 以稳定的 JSON 格式导出目录树的权限清单，并据此检查变化
+//!
+//! 每个路径为一个 JSON 对象，字段依次为：
+//!   * `path`：相对于导出时的起始路径的路径，起始路径本身为 `""`（同 PermissionSnapshot）；
+//!     不是有效的 UTF-8 时为其原始字节的 base64（标准字母表，带填充）
+//!   * `path_encoding`：`"utf8"` 或 `"base64"`
+//!   * `type`：`"file"`、`"dir"`、`"symlink"`、`"char"`、`"block"`、`"fifo"`、`"socket"`
+//!     或 `"unknown"`
+//!   * `mode`：四位八进制的权限字符串，如 `"0644"`，包括 S_ISUID、S_ISGID、S_ISVTX
+//!   * `uid`、`gid`：数字
+//!   * `flags`：字符串数组，为 `"setuid"`、`"setgid"`、`"sticky"`、`"world_writable"` 中
+//!     适用的项，按此顺序排列
+//!
+//! 之后的版本只会增加字段，不会改变已有字段的含义；读取时会忽略未知的字段。
+//!
+//! verify_manifest 将 `path` 拼接于给定的起始路径之后再检查，因此目录树被移动或复制至
+//! 别处、或在其它工作目录下，仍可检查。清单只有固定的结构（对象的数组，对象中只有
+//! `flags` 一个数组），读取时拒绝嵌套更深的输入，而不会因递归过深而栈溢出。
+use std::{
+    ffi::OsStr,
+    fmt::Write as _,
+    io::{self, Read, Write},
+    ops::ControlFlow,
+    path::{Path, PathBuf},
+    os::unix::ffi::OsStrExt,
+};
+
+use crate::modes;
+
+use super::{
+    diff::AccessDiff,
+    stat::{self, Stat},
+    walk::try_walk,
+    PermSnapshot,
+};
+
+/// export_manifest 的输出格式
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ManifestFormat {
+    /// JSON Lines：每行一个对象
+    JsonLines,
+    /// 单个 JSON 数组，每行一个元素
+    JsonArray,
+}
+
+// 先序遍历 root，不跟随符号链接，逐个写入 writer，返回写入的路径数量
+// 遇到无法获取 stat 或读取目录的路径时，已写入的内容保持不变，停止遍历并返回该错误
+pub(crate) fn export_manifest(root: &Path, writer: &mut dyn Write, format: ManifestFormat)
+    -> io::Result<u64> {
+    let mut count = 0;
+    let mut first_err = None;
+
+    if format == ManifestFormat::JsonArray {
+        writer.write_all(b"[")?;
+    }
+    try_walk(root, |path, stat, dir_err| {
+        let result = stat.and_then(|stat| {
+            if format == ManifestFormat::JsonArray {
+                writer.write_all(if count == 0 { b"\n" } else { b",\n" })?;
+            }
+            // walk 返回的路径都以 root 开头
+            let relative = path.strip_prefix(root).unwrap_or(path);
+            writer.write_all(entry_json(relative, &stat).as_bytes())?;
+            if format == ManifestFormat::JsonLines {
+                writer.write_all(b"\n")?;
+            }
+            count += 1;
+            Ok(())
+        });
+        match result.err().or(dir_err) {
+            Some(err) => {
+                first_err = Some(err);
+                ControlFlow::Break(())
+            }
+            None => ControlFlow::Continue(()),
+        }
+    });
+    if let Some(err) = first_err {
+        return Err(err)
+    }
+    if format == ManifestFormat::JsonArray {
+        writer.write_all(b"\n]\n")?;
+    }
+    writer.flush()?;
+    Ok(count)
+}
+
+fn entry_json(path: &Path, stat: &Stat) -> String {
+    let bytes = path.as_os_str().as_bytes();
+    let (path, encoding) = match std::str::from_utf8(bytes) {
+        Ok(path) => (path.to_string(), "utf8"),
+        Err(_) => (base64_encode(bytes), "base64"),
+    };
+
+    let mode = stat.perm();
+    let flags: Vec<_> = [(modes::SETUID, "setuid"), (modes::SETGID, "setgid"), (modes::STICKY, "sticky")]
+        .iter()
+        .filter(|&&(bit, _)| mode & bit != 0)
+        .map(|&(_, flag)| flag)
+        // 符号链接的权限没有意义
+        .chain(Some("world_writable").filter(|_| mode & modes::OTHER_W != 0 && ! stat.is_symlink()))
+        .map(|flag| format!("\"{}\"", flag))
+        .collect();
+
+    let kind = stat.kind().map_or("unknown", |kind| kind.name());
+    let mut json = String::from("{\"path\":");
+    push_json_string(&mut json, &path);
+    let _ = write!(json, ",\"path_encoding\":\"{}\",\"type\":\"{}\",\"mode\":\"{:04o}\",\
+                          \"uid\":{},\"gid\":{},\"flags\":[{}]}}",
+                   encoding, kind, mode, stat.uid, stat.gid, flags.join(","));
+    json
+}
+
+fn push_json_string(json: &mut String, s: &str) {
+    json.push('"');
+    for c in s.chars() {
+        match c {
+            '"' => json.push_str("\\\""),
+            '\\' => json.push_str("\\\\"),
+            '\n' => json.push_str("\\n"),
+            '\r' => json.push_str("\\r"),
+            '\t' => json.push_str("\\t"),
+            c if (c as u32) < 0x20 => {
+                let _ = write!(json, "\\u{:04x}", c as u32);
+            }
+            c => json.push(c),
+        }
+    }
+    json.push('"');
+}
+
+/// 读取 export_manifest 导出的清单（两种格式均可），与 root 下的现状比较，不跟随符号链接
+/// 清单中的路径相对于导出时的起始路径，拼接于 root 之后检查，root 无需与导出时相同。
+/// 返回权限、所有者或所属组发生了变化的路径（拼接于 root 之后）及其差异：
+/// AccessDiff::first 为清单中记录的值，second 为现状，路径已不存在时为 None。
+/// 清单之外新增的路径不会被列出。清单格式不符（包括嵌套过深）时返回 InvalidData 错误。
+///
+/// ```
+/// # use std::fs;
+/// # use path_permission::{verify_manifest, ManifestFormat, Mode, PathPermission};
+/// # let dir = std::env::temp_dir().join(format!("pp-manifest-{}", std::process::id()));
+/// # fs::create_dir_all(&dir).unwrap();
+/// fs::write(dir.join("config"), b"").unwrap();
+/// fs::write(dir.join("secret"), b"").unwrap();
+/// dir.join("config").set_mode(Mode::new(0o644)).unwrap();
+///
+/// for &format in &[ManifestFormat::JsonLines, ManifestFormat::JsonArray] {
+///     let mut manifest = Vec::new();
+///     assert_eq!(dir.export_manifest(&mut manifest, format).unwrap(), 3);
+///     assert!(verify_manifest(&dir, &manifest[..]).unwrap().is_empty());
+/// }
+///
+/// let mut manifest = Vec::new();
+/// dir.export_manifest(&mut manifest, ManifestFormat::JsonLines).unwrap();
+/// let text = String::from_utf8(manifest.clone()).unwrap();
+/// assert!(text.lines().next().unwrap().starts_with(r#"{"path":"","#));
+/// assert!(text.lines().nth(1).unwrap()
+///     .starts_with(r#"{"path":"config","path_encoding":"utf8","type":"file","mode":"0644""#));
+///
+/// // 路径相对于起始路径，移动之后仍可检查
+/// let moved = dir.with_extension("moved");
+/// fs::rename(&dir, &moved).unwrap();
+/// assert!(verify_manifest(&moved, &manifest[..]).unwrap().is_empty());
+/// fs::rename(&moved, &dir).unwrap();
+///
+/// // 嵌套过深的输入返回错误，而不会栈溢出
+/// let nested = "[".repeat(1 << 20);
+/// let err = verify_manifest(&dir, nested.as_bytes()).unwrap_err();
+/// assert_eq!(err.kind(), std::io::ErrorKind::InvalidData);
+///
+/// dir.join("config").set_mode(Mode::new(0o666)).unwrap();
+/// fs::remove_file(dir.join("secret")).unwrap();
+/// let drift = verify_manifest(&dir, &manifest[..]).unwrap();
+/// assert_eq!(drift.len(), 2);
+/// assert_eq!(drift[0].0, dir.join("config"));
+/// assert_eq!(drift[0].1.to_string(), "mode 0644 -> 0666");
+/// assert_eq!(drift[1].0, dir.join("secret"));
+/// assert_eq!(drift[1].1.second, None);
+/// # fs::remove_dir_all(&dir).unwrap();
+/// ```
+pub fn verify_manifest<P, R>(root: P, mut reader: R) -> io::Result<Vec<(PathBuf, AccessDiff)>>
+where
+    P: AsRef<Path>,
+    R: Read,
+{
+    let root = root.as_ref();
+    let mut input = String::new();
+    reader.read_to_string(&mut input)?;
+
+    let mut drift = Vec::new();
+    for (relative, recorded) in parse_entries(&input)? {
+        // 起始路径本身为 ""，避免拼接出以 "/" 结尾的路径
+        let path = if relative.as_os_str().is_empty() {
+            root.to_path_buf()
+        } else {
+            root.join(relative)
+        };
+        let current = match stat::stat(&path, false) {
+            Ok(stat) => Some(PermSnapshot::from_stat(&stat)),
+            Err(ref e) if super::is_missing(e) => None,
+            Err(e) => return Err(e),
+        };
+        if current.as_ref() != Some(&recorded) {
+            drift.push((path, AccessDiff { first: Some(recorded), second: current }));
+        }
+    }
+    Ok(drift)
+}
+
+// 清单为一个对象数组，或以空白分隔的多个对象（JSON Lines）
+fn parse_entries(input: &str) -> io::Result<Vec<(PathBuf, PermSnapshot)>> {
+    let mut parser = Parser { input: input.as_bytes(), pos: 0, depth: 0 };
+    let mut values = Vec::new();
+    parser.skip_whitespace();
+    if parser.peek() == Some(b'[') {
+        match parser.value()? {
+            Json::Array(items) => values = items,
+            _ => unreachable!(),
+        }
+        parser.skip_whitespace();
+        if parser.peek().is_some() {
+            return Err(parser.error("unexpected data after array"))
+        }
+    } else {
+        while parser.peek().is_some() {
+            values.push(parser.value()?);
+            parser.skip_whitespace();
+        }
+    }
+    values.into_iter().map(entry_from_json).collect()
+}
+
+fn entry_from_json(value: Json) -> io::Result<(PathBuf, PermSnapshot)> {
+    let invalid = |reason: &str| io::Error::new(io::ErrorKind::InvalidData, reason.to_string());
+    let fields = match value {
+        Json::Object(fields) => fields,
+        _ => return Err(invalid("manifest entry is not an object")),
+    };
+    let field = |name: &str| fields.iter().find(|(key, _)| key == name).map(|(_, value)| value);
+    let string = |name: &str| match field(name) {
+        Some(Json::String(s)) => Ok(s.as_str()),
+        _ => Err(invalid(&format!("missing or invalid field {:?}", name))),
+    };
+    let number = |name: &str| match field(name) {
+        Some(Json::Number(n)) if *n <= u64::from(u32::MAX) => Ok(*n as u32),
+        _ => Err(invalid(&format!("missing or invalid field {:?}", name))),
+    };
+
+    let path = match field("path_encoding") {
+        None => PathBuf::from(string("path")?),
+        Some(Json::String(e)) if e == "utf8" => PathBuf::from(string("path")?),
+        Some(Json::String(e)) if e == "base64" => {
+            let bytes = base64_decode(string("path")?).ok_or_else(|| invalid("invalid base64 path"))?;
+            PathBuf::from(OsStr::from_bytes(&bytes))
+        }
+        _ => return Err(invalid("unknown path_encoding")),
+    };
+    let mode = u16::from_str_radix(string("mode")?, 8)
+        .ok()
+        .filter(|&mode| mode <= modes::ALL_BITS)
+        .ok_or_else(|| invalid("invalid mode"))?;
+    Ok((path, PermSnapshot { mode, uid: number("uid")?, gid: number("gid")? }))
+}
+
+// 只需支持清单中出现的值
+enum Json {
+    Null,
+    Bool,
+    Number(u64),
+    String(String),
+    Array(Vec<Json>),
+    Object(Vec<(String, Json)>),
+}
+
+// 清单最深为：数组、其中的对象、对象中的 flags 数组
+const MAX_DEPTH: usize = 3;
+
+struct Parser<'a> {
+    input: &'a [u8],
+    pos: usize,
+    // 当前所在的数组及对象的层数
+    depth: usize,
+}
+
+impl Parser<'_> {
+    fn peek(&self) -> Option<u8> {
+        self.input.get(self.pos).copied()
+    }
+
+    fn error(&self, reason: &str) -> io::Error {
+        io::Error::new(io::ErrorKind::InvalidData, format!("{} at byte {}", reason, self.pos))
+    }
+
+    fn skip_whitespace(&mut self) {
+        while matches!(self.peek(), Some(b' ') | Some(b'\t') | Some(b'\n') | Some(b'\r')) {
+            self.pos += 1;
+        }
+    }
+
+    fn expect(&mut self, c: u8) -> io::Result<()> {
+        self.skip_whitespace();
+        if self.peek() != Some(c) {
+            return Err(self.error(&format!("expected {:?}", c as char)))
+        }
+        self.pos += 1;
+        Ok(())
+    }
+
+    fn literal(&mut self, word: &str, value: Json) -> io::Result<Json> {
+        if ! self.input[self.pos..].starts_with(word.as_bytes()) {
+            return Err(self.error("unexpected character"))
+        }
+        self.pos += word.len();
+        Ok(value)
+    }
+
+    fn value(&mut self) -> io::Result<Json> {
+        self.skip_whitespace();
+        match self.peek() {
+            Some(b'{') | Some(b'[') if self.depth == MAX_DEPTH => Err(self.error("nested too deeply")),
+            Some(b'{') => self.nested(Self::object),
+            Some(b'[') => self.nested(Self::array),
+            Some(b'"') => Ok(Json::String(self.string()?)),
+            Some(b'0'..=b'9') => self.number(),
+            Some(b't') => self.literal("true", Json::Bool),
+            Some(b'f') => self.literal("false", Json::Bool),
+            Some(b'n') => self.literal("null", Json::Null),
+            _ => Err(self.error("unexpected character")),
+        }
+    }
+
+    fn nested(&mut self, parse: fn(&mut Self) -> io::Result<Json>) -> io::Result<Json> {
+        self.depth += 1;
+        let value = parse(self);
+        self.depth -= 1;
+        value
+    }
+
+    fn object(&mut self) -> io::Result<Json> {
+        self.expect(b'{')?;
+        let mut fields = Vec::new();
+        self.skip_whitespace();
+        if self.peek() == Some(b'}') {
+            self.pos += 1;
+            return Ok(Json::Object(fields))
+        }
+        loop {
+            self.skip_whitespace();
+            let key = self.string()?;
+            self.expect(b':')?;
+            fields.push((key, self.value()?));
+            self.skip_whitespace();
+            match self.peek() {
+                Some(b',') => self.pos += 1,
+                Some(b'}') => {
+                    self.pos += 1;
+                    return Ok(Json::Object(fields))
+                }
+                _ => return Err(self.error("expected ',' or '}'")),
+            }
+        }
+    }
+
+    fn array(&mut self) -> io::Result<Json> {
+        self.expect(b'[')?;
+        let mut items = Vec::new();
+        self.skip_whitespace();
+        if self.peek() == Some(b']') {
+            self.pos += 1;
+            return Ok(Json::Array(items))
+        }
+        loop {
+            items.push(self.value()?);
+            self.skip_whitespace();
+            match self.peek() {
+                Some(b',') => self.pos += 1,
+                Some(b']') => {
+                    self.pos += 1;
+                    return Ok(Json::Array(items))
+                }
+                _ => return Err(self.error("expected ',' or ']'")),
+            }
+        }
+    }
+
+    // 只支持非负整数
+    fn number(&mut self) -> io::Result<Json> {
+        let start = self.pos;
+        while matches!(self.peek(), Some(b'0'..=b'9')) {
+            self.pos += 1;
+        }
+        std::str::from_utf8(&self.input[start..self.pos]).ok()
+            .and_then(|digits| digits.parse().ok())
+            .map(Json::Number)
+            .ok_or_else(|| self.error("invalid number"))
+    }
+
+    fn string(&mut self) -> io::Result<String> {
+        if self.peek() != Some(b'"') {
+            return Err(self.error("expected string"))
+        }
+        self.pos += 1;
+        let mut bytes = Vec::new();
+        loop {
+            let c = self.peek().ok_or_else(|| self.error("unterminated string"))?;
+            self.pos += 1;
+            match c {
+                b'"' => break,
+                b'\\' => {
+                    let escaped = self.peek().ok_or_else(|| self.error("unterminated string"))?;
+                    self.pos += 1;
+                    let c = match escaped {
+                        b'"' => '"',
+                        b'\\' => '\\',
+                        b'/' => '/',
+                        b'n' => '\n',
+                        b'r' => '\r',
+                        b't' => '\t',
+                        b'b' => '\u{8}',
+                        b'f' => '\u{c}',
+                        b'u' => self.unicode_escape()?,
+                        _ => return Err(self.error("invalid escape")),
+                    };
+                    let mut buf = [0; 4];
+                    bytes.extend_from_slice(c.encode_utf8(&mut buf).as_bytes());
+                }
+                c => bytes.push(c),
+            }
+        }
+        String::from_utf8(bytes).map_err(|_| self.error("invalid UTF-8 in string"))
+    }
+
+    // 不支持代理对，清单中只会出现控制字符的转义
+    fn unicode_escape(&mut self) -> io::Result<char> {
+        let hex = self.input.get(self.pos..self.pos + 4)
+            .and_then(|hex| std::str::from_utf8(hex).ok())
+            .and_then(|hex| u32::from_str_radix(hex, 16).ok())
+            .and_then(char::from_u32)
+            .ok_or_else(|| self.error("invalid \\u escape"))?;
+        self.pos += 4;
+        Ok(hex)
+    }
+}
+
+const BASE64: &[u8; 64] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+
+fn base64_encode(bytes: &[u8]) -> String {
+    let mut s = String::with_capacity((bytes.len() + 2) / 3 * 4);
+    for chunk in bytes.chunks(3) {
+        let n = chunk.iter().enumerate().fold(0u32, |n, (i, &b)| n | u32::from(b) << (16 - 8 * i));
+        for i in 0..4 {
+            if i <= chunk.len() {
+                s.push(BASE64[(n >> (18 - 6 * i) & 0o77) as usize] as char);
+            } else {
+                s.push('=');
+            }
+        }
+    }
+    s
+}
+
+fn base64_decode(s: &str) -> Option<Vec<u8>> {
+    let s = s.as_bytes();
+    if s.len() % 4 != 0 {
+        return None
+    }
+    let mut bytes = Vec::with_capacity(s.len() / 4 * 3);
+    for chunk in s.chunks(4) {
+        let padding = chunk.iter().rev().take_while(|&&c| c == b'=').count();
+        if padding > 2 {
+            return None
+        }
+        let mut n = 0u32;
+        for (i, &c) in chunk[..4 - padding].iter().enumerate() {
+            let digit = BASE64.iter().position(|&d| d == c)? as u32;
+            n |= digit << (18 - 6 * i);
+        }
+        bytes.extend_from_slice(&n.to_be_bytes()[1..4 - padding]);
+    }
+    Some(bytes)
+}
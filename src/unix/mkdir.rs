@@ -0,0 +1,76 @@
+//! 以确切的权限创建目录
+use std::{
+    fs::{self, DirBuilder},
+    io,
+    path::{Path, PathBuf},
+    os::unix::fs::DirBuilderExt,
+};
+
+use crate::modes;
+
+pub(crate) fn create_dir_with_mode(path: &Path, mode: u16) -> io::Result<()> {
+    DirBuilder::new().mode(u32::from(mode & modes::ALL_BITS)).create(path)?;
+    // mkdir 的 mode 会被 umask 去除部分位，且不保留 S_ISGID 等，需再次设置
+    if let Err(err) = chmod_exact(path, mode) {
+        let _ = fs::remove_dir(path);
+        return Err(err)
+    }
+    Ok(())
+}
+
+pub(crate) fn create_dir_all_with_mode(path: &Path, mode: u16, intermediate_mode: Option<u16>)
+    -> io::Result<Vec<PathBuf>> {
+    // 由近至远收集不存在的路径
+    let mut missing = Vec::new();
+    let mut current = path;
+    while current.symlink_metadata().is_err() {
+        missing.push(current.to_path_buf());
+        current = match current.parent() {
+            Some(parent) if ! parent.as_os_str().is_empty() => parent,
+            _ => break,
+        };
+    }
+    if missing.is_empty() && ! path.is_dir() {
+        return Err(io::Error::from(io::ErrorKind::AlreadyExists))
+    }
+
+    let mut created = Vec::new();
+    if let Err(err) = create_missing(path, &missing, mode, intermediate_mode, &mut created) {
+        // 与 create_dir_with_mode 一致，失败时删除已创建的目录，由深至浅
+        for dir in created.iter().rev() {
+            let _ = fs::remove_dir(dir);
+        }
+        return Err(err)
+    }
+    Ok(created)
+}
+
+// 由浅至深创建 missing 中的目录，实际创建的目录依次加入 created
+fn create_missing(path: &Path, missing: &[PathBuf], mode: u16, intermediate_mode: Option<u16>,
+                  created: &mut Vec<PathBuf>) -> io::Result<()> {
+    for dir in missing.iter().rev() {
+        let (dir_mode, exact) = if dir == path {
+            (mode, true)
+        } else {
+            match intermediate_mode {
+                Some(intermediate) => (intermediate, true),
+                None => (0o755, false),
+            }
+        };
+
+        match DirBuilder::new().mode(u32::from(dir_mode & modes::ALL_BITS)).create(dir) {
+            Ok(()) => created.push(dir.clone()),
+            // 与 create_dir_all 一致，容许其它进程同时创建
+            Err(ref e) if e.kind() == io::ErrorKind::AlreadyExists && dir.is_dir() => continue,
+            Err(e) => return Err(e),
+        }
+        if exact {
+            chmod_exact(dir, dir_mode)?;
+        }
+    }
+    Ok(())
+}
+
+fn chmod_exact(path: &Path, mode: u16) -> io::Result<()> {
+    super::chmod(path, libc::mode_t::from(mode))
+}
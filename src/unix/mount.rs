@@ -0,0 +1,82 @@
+//! 路径所在文件系统的挂载选项
+use std::{io, path::Path};
+
+// 路径所在的文件系统是否以 noexec 挂载
+#[cfg(target_os = "linux")]
+pub(crate) fn is_noexec(path: &Path) -> io::Result<bool> {
+    let buf = super::c_path(path)?;
+    let buf_ptr = buf.as_ptr() as *const libc::c_char;
+    let mut st = std::mem::MaybeUninit::<libc::statvfs>::zeroed();
+
+    let result = unsafe {
+        libc::statvfs(buf_ptr, st.as_mut_ptr())
+    };
+    if result != 0 {
+        return Err(io::Error::last_os_error())
+    }
+
+    let st = unsafe { st.assume_init() };
+    Ok(noexec_from_flags(st.f_flag))
+}
+
+// statvfs 的 f_flag 中是否有 ST_NOEXEC
+#[cfg(target_os = "linux")]
+fn noexec_from_flags(f_flag: libc::c_ulong) -> bool {
+    f_flag & libc::ST_NOEXEC != 0
+}
+
+// macOS 的 statvfs 不返回 ST_NOEXEC，需使用 statfs 的 MNT_NOEXEC
+#[cfg(target_os = "macos")]
+pub(crate) fn is_noexec(path: &Path) -> io::Result<bool> {
+    let buf = super::c_path(path)?;
+    let buf_ptr = buf.as_ptr() as *const libc::c_char;
+    let mut st = std::mem::MaybeUninit::<libc::statfs>::zeroed();
+
+    let result = unsafe {
+        libc::statfs(buf_ptr, st.as_mut_ptr())
+    };
+    if result != 0 {
+        return Err(io::Error::last_os_error())
+    }
+
+    let st = unsafe { st.assume_init() };
+    Ok(noexec_from_flags(st.f_flags))
+}
+
+// statfs 的 f_flags 中是否有 MNT_NOEXEC
+#[cfg(target_os = "macos")]
+fn noexec_from_flags(f_flags: u32) -> bool {
+    f_flags & libc::MNT_NOEXEC as u32 != 0
+}
+
+// 其它系统上无法获取，视为未以 noexec 挂载
+#[cfg(not(any(target_os = "linux", target_os = "macos")))]
+pub(crate) fn is_noexec(path: &Path) -> io::Result<bool> {
+    path.metadata()?;
+    Ok(false)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[cfg(target_os = "linux")]
+    #[test]
+    fn noexec_flag_decoding() {
+        // ST_NOEXEC 在 Linux 上为 8
+        assert!(noexec_from_flags(8));
+        assert!(noexec_from_flags(libc::ST_NOEXEC));
+        assert!(noexec_from_flags(libc::ST_NOEXEC | libc::ST_RDONLY | libc::ST_NOSUID));
+        assert!(! noexec_from_flags(0));
+        assert!(! noexec_from_flags(libc::ST_RDONLY | libc::ST_NOSUID | libc::ST_NODEV));
+    }
+
+    #[cfg(target_os = "macos")]
+    #[test]
+    fn noexec_flag_decoding() {
+        assert!(noexec_from_flags(libc::MNT_NOEXEC as u32));
+        assert!(noexec_from_flags((libc::MNT_NOEXEC | libc::MNT_RDONLY | libc::MNT_NOSUID) as u32));
+        assert!(! noexec_from_flags(0));
+        assert!(! noexec_from_flags((libc::MNT_RDONLY | libc::MNT_NOSUID) as u32));
+    }
+}
@@ -0,0 +1,84 @@
+//! 以检查权限的方式打开、创建文件，避免先检查、后打开的竞争（TOCTOU）
+use std::{
+    fs::{self, File, OpenOptions, Permissions},
+    io,
+    path::Path,
+    os::unix::{
+        fs::{OpenOptionsExt, PermissionsExt},
+        io::AsRawFd,
+    },
+};
+
+use crate::modes;
+
+pub(crate) fn open_if_readable(path: &Path, nofollow: bool) -> io::Result<Option<File>> {
+    let mut opts = OpenOptions::new();
+    opts.read(true);
+    open_if_permitted(path, &opts, nofollow)
+}
+
+pub(crate) fn open_if_permitted(path: &Path, opts: &OpenOptions, nofollow: bool)
+    -> io::Result<Option<File>> {
+    let mut opts = opts.clone();
+    // 以 O_NONBLOCK 打开，避免在没有写入（读取）端的 FIFO 上阻塞，打开后再还原
+    let mut flags = libc::O_NONBLOCK;
+    if nofollow {
+        flags |= libc::O_NOFOLLOW;
+    }
+    opts.custom_flags(flags);
+
+    let file = match opts.open(path) {
+        Ok(file) => file,
+        Err(ref e) if is_denied(e) => return Ok(None),
+        Err(e) => return Err(e),
+    };
+    clear_nonblock(&file)?;
+    Ok(Some(file))
+}
+
+fn is_denied(err: &io::Error) -> bool {
+    matches!(err.raw_os_error(), Some(libc::EACCES) | Some(libc::EPERM))
+}
+
+fn clear_nonblock(file: &File) -> io::Result<()> {
+    let fd = file.as_raw_fd();
+    let flags = unsafe { libc::fcntl(fd, libc::F_GETFL) };
+    if flags == -1 {
+        return Err(io::Error::last_os_error())
+    }
+    if unsafe { libc::fcntl(fd, libc::F_SETFL, flags & ! libc::O_NONBLOCK) } == -1 {
+        return Err(io::Error::last_os_error())
+    }
+    Ok(())
+}
+
+pub(crate) fn create_with_mode(path: &Path, mode: u16, overwrite: bool) -> io::Result<File> {
+    let mode = u32::from(mode & modes::ALL_BITS);
+    // O_EXCL 不会跟随符号链接，已存在（包括符号链接）时返回 AlreadyExists
+    let created = OpenOptions::new()
+        .write(true)
+        .create_new(true)
+        .mode(mode)
+        .open(path);
+    match created {
+        Ok(file) => {
+            // open 的 mode 会被 umask 去除部分位，需再次设置
+            if let Err(err) = file.set_permissions(Permissions::from_mode(mode)) {
+                let _ = fs::remove_file(path);
+                return Err(err)
+            }
+            Ok(file)
+        }
+        Err(ref e) if overwrite && e.kind() == io::ErrorKind::AlreadyExists => {
+            // 不是由此创建的文件，失败时不删除；先设置权限再截断，失败时内容保持不变
+            let file = OpenOptions::new()
+                .write(true)
+                .custom_flags(libc::O_NOFOLLOW)
+                .open(path)?;
+            file.set_permissions(Permissions::from_mode(mode))?;
+            file.set_len(0)?;
+            Ok(file)
+        }
+        Err(e) => Err(e),
+    }
+}
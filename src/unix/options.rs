@@ -0,0 +1,125 @@
+//! 可配置的权限检查：是否跟随符号链接、以实际还是有效的 uid/gid 检查
+use std::{
+    io,
+    path::Path,
+};
+
+use super::{compute::AccessMode, faccessat};
+
+/// 权限检查的选项，通过 faccessat(2) 检查
+/// 帮助手册[faccessat(2)](https://man7.org/linux/man-pages/man2/faccessat.2.html)
+///
+/// 默认跟随符号链接、以实际的 uid/gid 检查，与 PathPermission::access 等方法一致。
+/// 设置了任一选项时，Linux 上通过 faccessat2(2)（5.8+）由内核检查，可同时正确地处理
+/// 有效 uid/gid 及不跟随符号链接；内核不支持时退回至 faccessat(2)，由 libc 模拟。
+///
+/// ```
+/// # use std::{fs, os::unix::fs::symlink};
+/// # use path_permission::{AccessMode, AccessOptions, Mode, PathPermission};
+/// # let dir = std::env::temp_dir().join(format!("pp-options-{}", std::process::id()));
+/// # fs::create_dir_all(&dir).unwrap();
+/// let file = dir.join("file");
+/// fs::write(&file, b"").unwrap();
+/// file.set_mode(Mode::new(0o644)).unwrap();
+/// let link = dir.join("link");
+/// symlink(&file, &link).unwrap();
+///
+/// for &effective in &[false, true] {
+///     let mut options = AccessOptions::new();
+///     options.effective_ids(effective);
+///     // 跟随符号链接时检查的是 file
+///     assert!(options.check(&link, AccessMode::READ).unwrap());
+///     assert!(! options.check(&link, AccessMode::EXECUTE).unwrap());
+///     // 不跟随时检查的是符号链接本身（其权限总是 0777）
+///     options.follow_symlinks(false);
+///     assert!(options.check(&link, AccessMode::EXECUTE).unwrap());
+///     assert!(! options.check(&file, AccessMode::EXECUTE).unwrap());
+/// }
+///
+/// assert_eq!(AccessOptions::new().check(&link, AccessMode::READ).unwrap(),
+///            link.is_readable().unwrap());
+///
+/// // 实际与有效的 uid/gid 相同时，对普通文件的检查与 access(2) 一致
+/// if unsafe { libc::getuid() == libc::geteuid() && libc::getgid() == libc::getegid() } {
+///     let mut options = AccessOptions::new();
+///     options.effective_ids(true).follow_symlinks(false);
+///     for &mode in &[0o000, 0o400, 0o200, 0o100, 0o755] {
+///         file.set_mode(Mode::new(mode)).unwrap();
+///         for &amode in &[AccessMode::READ, AccessMode::WRITE, AccessMode::EXECUTE] {
+///             assert_eq!(options.check(&file, amode).unwrap(),
+///                        file.access(amode.bits()).unwrap());
+///         }
+///     }
+/// }
+///
+/// // 不可变（chattr +i）的文件：写的检查返回 EPERM，之后的检查仍与 access(2) 一致
+/// # #[cfg(target_os = "linux")]
+/// # {
+/// # use std::os::unix::io::AsRawFd;
+/// let immutable = dir.join("immutable");
+/// fs::write(&immutable, b"").unwrap();
+/// let fd = fs::File::open(&immutable).unwrap();
+/// let set_flags = |flags: libc::c_int| unsafe {
+///     libc::ioctl(fd.as_raw_fd(), libc::FS_IOC_SETFLAGS, &flags)
+/// };
+/// // FS_IMMUTABLE_FL，需要 CAP_LINUX_IMMUTABLE 及文件系统的支持
+/// if set_flags(0x10) == 0 {
+///     let mut options = AccessOptions::new();
+///     options.effective_ids(true);
+///     for _ in 0..2 {
+///         let err = options.check(&immutable, AccessMode::WRITE).unwrap_err();
+///         assert_eq!(err.raw_os_error(), Some(libc::EPERM));
+///         assert_eq!(immutable.access(AccessMode::WRITE.bits()).unwrap_err().raw_os_error(),
+///                    Some(libc::EPERM));
+///         assert_eq!(options.check(&immutable, AccessMode::READ).unwrap(),
+///                    immutable.access(AccessMode::READ.bits()).unwrap());
+///     }
+///     assert_eq!(set_flags(0), 0);
+/// }
+/// # }
+/// # fs::remove_dir_all(&dir).unwrap();
+/// ```
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct AccessOptions {
+    follow_symlinks: bool,
+    effective_ids: bool,
+}
+
+impl Default for AccessOptions {
+    fn default() -> AccessOptions {
+        AccessOptions::new()
+    }
+}
+
+impl AccessOptions {
+    pub fn new() -> AccessOptions {
+        AccessOptions {
+            follow_symlinks: true,
+            effective_ids: false,
+        }
+    }
+
+    /// 为 false 时检查符号链接本身（AT_SYMLINK_NOFOLLOW）
+    pub fn follow_symlinks(&mut self, follow: bool) -> &mut AccessOptions {
+        self.follow_symlinks = follow;
+        self
+    }
+
+    /// 为 true 时以有效的 uid/gid 检查（AT_EACCESS），适用于 setuid 程序
+    pub fn effective_ids(&mut self, effective: bool) -> &mut AccessOptions {
+        self.effective_ids = effective;
+        self
+    }
+
+    /// 检查对路径的 amode 权限，无权限时返回 Ok(false)，路径不存在等返回错误
+    pub fn check<P: AsRef<Path>>(&self, path: P, amode: AccessMode) -> io::Result<bool> {
+        let mut flags = 0;
+        if ! self.follow_symlinks {
+            flags |= libc::AT_SYMLINK_NOFOLLOW;
+        }
+        if self.effective_ids {
+            flags |= libc::AT_EACCESS;
+        }
+        faccessat(path.as_ref(), amode.bits(), flags)
+    }
+}
@@ -0,0 +1,75 @@
+//! 比较两个路径的所有者及所属组
+use std::{
+    io,
+    ops::BitOr,
+    path::Path,
+};
+
+use super::stat::{self, Stat};
+
+/// 比较所有权时所比较的方面，可通过 `|` 组合，如：`OwnershipAspect::OWNER | OwnershipAspect::GROUP`
+///
+/// ```
+/// # use std::{fs, os::unix::fs::symlink};
+/// # use path_permission::{OwnershipAspect, PathPermission};
+/// # let dir = std::env::temp_dir().join(format!("pp-ownership-{}", std::process::id()));
+/// # fs::create_dir_all(&dir).unwrap();
+/// let (a, b) = (dir.join("a"), dir.join("b"));
+/// fs::write(&a, b"").unwrap();
+/// fs::write(&b, b"").unwrap();
+/// assert!(a.same_owner_as(&b).unwrap());
+/// assert!(a.same_group_as(&b).unwrap());
+/// assert!(a.ownership_matches(&b, OwnershipAspect::OWNER | OwnershipAspect::GROUP).unwrap());
+///
+/// // 无法获取 stat 时返回错误，而非 false，错误信息中包含该路径
+/// let missing = dir.join("missing");
+/// let err = a.same_owner_as(&missing).unwrap_err();
+/// assert_eq!(err.kind(), std::io::ErrorKind::NotFound);
+/// assert!(err.to_string().contains(&missing.display().to_string()));
+///
+/// // 不跟随时比较的是符号链接本身
+/// let link = dir.join("link");
+/// symlink("/", &link).unwrap();
+/// assert!(link.same_owner_as_nofollow(&a).unwrap());
+/// assert!(link.same_owner_as(std::path::Path::new("/")).unwrap());
+/// if unsafe { libc::geteuid() } != 0 {
+///     assert!(! link.same_owner_as(&a).unwrap());
+/// }
+/// # fs::remove_dir_all(&dir).unwrap();
+/// ```
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct OwnershipAspect(u8);
+
+impl OwnershipAspect {
+    /// 所有者（st_uid）
+    pub const OWNER: OwnershipAspect = OwnershipAspect(1);
+    /// 所属组（st_gid）
+    pub const GROUP: OwnershipAspect = OwnershipAspect(2);
+
+    /// 是否包含 other 中的全部方面
+    pub fn contains(&self, other: OwnershipAspect) -> bool {
+        self.0 & other.0 == other.0
+    }
+}
+
+impl BitOr for OwnershipAspect {
+    type Output = OwnershipAspect;
+
+    fn bitor(self, rhs: OwnershipAspect) -> OwnershipAspect {
+        OwnershipAspect(self.0 | rhs.0)
+    }
+}
+
+// 各获取一次两个路径的 stat，比较 aspect 中的各方面
+pub(crate) fn ownership_matches(path: &Path, other: &Path, aspect: OwnershipAspect,
+                                follow: bool) -> io::Result<bool> {
+    let (this, other) = (stat_with_path(path, follow)?, stat_with_path(other, follow)?);
+    Ok((! aspect.contains(OwnershipAspect::OWNER) || this.uid == other.uid)
+       && (! aspect.contains(OwnershipAspect::GROUP) || this.gid == other.gid))
+}
+
+// 出错时在错误信息中加入路径，以区分是哪一个路径无法获取，错误的类型不变
+fn stat_with_path(path: &Path, follow: bool) -> io::Result<Stat> {
+    stat::stat(path, follow)
+        .map_err(|err| io::Error::new(err.kind(), format!("{}: {}", path.display(), err)))
+}
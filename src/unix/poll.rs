@@ -0,0 +1,333 @@
+//! 以定时轮询 stat 的方式监视权限及所有者的变化，仅使用 std，适用于所有 Unix 类系统
+//!
+//! inotify 无法报告 NFS 等网络文件系统上由其他主机作出的变化，此时也可使用轮询。
+//! Linux 上启用 watch 特性后，可使用基于 inotify 的 PermissionWatcher。
+use std::{
+    collections::{BTreeMap, VecDeque},
+    io,
+    path::{Path, PathBuf},
+    sync::atomic::{AtomicU64, Ordering},
+    thread,
+    time::{Duration, Instant, SystemTime, UNIX_EPOCH},
+};
+
+use crate::Mode;
+
+use super::{is_missing, stat::{self, Stat}, walk::walk};
+
+/// 路径的权限或所有者发生了变化
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct PermissionEvent {
+    pub path: PathBuf,
+    /// 上一次看到的权限，之前未见过此路径（如新建的路径）时为 None
+    pub old: Option<Mode>,
+    pub new: Mode,
+    /// 变化后的所有者及所属组
+    pub uid: u32,
+    pub gid: u32,
+}
+
+/// PollWatcher 产生的事件
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum PollEvent {
+    /// 同一文件（设备号及 inode 相同）的权限、所有者或所属组发生了变化
+    Changed(PermissionEvent),
+    /// 路径上一次不存在，现在存在，old 为 None
+    Created(PermissionEvent),
+    /// 路径上一次存在，现在不存在
+    Deleted(PathBuf),
+    /// 路径在两次轮询之间被删除并重新创建（设备号或 inode 不同），
+    /// old 为原文件的权限；权限等未变化时同样报告
+    Recreated(PermissionEvent),
+}
+
+#[derive(Debug)]
+struct Watched {
+    // None 表示上一次不存在
+    seen: Option<Stat>,
+    // 由 add 添加；否则为 add_tree 时发现的路径，被删除后不再监视
+    explicit: bool,
+}
+
+/// 定时轮询路径的 stat（不跟随符号链接），报告权限、所有者或所属组的变化，以及删除、
+/// 新建和重建
+///
+/// 作为迭代器时，先报告尚未取出的事件，没有时休眠至下一次轮询。每次的间隔在 interval
+/// 的 ±10% 内随机浮动，首次轮询也随机推迟至多一个 interval，以免大量同时创建的
+/// PollWatcher 同时访问文件系统。没有监视任何路径时迭代结束。
+///
+/// 两次迭代之间可以 add、add_tree 或 remove 路径；check 立即轮询一次，不会休眠。
+/// 两次轮询之间的多次变化只报告最终的结果，如在同一间隔内被删除后又被重新创建的路径
+/// 报告为 Recreated，而分处两个间隔时依次报告为 Deleted 和 Created。
+///
+/// ```
+/// # use std::{fs, time::Duration};
+/// # use path_permission::{Mode, PathPermission, PollEvent, PollWatcher};
+/// # let dir = std::env::temp_dir().join(format!("pp-poll-{}", std::process::id()));
+/// # fs::create_dir_all(&dir).unwrap();
+/// let file = dir.join("file");
+/// fs::write(&file, b"").unwrap();
+/// file.set_mode(Mode::new(0o644)).unwrap();
+///
+/// let mut watcher = PollWatcher::new([&file], Duration::from_millis(10)).unwrap();
+/// file.set_mode(Mode::new(0o600)).unwrap();
+/// match watcher.next().unwrap().unwrap() {
+///     PollEvent::Changed(event) => {
+///         assert_eq!(event.path, file);
+///         assert_eq!(event.old, Some(Mode::new(0o644)));
+///         assert_eq!(event.new, Mode::new(0o600));
+///     }
+///     event => panic!("{:?}", event),
+/// }
+///
+/// // 删除后重新创建
+/// fs::remove_file(&file).unwrap();
+/// let _keep = fs::File::create(dir.join("keep")).unwrap();
+/// fs::write(&file, b"").unwrap();
+/// file.set_mode(Mode::new(0o600)).unwrap();
+/// let events: Vec<_> = watcher.check().into_iter().map(Result::unwrap).collect();
+/// assert!(matches!(&events[..], [PollEvent::Recreated(e)] if e.old == Some(Mode::new(0o600))));
+/// fs::remove_file(&file).unwrap();
+/// assert_eq!(watcher.check().pop().unwrap().unwrap(), PollEvent::Deleted(file.clone()));
+/// fs::write(&file, b"").unwrap();
+/// assert!(matches!(watcher.check().pop().unwrap().unwrap(), PollEvent::Created(_)));
+///
+/// // 监视目录树，报告其中新建的路径
+/// watcher.add_tree(&dir).unwrap();
+/// fs::create_dir(dir.join("sub")).unwrap();
+/// let events = watcher.check();
+/// assert!(matches!(events[..], [Ok(PollEvent::Created(ref e))] if e.path == dir.join("sub")));
+/// dir.join("sub").set_mode(Mode::new(0o700)).unwrap();
+/// let events = watcher.check();
+/// assert!(matches!(events[..], [Ok(PollEvent::Changed(ref e))]
+///     if e.path == dir.join("sub") && e.new == Mode::new(0o700)));
+///
+/// assert!(watcher.remove(&dir));
+/// assert!(watcher.remove(&file));
+/// assert!(watcher.next().is_none());
+/// # fs::remove_dir_all(&dir).unwrap();
+/// ```
+#[derive(Debug)]
+pub struct PollWatcher {
+    watched: BTreeMap<PathBuf, Watched>,
+    // 由 add_tree 添加的目录树
+    trees: Vec<PathBuf>,
+    interval: Duration,
+    next_poll: Instant,
+    rng: u64,
+    pending: VecDeque<io::Result<PollEvent>>,
+}
+
+impl PollWatcher {
+    /// 监视 paths 中的各路径，并记录其当前的状态；路径可以不存在
+    pub fn new<I, P>(paths: I, interval: Duration) -> io::Result<PollWatcher>
+    where
+        I: IntoIterator<Item = P>,
+        P: AsRef<Path>,
+    {
+        // 以时间、进程 id 及创建的序号作为种子，避免同时创建的实例使用相同的序列
+        static COUNT: AtomicU64 = AtomicU64::new(0);
+        let rng = SystemTime::now().duration_since(UNIX_EPOCH)
+            .map_or(0, |now| now.as_nanos() as u64)
+            ^ (std::process::id() as u64).rotate_left(32)
+            ^ COUNT.fetch_add(1, Ordering::Relaxed).wrapping_mul(0x9e37_79b9_7f4a_7c15);
+        let mut watcher = PollWatcher {
+            watched: BTreeMap::new(),
+            trees: Vec::new(),
+            interval,
+            next_poll: Instant::now(),
+            rng: rng | 1,
+            pending: VecDeque::new(),
+        };
+        for path in paths {
+            watcher.add(path.as_ref())?;
+        }
+        let delay = watcher.jitter(interval / 2, interval / 2);
+        watcher.next_poll = Instant::now() + delay;
+        Ok(watcher)
+    }
+
+    /// 开始监视路径，并记录其当前的状态；已在监视时只更新其状态
+    pub fn add<P: AsRef<Path>>(&mut self, path: P) -> io::Result<()> {
+        let path = path.as_ref();
+        let seen = lstat(path)?;
+        self.watched.insert(path.to_path_buf(), Watched { seen, explicit: true });
+        Ok(())
+    }
+
+    /// 监视目录树中的所有路径，之后在其中新建的路径报告为 Created 并一同监视
+    /// 无法读取的子目录被跳过。
+    pub fn add_tree<P: AsRef<Path>>(&mut self, root: P) -> io::Result<()> {
+        let root = root.as_ref();
+        let mut first_err = None;
+        for (path, seen) in scan_tree(root) {
+            match seen {
+                Ok(seen) => {
+                    self.watched.entry(path)
+                        .and_modify(|watched| watched.seen = Some(seen))
+                        .or_insert(Watched { seen: Some(seen), explicit: false });
+                }
+                Err(err) => { first_err.get_or_insert(err); }
+            }
+        }
+        if let Some(err) = first_err {
+            return Err(err)
+        }
+        if ! self.trees.iter().any(|tree| tree == root) {
+            self.trees.push(root.to_path_buf());
+        }
+        Ok(())
+    }
+
+    /// 停止监视路径；为 add_tree 添加的目录树时，一同停止监视其中发现的路径
+    /// 返回此前是否在监视此路径。
+    pub fn remove<P: AsRef<Path>>(&mut self, path: P) -> bool {
+        let path = path.as_ref();
+        let mut removed = false;
+        if let Some(index) = self.trees.iter().position(|tree| tree == path) {
+            self.trees.remove(index);
+            let trees = &self.trees;
+            self.watched.retain(|watched_path, watched| {
+                watched.explicit || ! watched_path.starts_with(path)
+                    || trees.iter().any(|tree| watched_path.starts_with(tree))
+            });
+            removed = true;
+        }
+        removed | self.watched.remove(path).is_some()
+    }
+
+    /// 正在监视的路径，包括 add_tree 时发现的路径
+    pub fn paths(&self) -> impl Iterator<Item = &Path> {
+        self.watched.keys().map(PathBuf::as_path)
+    }
+
+    /// 立即轮询一次，返回本次及之前尚未取出的事件
+    /// 获取某个路径的 stat 出错（路径不存在除外）时，返回该错误，并保留其之前的状态。
+    pub fn check(&mut self) -> Vec<io::Result<PollEvent>> {
+        self.poll();
+        self.pending.drain(..).collect()
+    }
+
+    fn poll(&mut self) {
+        // 目录树中现有路径的 stat 已在遍历时获取，之后不再重复获取
+        let mut scanned = BTreeMap::new();
+        for tree in &self.trees {
+            for (path, stat) in scan_tree(tree) {
+                match stat {
+                    Ok(stat) => { scanned.insert(path, stat); }
+                    Err(err) if is_missing(&err) => {}
+                    Err(err) => self.pending.push_back(Err(err)),
+                }
+            }
+        }
+
+        let mut deleted = Vec::new();
+        for (path, watched) in &mut self.watched {
+            let seen = match scanned.remove(path) {
+                Some(stat) => Some(stat),
+                None => match lstat(path) {
+                    Ok(seen) => seen,
+                    Err(err) => {
+                        self.pending.push_back(Err(err));
+                        continue
+                    }
+                },
+            };
+            let event = match (watched.seen, seen) {
+                (None, None) => None,
+                (None, Some(new)) => Some(PollEvent::Created(event(path, None, &new))),
+                (Some(_), None) => {
+                    if ! watched.explicit {
+                        deleted.push(path.clone());
+                    }
+                    Some(PollEvent::Deleted(path.clone()))
+                }
+                (Some(old), Some(new)) if old.dev != new.dev || old.ino != new.ino => {
+                    Some(PollEvent::Recreated(event(path, Some(&old), &new)))
+                }
+                (Some(old), Some(new)) => {
+                    if old.perm() == new.perm() && old.uid == new.uid && old.gid == new.gid {
+                        None
+                    } else {
+                        Some(PollEvent::Changed(event(path, Some(&old), &new)))
+                    }
+                }
+            };
+            watched.seen = seen;
+            self.pending.extend(event.map(Ok));
+        }
+        for path in deleted {
+            self.watched.remove(&path);
+        }
+
+        // 余下的为目录树中新建的路径
+        for (path, stat) in scanned {
+            self.pending.push_back(Ok(PollEvent::Created(event(&path, None, &stat))));
+            self.watched.insert(path, Watched { seen: Some(stat), explicit: false });
+        }
+    }
+
+    // 在 [base - spread, base + spread) 内随机取值（xorshift64）
+    fn jitter(&mut self, base: Duration, spread: Duration) -> Duration {
+        self.rng ^= self.rng << 13;
+        self.rng ^= self.rng >> 7;
+        self.rng ^= self.rng << 17;
+        let fraction = (self.rng >> 11) as f64 / (1u64 << 53) as f64;
+        (base + spread.mul_f64(2.0 * fraction)).saturating_sub(spread)
+    }
+}
+
+impl Iterator for PollWatcher {
+    type Item = io::Result<PollEvent>;
+
+    fn next(&mut self) -> Option<io::Result<PollEvent>> {
+        loop {
+            if let Some(event) = self.pending.pop_front() {
+                return Some(event)
+            }
+            if self.watched.is_empty() && self.trees.is_empty() {
+                return None
+            }
+            let now = Instant::now();
+            if self.next_poll > now {
+                thread::sleep(self.next_poll - now);
+            }
+            self.poll();
+            let delay = self.jitter(self.interval, self.interval / 10);
+            self.next_poll = Instant::now() + delay;
+        }
+    }
+}
+
+fn event(path: &Path, old: Option<&Stat>, new: &Stat) -> PermissionEvent {
+    PermissionEvent {
+        path: path.to_path_buf(),
+        old: old.map(|old| Mode::new(old.perm())),
+        new: Mode::new(new.perm()),
+        uid: new.uid,
+        gid: new.gid,
+    }
+}
+
+// 路径不存在时返回 None
+fn lstat(path: &Path) -> io::Result<Option<Stat>> {
+    match stat::stat(path, false) {
+        Ok(stat) => Ok(Some(stat)),
+        Err(err) if is_missing(&err) => Ok(None),
+        Err(err) => Err(err),
+    }
+}
+
+// 目录树中现有的路径及其 stat（取自遍历时的结果）；读取目录出错时返回该错误
+fn scan_tree(root: &Path) -> Vec<(PathBuf, io::Result<Stat>)> {
+    let mut paths = Vec::new();
+    walk(root, |path, stat, dir_err| {
+        if let Some(err) = dir_err {
+            paths.push((path.to_path_buf(), Err(err)));
+        }
+        if let Ok(stat) = stat {
+            paths.push((path.to_path_buf(), Ok(stat)));
+        }
+    });
+    paths
+}
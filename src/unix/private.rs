@@ -0,0 +1,126 @@
+//! 检查保存密钥等机密的文件是否仅当前用户可访问，同 OpenSSH 对私钥的检查
+use std::{
+    io,
+    path::Path,
+};
+
+use crate::modes;
+
+use super::{compute, stat};
+
+/// 机密文件的检查选项，见 PathPermission::is_private
+///
+/// 默认跟随符号链接，检查其目标；要求为普通文件，所有者为当前进程的有效 uid 或 root，
+/// 且所属组及其他用户没有任何权限（mode & 0o077 == 0）。
+///
+/// ```
+/// # use std::{fs, os::unix::fs::symlink};
+/// # use path_permission::{Mode, PathPermission, PrivateOptions};
+/// # let dir = std::env::temp_dir().join(format!("pp-private-{}", std::process::id()));
+/// # fs::create_dir_all(&dir).unwrap();
+/// let key = dir.join("id_ed25519");
+/// fs::write(&key, b"").unwrap();
+/// key.set_mode(Mode::new(0o600)).unwrap();
+/// assert!(key.is_private().unwrap());
+/// key.ensure_private().unwrap();
+///
+/// key.set_mode(Mode::new(0o644)).unwrap();
+/// assert!(! key.is_private().unwrap());
+/// let err = key.ensure_private().unwrap_err();
+/// assert!(err.to_string().contains("0044"));
+///
+/// // TLS 证书的私钥可以允许指定的组读取
+/// key.set_mode(Mode::new(0o640)).unwrap();
+/// let mut options = PrivateOptions::new();
+/// options.allow_group_read(true);
+/// assert!(options.check(&key).unwrap());
+/// key.set_mode(Mode::new(0o660)).unwrap();
+/// assert!(! options.check(&key).unwrap());
+///
+/// // 默认检查符号链接的目标，也可拒绝符号链接
+/// key.set_mode(Mode::new(0o400)).unwrap();
+/// let link = dir.join("link");
+/// symlink(&key, &link).unwrap();
+/// assert!(link.is_private().unwrap());
+/// options.allow_group_read(false).follow_symlinks(false);
+/// assert!(options.ensure(&link).unwrap_err().to_string().contains("symbolic link"));
+/// assert!(! dir.is_private().unwrap());
+/// # fs::remove_dir_all(&dir).unwrap();
+/// ```
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct PrivateOptions {
+    follow_symlinks: bool,
+    allow_group_read: bool,
+}
+
+impl Default for PrivateOptions {
+    fn default() -> PrivateOptions {
+        PrivateOptions::new()
+    }
+}
+
+impl PrivateOptions {
+    pub fn new() -> PrivateOptions {
+        PrivateOptions {
+            follow_symlinks: true,
+            allow_group_read: false,
+        }
+    }
+
+    /// 为 false 时，路径为符号链接则检查不通过
+    pub fn follow_symlinks(&mut self, follow: bool) -> &mut PrivateOptions {
+        self.follow_symlinks = follow;
+        self
+    }
+
+    /// 为 true 时允许所属组可读（如 0640），所属组仍不能有写及执行权限
+    pub fn allow_group_read(&mut self, allow: bool) -> &mut PrivateOptions {
+        self.allow_group_read = allow;
+        self
+    }
+
+    /// 检查是否通过，不通过时返回 Ok(false)；获取 stat 出错时返回 Err
+    pub fn check<P: AsRef<Path>>(&self, path: P) -> io::Result<bool> {
+        Ok(self.violation(path.as_ref())?.is_none())
+    }
+
+    /// 检查是否通过，不通过时返回 PermissionDenied 错误，说明原因，如多余的权限位
+    pub fn ensure<P: AsRef<Path>>(&self, path: P) -> io::Result<()> {
+        let path = path.as_ref();
+        match self.violation(path)? {
+            None => Ok(()),
+            Some(reason) => Err(io::Error::new(io::ErrorKind::PermissionDenied,
+                                               format!("{}: {}", path.display(), reason))),
+        }
+    }
+
+    // 不通过的原因
+    fn violation(&self, path: &Path) -> io::Result<Option<String>> {
+        let stat = if self.follow_symlinks {
+            stat::stat(path, true)?
+        } else {
+            let stat = stat::stat(path, false)?;
+            if stat.is_symlink() {
+                return Ok(Some("is a symbolic link".to_string()))
+            }
+            stat
+        };
+        if ! stat.is_file() {
+            return Ok(Some("not a regular file".to_string()))
+        }
+        if ! compute::is_current_user(stat.uid) && stat.uid != 0 {
+            return Ok(Some(format!("owned by uid {}, not the current user or root", stat.uid)))
+        }
+        let forbidden = if self.allow_group_read {
+            modes::GROUP_W | modes::GROUP_X | modes::OTHER_RWX
+        } else {
+            modes::GROUP_RWX | modes::OTHER_RWX
+        };
+        let extra = stat.perm() & forbidden;
+        if extra != 0 {
+            return Ok(Some(format!("group/other bits {:04o} are set (mode {:04o})",
+                                   extra, stat.perm())))
+        }
+        Ok(None)
+    }
+}
@@ -0,0 +1,185 @@
+//! 计算使某个用户获得某项操作的权限所需的最少变更
+use std::{
+    fmt, io,
+    path::{Path, PathBuf},
+};
+
+use crate::modes;
+
+use super::{
+    chmod_permitted,
+    compute::{self, AccessMode, Privilege},
+    stat, users,
+};
+
+/// 按权限位计算时所模拟的用户：uid 及其所属的全部组
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct UserContext {
+    uid: u32,
+    gids: Vec<u32>,
+}
+
+impl UserContext {
+    pub fn new(uid: u32, gids: Vec<u32>) -> UserContext {
+        UserContext { uid, gids }
+    }
+
+    /// 当前进程的有效 uid，及有效 gid 与附加组
+    pub fn current() -> io::Result<UserContext> {
+        Ok(UserContext {
+            uid: unsafe { libc::geteuid() },
+            gids: compute::current_groups()?,
+        })
+    }
+
+    /// 以用户名查找其 uid 及所属的全部组，用户不存在时返回 NotFound 错误
+    pub fn for_user(name: &str) -> io::Result<UserContext> {
+        let (uid, gids) = users::user_groups(name)?;
+        Ok(UserContext { uid, gids })
+    }
+
+    pub fn uid(&self) -> u32 {
+        self.uid
+    }
+
+    pub fn gids(&self) -> &[u32] {
+        &self.gids
+    }
+}
+
+/// required_mode_for 所检查的操作
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Operation {
+    Read,
+    Write,
+    Execute,
+    /// 进入目录（执行权限），路径须为目录
+    Traverse,
+}
+
+impl Operation {
+    fn access_mode(self) -> AccessMode {
+        match self {
+            Operation::Read => AccessMode::READ,
+            Operation::Write => AccessMode::WRITE,
+            Operation::Execute | Operation::Traverse => AccessMode::EXECUTE,
+        }
+    }
+}
+
+/// 为某一个路径（路径本身或其父级目录）所需的变更
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct RemedyStep {
+    pub path: PathBuf,
+    /// 需加入的权限位，只在用户所匹配到的分类中，如 0o040 即 g+r
+    pub bits: u16,
+    /// 路径所属组的权限位已允许时为其 gid：将用户加入此组，同样可以获得权限
+    pub group: Option<u32>,
+    /// 当前进程能否变更此路径的权限，同 can_chmod；不能时需由所有者 chmod，或先变更所有者
+    pub chmod_permitted: bool,
+}
+
+/// required_mode_for 的结果，按父级目录至路径本身的顺序列出各个所需的变更
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Remedy {
+    steps: Vec<RemedyStep>,
+}
+
+impl Remedy {
+    /// 是否已有权限，即无需任何变更
+    pub fn is_granted(&self) -> bool {
+        self.steps.is_empty()
+    }
+
+    pub fn steps(&self) -> &[RemedyStep] {
+        &self.steps
+    }
+
+    /// 是否有当前进程无法变更其权限的路径，即仅靠 chmod 无法解决
+    pub fn needs_ownership_change(&self) -> bool {
+        self.steps.iter().any(|step| ! step.chmod_permitted)
+    }
+}
+
+impl fmt::Display for Remedy {
+    /// 如："chmod g+r /var/log/app.log or add the user to group adm"，多个变更以 "; " 分隔
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if self.steps.is_empty() {
+            return f.write_str("already permitted")
+        }
+        for (i, step) in self.steps.iter().enumerate() {
+            if i > 0 {
+                f.write_str("; ")?;
+            }
+            write!(f, "chmod {} {}", symbolic(step.bits), step.path.display())?;
+            if ! step.chmod_permitted {
+                f.write_str(" (as the owner)")?;
+            }
+            if let Some(gid) = step.group {
+                match users::group_name(gid) {
+                    Ok(Some(name)) => write!(f, " or add the user to group {}", name)?,
+                    _ => write!(f, " or add the user to group {}", gid)?,
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+// 如 0o040 -> "g+r"，0o005 -> "o+rx"
+fn symbolic(bits: u16) -> String {
+    let (class, shift) = if bits & modes::OWNER_RWX != 0 {
+        ('u', 6)
+    } else if bits & modes::GROUP_RWX != 0 {
+        ('g', 3)
+    } else {
+        ('o', 0)
+    };
+    let mut s = format!("{}+", class);
+    for (bit, c) in [(4, 'r'), (2, 'w'), (1, 'x')] {
+        if (bits >> shift) & bit != 0 {
+            s.push(c);
+        }
+    }
+    s
+}
+
+pub(crate) fn required_mode_for(path: &Path, who: &UserContext, op: Operation) -> io::Result<Remedy> {
+    let path = if path.is_absolute() {
+        path.to_path_buf()
+    } else {
+        std::env::current_dir()?.join(path)
+    };
+    let privilege = Privilege::of_uid(who.uid);
+    // 当前线程能否 chmod 各级路径，与 who 无关
+    let current = Privilege::current();
+
+    let mut steps = Vec::new();
+    let ancestors = path.ancestors().collect::<Vec<_>>();
+    for ancestor in ancestors.into_iter().rev() {
+        let stat = stat::stat(ancestor, true)?;
+        let amode = if ancestor == path {
+            if op == Operation::Traverse && ! stat.is_dir() {
+                return Err(io::Error::new(io::ErrorKind::InvalidInput, "not a directory"))
+            }
+            op.access_mode()
+        } else {
+            AccessMode::EXECUTE
+        };
+        let bits = compute::grant_bits(&stat, who.uid, &who.gids, amode, privilege);
+        if bits == 0 {
+            continue
+        }
+        // 用户属于其他用户分类时，所属组的权限位可能已允许
+        let group = (stat.uid != who.uid && ! who.gids.contains(&stat.gid)
+                     && compute::stat_allows(&stat, who.uid, &[stat.gid], amode, privilege))
+            .then_some(stat.gid);
+        steps.push(RemedyStep {
+            path: ancestor.to_path_buf(),
+            bits,
+            group,
+            chmod_permitted: chmod_permitted(&stat, current),
+        });
+    }
+    Ok(Remedy { steps })
+}
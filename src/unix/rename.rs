@@ -0,0 +1,100 @@
+//! 重命名（移动）的前提条件
+use std::{
+    io,
+    path::Path,
+    os::unix::fs::MetadataExt,
+};
+
+use super::{compute::Privilege, effective_access, parent_dir, removable_in};
+
+#[cfg(target_os = "macos")]
+use super::PathPermission;
+
+/// is_renamable_to 的结果，指出 rename(2) 会因哪个条件而失败
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum RenameCheck {
+    /// 可以重命名
+    Allowed,
+    /// 源路径不存在
+    SourceMissing,
+    /// 无法从源目录中移除：无写和执行的权限，或 S_ISVTX 下不满足所属用户的条件
+    SourceNotRemovable,
+    /// 目标的父级目录不存在（或为根目录）
+    DestinationParentMissing,
+    /// 两者不在同一文件系统上，rename(2) 会返回 EXDEV
+    CrossDevice,
+    /// 源路径为目录，移动至其他目录时需更新其中的 ".."，但对其无写的权限
+    SourceDirectoryNotWritable,
+    /// 无法在目标目录中创建：无写和执行的权限
+    DestinationNotCreatable,
+    /// 目标已存在，但无法将其替换（删除）
+    DestinationNotReplaceable,
+}
+
+impl RenameCheck {
+    pub fn is_allowed(&self) -> bool {
+        *self == RenameCheck::Allowed
+    }
+}
+
+pub(crate) fn is_renamable_to(source: &Path, dest: &Path) -> io::Result<RenameCheck> {
+    // 重命名的是路径本身，不跟随符号链接
+    let source_stat = match source.symlink_metadata() {
+        Ok(metadata) => metadata,
+        Err(ref e) if e.kind() == io::ErrorKind::NotFound => {
+            return Ok(RenameCheck::SourceMissing)
+        }
+        Err(e) => return Err(e),
+    };
+    #[cfg(target_os = "macos")]
+    {
+        if ! source_stat.file_type().is_symlink() && source.bsd_flags()?.is_immutable() {
+            return Ok(RenameCheck::SourceNotRemovable)
+        }
+    }
+    let source_parent = match parent_dir(source)? {
+        Some(parent) => parent,
+        None => return Ok(RenameCheck::SourceNotRemovable),
+    };
+    let privilege = Privilege::current();
+    if ! removable_in(&source_parent, source_stat.uid(), privilege)? {
+        return Ok(RenameCheck::SourceNotRemovable)
+    }
+
+    let dest_parent = match parent_dir(dest)? {
+        Some(parent) => parent,
+        None => return Ok(RenameCheck::DestinationParentMissing),
+    };
+    let dest_parent_stat = match dest_parent.metadata() {
+        Ok(metadata) if metadata.is_dir() => metadata,
+        _ => return Ok(RenameCheck::DestinationParentMissing),
+    };
+    let source_parent_stat = source_parent.metadata()?;
+    if source_parent_stat.dev() != dest_parent_stat.dev() {
+        return Ok(RenameCheck::CrossDevice)
+    }
+    if source_stat.is_dir() && source_parent_stat.ino() != dest_parent_stat.ino()
+        && ! effective_access(source, libc::W_OK)? {
+        return Ok(RenameCheck::SourceDirectoryNotWritable)
+    }
+
+    match dest.symlink_metadata() {
+        // 目标已存在时，会被替换掉，需同样满足删除的条件
+        Ok(dest_stat) => {
+            // 同一文件，rename(2) 不做任何操作
+            if dest_stat.dev() == source_stat.dev() && dest_stat.ino() == source_stat.ino() {
+                return Ok(RenameCheck::Allowed)
+            }
+            if ! removable_in(&dest_parent, dest_stat.uid(), privilege)? {
+                return Ok(RenameCheck::DestinationNotReplaceable)
+            }
+        }
+        Err(_) => {
+            if ! effective_access(&dest_parent, libc::X_OK + libc::W_OK)? {
+                return Ok(RenameCheck::DestinationNotCreatable)
+            }
+        }
+    }
+
+    Ok(RenameCheck::Allowed)
+}
@@ -0,0 +1,132 @@
+//! 判断能否创建、删除路径的规则
+//!
+//! 规则只通过 MetadataSource 获取路径的信息，由 PathPermission 以真实的文件系统（RealFs）
+//! 调用，testing::MockPath 以 MockFs 调用，两者的结果因此总是一致。
+use std::{
+    io,
+    path::{Path, PathBuf},
+};
+
+use crate::modes;
+
+use super::{
+    compute::{self, AccessMode, Privilege},
+    stat::{self, Stat},
+};
+
+// 判断时所需的路径信息
+pub(crate) trait MetadataSource {
+    fn stat(&self, path: &Path, follow: bool) -> io::Result<Stat>;
+
+    // 同 access(2)，以实际 uid/gid 检查：无查看此路径的权限时返回 Ok(false)，不存在等返回错误
+    fn access(&self, path: &Path, amode: AccessMode) -> io::Result<bool>;
+
+    // 同 access，但以有效 uid/gid 检查（AT_EACCESS）
+    fn effective_access(&self, path: &Path, amode: AccessMode) -> io::Result<bool>;
+
+    fn euid(&self) -> u32;
+
+    fn privilege(&self) -> Privilege;
+
+    // 同 unix::parent_dir
+    fn parent_dir(&self, path: &Path) -> io::Result<Option<PathBuf>>;
+
+    // 是否设置了阻止删除的标志，如 macOS 的 uchg/schg
+    fn is_immutable(&self, _path: &Path, _follow: bool) -> io::Result<bool> {
+        Ok(false)
+    }
+}
+
+// 真实的文件系统，当前线程的身份
+pub(crate) struct RealFs;
+
+impl MetadataSource for RealFs {
+    fn stat(&self, path: &Path, follow: bool) -> io::Result<Stat> {
+        stat::stat(path, follow)
+    }
+
+    fn access(&self, path: &Path, amode: AccessMode) -> io::Result<bool> {
+        super::access(path, amode.bits())
+    }
+
+    fn effective_access(&self, path: &Path, amode: AccessMode) -> io::Result<bool> {
+        super::effective_access(path, amode.bits())
+    }
+
+    fn euid(&self) -> u32 {
+        unsafe { libc::geteuid() }
+    }
+
+    fn privilege(&self) -> Privilege {
+        Privilege::current()
+    }
+
+    fn parent_dir(&self, path: &Path) -> io::Result<Option<PathBuf>> {
+        super::parent_dir(path)
+    }
+
+    #[cfg(target_os = "macos")]
+    fn is_immutable(&self, path: &Path, follow: bool) -> io::Result<bool> {
+        let flags = if follow {
+            super::darwin::bsd_flags(path)?
+        } else {
+            super::darwin::bsd_flags_nofollow(path)?
+        };
+        Ok(flags.is_immutable())
+    }
+}
+
+// 见 PathPermission::is_creatable
+pub(crate) fn is_creatable<S: MetadataSource>(source: &S, path: &Path) -> io::Result<bool> {
+    let parent = match source.parent_dir(path)? {
+        Some(parent) => parent,
+        None => return Ok(false),
+    };
+    match source.stat(&parent, true) {
+        // 需要能进入父级目录，并对其有写权限
+        Ok(stat) if stat.is_dir() => {
+            Ok(source.access(&parent, AccessMode::EXECUTE)? && source.access(&parent, AccessMode::WRITE)?)
+        }
+        // 父级路径存在，但不是目录（或指向目录的符号链接），无法在其下创建
+        Ok(_) => Ok(false),
+        // 悬空的符号链接同样占用了此路径
+        Err(_) if source.stat(&parent, false).is_ok() => Ok(false),
+        // 父级路径不存在（或其上级中有非目录的路径），继续向上查找
+        Err(_) => is_creatable(source, &parent),
+    }
+}
+
+// 见 PathPermission::is_removable，follow 为 false 时判断符号链接本身
+pub(crate) fn is_removable<S: MetadataSource>(source: &S, path: &Path, follow: bool) -> io::Result<bool> {
+    // 文件不存在时，返回Ok(false)
+    let stat = match source.stat(path, follow) {
+        Ok(stat) => stat,
+        Err(_) => return Ok(false),
+    };
+    // uchg/schg 标志同样阻止删除
+    if source.is_immutable(path, follow)? {
+        return Ok(false)
+    }
+    let parent = match source.parent_dir(path)? {
+        Some(parent) => parent,
+        None => return Ok(false),
+    };
+
+    removable_in(source, &parent, stat.uid, source.privilege())
+}
+
+// 判断所有者为 owner 的路径，能否从目录 parent 中删除
+// privilege 由调用者获取，以便多次检查时只获取一次
+// 与内核一致，全部以有效 uid/gid 判断
+pub(crate) fn removable_in<S: MetadataSource>(source: &S, parent: &Path, owner: u32,
+                                              privilege: Privilege) -> io::Result<bool> {
+    // 设置了 S_ISVTX 时，须为文件或目录的所有者，或拥有特权（root 或 CAP_FOWNER）
+    let parent_stat = source.stat(parent, true)?;
+    if parent_stat.perm() & modes::STICKY != 0
+        && ! compute::sticky_allows(source.euid(), owner, parent_stat.uid, privilege) {
+        return Ok(false)
+    }
+
+    // 需要对父级目录有写和执行的权限
+    source.effective_access(parent, AccessMode::EXECUTE | AccessMode::WRITE)
+}
@@ -0,0 +1,68 @@
+//! 脚本（#!）的解释器
+use std::{
+    fs::File,
+    io::{self, Read},
+    path::{Path, PathBuf},
+    os::unix::ffi::OsStrExt,
+};
+
+use super::PathPermission;
+
+// 只读取第一行的前 256 个字节
+const SHEBANG_MAX: usize = 256;
+
+pub(crate) fn script_interpreter(path: &Path) -> io::Result<Option<PathBuf>> {
+    Ok(shebang(path)?.filter(|interpreter| ! interpreter.as_os_str().is_empty()))
+}
+
+pub(crate) fn is_runnable(path: &Path) -> io::Result<bool> {
+    if ! path.is_executable_file()? {
+        return Ok(false)
+    }
+    // 空文件无法被 execve 执行（ENOEXEC）
+    if path.metadata()?.len() == 0 {
+        return Ok(false)
+    }
+
+    match shebang(path) {
+        // #! 后没有解释器
+        Ok(Some(ref interpreter)) if interpreter.as_os_str().is_empty() => Ok(false),
+        Ok(Some(interpreter)) => match interpreter.is_executable_file() {
+            Err(ref e) if super::is_missing(e) => Ok(false),
+            result => result,
+        },
+        Ok(None) => Ok(true),
+        // 只有执行权限时无法读取，但仍可执行（脚本则需要可读）
+        Err(ref e) if e.kind() == io::ErrorKind::PermissionDenied => Ok(true),
+        Err(e) => Err(e),
+    }
+}
+
+// 解析 #! 行，返回解释器的路径（可能为空），不是脚本时返回 None
+// 与内核一致，只识别开头的 "#!"，解释器之后的参数被忽略
+fn shebang(path: &Path) -> io::Result<Option<PathBuf>> {
+    let mut buf = [0; SHEBANG_MAX];
+    let mut len = 0;
+    let mut file = File::open(path)?;
+    while len < buf.len() {
+        match file.read(&mut buf[len..]) {
+            Ok(0) => break,
+            Ok(n) => len += n,
+            Err(ref e) if e.kind() == io::ErrorKind::Interrupted => {}
+            Err(e) => return Err(e),
+        }
+    }
+
+    let head = &buf[..len];
+    if ! head.starts_with(b"#!") {
+        return Ok(None)
+    }
+    let line = head[2..].split(|&b| b == b'\n').next().unwrap_or(&[]);
+    let is_blank = |b: &u8| *b == b' ' || *b == b'\t' || *b == b'\r';
+    let interpreter = line.iter()
+        .skip_while(|b| is_blank(b))
+        .take_while(|b| ! is_blank(b))
+        .cloned()
+        .collect::<Vec<u8>>();
+    Ok(Some(PathBuf::from(std::ffi::OsStr::from_bytes(&interpreter))))
+}
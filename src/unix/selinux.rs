@@ -0,0 +1,44 @@
+//! SELinux 安全上下文
+//!
+//! 直接读写 security.selinux 扩展属性，不依赖 libselinux。
+use std::{
+    fs,
+    io,
+    path::Path,
+};
+
+use super::xattr;
+
+const XATTR_NAME: &str = "security.selinux";
+
+pub(crate) fn selinux_context(path: &Path) -> io::Result<Option<String>> {
+    match xattr::get(path, XATTR_NAME)? {
+        None => Ok(None),
+        Some(mut value) => {
+            // 属性值通常以'\0'结尾
+            if value.last() == Some(&0) {
+                value.pop();
+            }
+            String::from_utf8(value)
+                .map(Some)
+                .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))
+        }
+    }
+}
+
+pub(crate) fn set_selinux_context(path: &Path, context: &str) -> io::Result<bool> {
+    // 与 setfilecon(3) 一致，写入时包含结尾的'\0'
+    let mut value = Vec::from(context.as_bytes());
+    value.push(0);
+    xattr::set(path, XATTR_NAME, &value)
+}
+
+/// 判断 SELinux 是否处于 enforcing 模式
+/// 未启用 SELinux（/sys/fs/selinux/enforce 不存在）时返回 Ok(false)
+pub fn is_selinux_enforcing() -> io::Result<bool> {
+    match fs::read_to_string("/sys/fs/selinux/enforce") {
+        Ok(value) => Ok(value.trim() == "1"),
+        Err(ref e) if e.kind() == io::ErrorKind::NotFound => Ok(false),
+        Err(e) => Err(e),
+    }
+}
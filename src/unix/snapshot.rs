@@ -0,0 +1,300 @@
+//! 记录目录树中各路径的权限及所有者，之后再还原
+use std::{
+    ffi::OsStr,
+    io::{self, BufRead, Write},
+    path::{Path, PathBuf},
+    os::unix::ffi::OsStrExt,
+};
+
+use crate::modes;
+
+use super::{
+    stat,
+    walk::walk,
+    PermSnapshot,
+};
+
+/// 目录树中各路径的权限、所有者及所属组，由 PathPermission::snapshot_tree 创建
+///
+/// 路径以相对于起始路径的形式保存（起始路径本身为 ""），按先序排列，父级目录总在其下的
+/// 路径之前。
+///
+/// 启用 serde 特性时可序列化为各路径对象的数组，字段为 path、mode（四位八进制的字符串）、
+/// uid、gid；路径需为有效的 UTF-8。此外不依赖 serde，也可通过 write_to 写入文件，再以
+/// read_from 读回，可保存任意的路径，格式为每行一个路径：
+/// `权限（四位八进制） uid gid 相对路径`，路径中的 `\` 及换行分别写作 `\\`、`\n`。
+///
+/// ```
+/// # use std::fs;
+/// # use path_permission::{Mode, PathPermission, PermissionSnapshot};
+/// # let dir = std::env::temp_dir().join(format!("pp-snapshot-tree-{}", std::process::id()));
+/// # fs::create_dir_all(dir.join("sub")).unwrap();
+/// fs::write(dir.join("sub/file"), b"").unwrap();
+/// dir.set_mode(Mode::new(0o755)).unwrap();
+/// dir.join("sub").set_mode(Mode::new(0o750)).unwrap();
+/// dir.join("sub/file").set_mode(Mode::new(0o640)).unwrap();
+///
+/// let snapshot = dir.snapshot_tree().unwrap();
+/// let mut saved = Vec::new();
+/// snapshot.write_to(&mut saved).unwrap();
+///
+/// // 迁移时变更了权限，并删除了一个文件；sub 已无法进入
+/// dir.join("sub/file").set_mode(Mode::new(0o666)).unwrap();
+/// dir.join("sub").set_mode(Mode::new(0o000)).unwrap();
+///
+/// let snapshot = PermissionSnapshot::read_from(&saved[..]).unwrap();
+/// let report = snapshot.restore(&dir).unwrap();
+/// assert_eq!(report.restored, 2);
+/// assert!(report.missing.is_empty() && report.failed.is_empty());
+/// assert_eq!(dir.join("sub").get_mode().unwrap(), 0o750);
+/// assert_eq!(dir.join("sub/file").get_mode().unwrap(), 0o640);
+///
+/// fs::remove_file(dir.join("sub/file")).unwrap();
+/// let report = snapshot.restore(&dir).unwrap();
+/// assert_eq!(report.missing, vec![dir.join("sub/file")]);
+/// # fs::remove_dir_all(&dir).unwrap();
+/// ```
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct PermissionSnapshot {
+    entries: Vec<(PathBuf, PermSnapshot)>,
+}
+
+/// PermissionSnapshot::restore 的结果
+#[derive(Debug, Default)]
+pub struct RestoreReport {
+    /// 实际变更了权限或所有者的路径数量
+    pub restored: usize,
+    /// 已不存在的路径，被跳过
+    pub missing: Vec<PathBuf>,
+    /// 未能还原的路径及其错误
+    pub failed: Vec<(PathBuf, io::Error)>,
+}
+
+impl PermissionSnapshot {
+    // 先序遍历 root，不跟随符号链接；遇到错误时返回遇到的第一个错误
+    pub(crate) fn new(root: &Path) -> io::Result<PermissionSnapshot> {
+        let mut entries = Vec::new();
+        let mut first_err = None;
+
+        walk(root, |path, stat, dir_err| {
+            match stat {
+                Ok(stat) => {
+                    // walk 返回的路径都以 root 开头
+                    let relative = path.strip_prefix(root).unwrap_or(path);
+                    entries.push((relative.to_path_buf(), PermSnapshot::from_stat(&stat)));
+                }
+                Err(err) => {
+                    first_err.get_or_insert(err);
+                }
+            }
+            if let Some(err) = dir_err {
+                first_err.get_or_insert(err);
+            }
+        });
+
+        match first_err {
+            Some(err) => Err(err),
+            None => Ok(PermissionSnapshot { entries }),
+        }
+    }
+
+    /// 各路径（相对于起始路径）及其权限、所有者
+    pub fn entries(&self) -> &[(PathBuf, PermSnapshot)] {
+        &self.entries
+    }
+
+    /// 将记录的权限还原至 root 下相同相对路径上，不跟随符号链接
+    /// 当前进程为 root 时同时还原所有者及所属组，否则只还原权限；符号链接只还原所有者。
+    /// 已不存在的路径记录于 missing，失败的路径记录于 failed，均不会中断还原。
+    ///
+    /// 为了能够进入之后被设置为无法进入的目录，先按先序还原各路径，其间目录额外保留
+    /// 所有者的执行位；最后再按相反的顺序去除这些目录上多余的执行位。
+    pub fn restore(&self, root: &Path) -> io::Result<RestoreReport> {
+        let privileged = unsafe { libc::geteuid() } == 0;
+        let mut report = RestoreReport::default();
+        let mut dirs = Vec::new();
+
+        for (relative, snap) in &self.entries {
+            let path = root.join(relative);
+            match restore_entry(&path, snap, privileged) {
+                Ok(Some((changed, is_dir))) => {
+                    if changed {
+                        report.restored += 1;
+                    }
+                    if is_dir && snap.mode & modes::OWNER_X == 0 {
+                        dirs.push((path, snap.mode));
+                    }
+                }
+                Ok(None) => report.missing.push(path),
+                Err(err) => report.failed.push((path, err)),
+            }
+        }
+
+        for (path, mode) in dirs.into_iter().rev() {
+            if let Err(err) = set_mode(&path, mode) {
+                report.failed.push((path, err));
+            }
+        }
+        Ok(report)
+    }
+
+    /// 以文本形式写入 writer，见 PermissionSnapshot 的说明
+    pub fn write_to<W: Write>(&self, mut writer: W) -> io::Result<()> {
+        for (path, snap) in &self.entries {
+            write!(writer, "{:04o} {} {} ", snap.mode, snap.uid, snap.gid)?;
+            for &b in path.as_os_str().as_bytes() {
+                match b {
+                    b'\\' => writer.write_all(b"\\\\")?,
+                    b'\n' => writer.write_all(b"\\n")?,
+                    _ => writer.write_all(&[b])?,
+                }
+            }
+            writer.write_all(b"\n")?;
+        }
+        Ok(())
+    }
+
+    /// 读取由 write_to 写入的内容，格式不符时返回 InvalidData 错误
+    pub fn read_from<R: BufRead>(reader: R) -> io::Result<PermissionSnapshot> {
+        let mut entries = Vec::new();
+        for (i, line) in reader.split(b'\n').enumerate() {
+            let line = line?;
+            let invalid = |reason: &str| {
+                io::Error::new(io::ErrorKind::InvalidData, format!("line {}: {}", i + 1, reason))
+            };
+            let mut fields = line.splitn(4, |&b| b == b' ');
+            let mut number = |radix| {
+                fields.next()
+                    .and_then(|field| std::str::from_utf8(field).ok())
+                    .and_then(|field| u32::from_str_radix(field, radix).ok())
+                    .ok_or_else(|| invalid("expected mode, uid and gid"))
+            };
+            let (mode, uid, gid) = (number(8)?, number(10)?, number(10)?);
+            if mode > u32::from(modes::ALL_BITS) {
+                return Err(invalid("mode out of range"))
+            }
+            let path = fields.next().ok_or_else(|| invalid("missing path"))?;
+            let path = unescape(path).ok_or_else(|| invalid("invalid escape in path"))?;
+            entries.push((path, PermSnapshot { mode: mode as u16, uid, gid }));
+        }
+        Ok(PermissionSnapshot { entries })
+    }
+}
+
+/// 序列化为 `[{"path": "", "mode": "0755", "uid": 0, "gid": 0}, ...]`
+///
+/// ```
+/// # use std::fs;
+/// # use path_permission::{Mode, PathPermission, PermissionSnapshot};
+/// # let dir = std::env::temp_dir().join(format!("pp-snapshot-serde-{}", std::process::id()));
+/// # fs::create_dir_all(&dir).unwrap();
+/// fs::write(dir.join("file"), b"").unwrap();
+/// dir.set_mode(Mode::new(0o750)).unwrap();
+/// dir.join("file").set_mode(Mode::new(0o640)).unwrap();
+///
+/// let snapshot = dir.snapshot_tree().unwrap();
+/// let json = serde_json::to_string(&snapshot).unwrap();
+/// assert!(json.starts_with(r#"[{"path":"","mode":"0750","uid":"#));
+/// assert!(json.contains(r#"{"path":"file","mode":"0640","uid":"#));
+/// assert_eq!(serde_json::from_str::<PermissionSnapshot>(&json).unwrap(), snapshot);
+///
+/// dir.join("file").set_mode(Mode::new(0o666)).unwrap();
+/// let snapshot: PermissionSnapshot = serde_json::from_str(&json).unwrap();
+/// assert_eq!(snapshot.restore(&dir).unwrap().restored, 1);
+/// assert_eq!(dir.join("file").get_mode().unwrap(), 0o640);
+/// # fs::remove_dir_all(&dir).unwrap();
+/// ```
+#[cfg(feature = "serde")]
+impl serde::Serialize for PermissionSnapshot {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        #[derive(serde::Serialize)]
+        struct Entry<'a> {
+            path: &'a Path,
+            #[serde(with = "crate::mode_serde::octal_bits")]
+            mode: u16,
+            uid: u32,
+            gid: u32,
+        }
+
+        serializer.collect_seq(self.entries.iter().map(|(path, snap)| Entry {
+            path,
+            mode: snap.mode,
+            uid: snap.uid,
+            gid: snap.gid,
+        }))
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de> serde::Deserialize<'de> for PermissionSnapshot {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<PermissionSnapshot, D::Error> {
+        #[derive(serde::Deserialize)]
+        struct Entry {
+            path: PathBuf,
+            #[serde(with = "crate::mode_serde::octal_bits")]
+            mode: u16,
+            uid: u32,
+            gid: u32,
+        }
+
+        let entries = <Vec<Entry> as serde::Deserialize>::deserialize(deserializer)?;
+        Ok(PermissionSnapshot {
+            entries: entries.into_iter()
+                .map(|entry| (entry.path, PermSnapshot { mode: entry.mode, uid: entry.uid, gid: entry.gid }))
+                .collect(),
+        })
+    }
+}
+
+// 返回是否变更了路径，及其是否为目录；路径不存在时返回 None
+fn restore_entry(path: &Path, snap: &PermSnapshot, privileged: bool)
+    -> io::Result<Option<(bool, bool)>> {
+    let current = match stat::stat(path, false) {
+        Ok(stat) => stat,
+        Err(ref e) if super::is_missing(e) => return Ok(None),
+        Err(e) => return Err(e),
+    };
+
+    let mut changed = false;
+    // chown 会清除 S_ISUID、S_ISGID，需先于 chmod
+    if privileged && (current.uid != snap.uid || current.gid != snap.gid) {
+        if current.is_symlink() {
+            super::lchown(path, snap.uid, snap.gid)?;
+        } else {
+            super::chown(path, snap.uid, snap.gid)?;
+        }
+        changed = true;
+    }
+    // chmod 会跟随符号链接，修改的将是链接目标
+    if current.is_symlink() {
+        return Ok(Some((changed, false)))
+    }
+
+    // 目录暂时保留所有者的执行位，以便还原其下的路径
+    let mode = if current.is_dir() { snap.mode | modes::OWNER_X } else { snap.mode };
+    if changed || current.perm() != mode {
+        set_mode(path, mode)?;
+    }
+    Ok(Some((changed || current.perm() != snap.mode, current.is_dir())))
+}
+
+fn set_mode(path: &Path, mode: u16) -> io::Result<()> {
+    super::chmod(path, libc::mode_t::from(mode))
+}
+
+fn unescape(bytes: &[u8]) -> Option<PathBuf> {
+    let mut path = Vec::with_capacity(bytes.len());
+    let mut iter = bytes.iter();
+    while let Some(&b) = iter.next() {
+        if b != b'\\' {
+            path.push(b);
+            continue
+        }
+        match iter.next() {
+            Some(b'\\') => path.push(b'\\'),
+            Some(b'n') => path.push(b'\n'),
+            _ => return None,
+        }
+    }
+    Some(PathBuf::from(OsStr::from_bytes(&path)))
+}
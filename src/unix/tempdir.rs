@@ -0,0 +1,137 @@
+//! 检查及选择可安全地写入临时文件的目录
+use std::{
+    env, io,
+    path::{Path, PathBuf},
+};
+
+use crate::modes;
+
+use super::{compute, stat, PathPermission};
+
+/// 临时目录的检查选项，见 PathPermission::is_secure_tempdir
+///
+/// 跟随符号链接，要求：为当前进程可进入及写入的目录；所有者为当前进程的有效 uid 或
+/// root；所属组或其他用户可写时须设置了 S_ISVTX，否则他人可删除或替换其中的文件。
+/// 设置 require_exec 后，还要求所在的文件系统不是以 noexec 挂载的。
+///
+/// ```
+/// # use std::{fs, path::PathBuf};
+/// # use path_permission::{Mode, PathPermission, TempDirOptions, select_secure_tempdir};
+/// # let dir = std::env::temp_dir().join(format!("pp-tempdir-{}", std::process::id()));
+/// # fs::create_dir_all(&dir).unwrap();
+/// dir.set_mode(Mode::new(0o777)).unwrap();
+/// assert!(! dir.is_secure_tempdir().unwrap());
+/// dir.set_mode(Mode::new(0o1777)).unwrap();
+/// assert!(dir.is_secure_tempdir().unwrap());
+///
+/// // 依次检查，返回第一个安全的目录
+/// let missing = dir.join("missing");
+/// let selected = select_secure_tempdir(&[missing.clone(), dir.clone()]).unwrap();
+/// assert_eq!(selected, dir);
+///
+/// // 均不安全时，错误中列出各目录的原因
+/// dir.set_mode(Mode::new(0o777)).unwrap();
+/// let err = select_secure_tempdir(&[missing.clone(), dir.clone()]).unwrap_err();
+/// let message = err.to_string();
+/// assert!(message.contains(&format!("{}: ", missing.display())));
+/// assert!(message.contains("without the sticky bit"));
+///
+/// let mut options = TempDirOptions::new();
+/// options.require_exec(true);
+/// dir.set_mode(Mode::new(0o700)).unwrap();
+/// assert_eq!(options.check(&dir).unwrap(), ! dir.is_on_noexec_mount().unwrap());
+/// # fs::remove_dir_all(&dir).unwrap();
+/// ```
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct TempDirOptions {
+    require_exec: bool,
+}
+
+impl Default for TempDirOptions {
+    fn default() -> TempDirOptions {
+        TempDirOptions::new()
+    }
+}
+
+impl TempDirOptions {
+    pub fn new() -> TempDirOptions {
+        TempDirOptions {
+            require_exec: false,
+        }
+    }
+
+    /// 为 true 时要求文件系统不是以 noexec 挂载的，用于将在其中运行程序的情况
+    pub fn require_exec(&mut self, require: bool) -> &mut TempDirOptions {
+        self.require_exec = require;
+        self
+    }
+
+    /// 检查是否安全，不安全时返回 Ok(false)；获取 stat 等出错时返回 Err
+    pub fn check<P: AsRef<Path>>(&self, path: P) -> io::Result<bool> {
+        Ok(self.violation(path.as_ref())?.is_none())
+    }
+
+    /// 依次检查 candidates，返回第一个安全的目录；candidates 为空时依次检查 TMPDIR、
+    /// /tmp 及 /var/tmp。均不安全时返回 NotFound 错误，列出各目录不安全（或检查出错）的原因。
+    pub fn select(&self, candidates: &[PathBuf]) -> io::Result<PathBuf> {
+        let defaults;
+        let candidates = if candidates.is_empty() {
+            defaults = default_candidates();
+            &defaults[..]
+        } else {
+            candidates
+        };
+
+        let mut reasons = Vec::new();
+        for candidate in candidates {
+            let reason = match self.violation(candidate) {
+                Ok(None) => return Ok(candidate.clone()),
+                Ok(Some(reason)) => reason,
+                Err(err) => err.to_string(),
+            };
+            reasons.push(format!("{}: {}", candidate.display(), reason));
+        }
+        Err(io::Error::new(io::ErrorKind::NotFound,
+                           format!("no secure temporary directory ({})", reasons.join("; "))))
+    }
+
+    // 不安全的原因
+    fn violation(&self, path: &Path) -> io::Result<Option<String>> {
+        let stat = stat::stat(path, true)?;
+        if ! stat.is_dir() {
+            return Ok(Some("not a directory".to_string()))
+        }
+        if ! compute::is_current_user(stat.uid) && stat.uid != 0 {
+            return Ok(Some(format!("owned by uid {}, not the current user or root", stat.uid)))
+        }
+        if stat.perm() & (modes::GROUP_W | modes::OTHER_W) != 0 && stat.perm() & modes::STICKY == 0 {
+            return Ok(Some(format!("group or world writable without the sticky bit (mode {:04o})",
+                                   stat.perm())))
+        }
+        if ! path.is_traversable()? || ! path.access(libc::W_OK)? {
+            return Ok(Some("not writable by the current user".to_string()))
+        }
+        if self.require_exec && path.is_on_noexec_mount()? {
+            return Ok(Some("on a noexec mount".to_string()))
+        }
+        Ok(None)
+    }
+}
+
+// TMPDIR（已设置且非空时）、/tmp、/var/tmp
+fn default_candidates() -> Vec<PathBuf> {
+    let mut candidates = Vec::new();
+    if let Some(dir) = env::var_os("TMPDIR").filter(|dir| ! dir.is_empty()) {
+        candidates.push(PathBuf::from(dir));
+    }
+    candidates.push(PathBuf::from("/tmp"));
+    candidates.push(PathBuf::from("/var/tmp"));
+    candidates
+}
+
+/// 依次检查 candidates，返回第一个安全的临时目录，规则同 PathPermission::is_secure_tempdir
+/// candidates 为空时依次检查 TMPDIR、/tmp 及 /var/tmp；均不安全时返回 NotFound 错误，
+/// 列出各目录的原因。需要 noexec 检查时见 TempDirOptions::select。
+pub fn select_secure_tempdir(candidates: &[PathBuf]) -> io::Result<PathBuf> {
+    TempDirOptions::new().select(candidates)
+}
@@ -0,0 +1,305 @@
+//! 用于测试的模拟文件系统
+//!
+//! 以编程方式设置各路径的权限、所有者及是否存在，并模拟以某个用户（uid 及所属组）
+//! 运行，按与 PathPermission 相同的规则计算，不会访问真实的文件系统。
+//! 在 CI 中以 root 运行时（一切皆可写），依然可以测试无权限的分支。
+//! MockPath 的 is_creatable、is_removable 与 PathPermission 使用同一份实现，
+//! 只是路径的信息来自 MockFs。
+//!
+//! 只支持绝对路径（相对路径视为相对于根目录），不支持符号链接。
+//!
+//! ```
+//! # use path_permission::testing::MockFs;
+//! let mut fs = MockFs::new();
+//! fs.add_dir("/srv", 0o755, 0, 0)
+//!   .add_file("/srv/config", 0o644, 0, 0)
+//!   .run_as(1000, &[1000]);
+//!
+//! assert!(fs.path("/srv/config").is_readable().unwrap());
+//! assert!(! fs.path("/srv/config").is_writable().unwrap());
+//! assert!(! fs.path("/srv/new").is_creatable().unwrap());
+//!
+//! fs.run_as(0, &[0]);
+//! assert!(fs.path("/srv/config").is_writable().unwrap());
+//! ```
+use std::{
+    collections::BTreeMap,
+    io,
+    path::{Component, Path, PathBuf},
+};
+
+use crate::{modes, FileKind};
+
+use super::{
+    compute::{self, AccessMode, Privilege},
+    rules::{self, MetadataSource},
+    stat::Stat,
+};
+
+/// 模拟的文件或目录
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct MockEntry {
+    /// 权限，包括 S_ISUID、S_ISGID、S_ISVTX（0o7777）
+    pub mode: u16,
+    pub uid: u32,
+    pub gid: u32,
+    pub is_dir: bool,
+}
+
+impl MockEntry {
+    fn stat(&self) -> Stat {
+        let kind = if self.is_dir { FileKind::Directory } else { FileKind::Regular };
+        Stat {
+            mode: kind.bits() | u32::from(self.mode),
+            uid: self.uid,
+            gid: self.gid,
+            dev: 0,
+            ino: 0,
+        }
+    }
+}
+
+/// 模拟的文件系统
+#[derive(Clone, Debug)]
+pub struct MockFs {
+    entries: BTreeMap<PathBuf, MockEntry>,
+    uid: u32,
+    gids: Vec<u32>,
+}
+
+impl Default for MockFs {
+    fn default() -> MockFs {
+        MockFs::new()
+    }
+}
+
+impl MockFs {
+    /// 只包含根目录（0o755，所有者为 root），以 root 运行
+    pub fn new() -> MockFs {
+        let mut entries = BTreeMap::new();
+        entries.insert(PathBuf::from("/"), MockEntry { mode: 0o755, uid: 0, gid: 0, is_dir: true });
+        MockFs {
+            entries,
+            uid: 0,
+            gids: vec![0],
+        }
+    }
+
+    /// 模拟以 uid 运行，gids 为有效 gid 及附加组；只有 uid 为 0 时拥有特权
+    pub fn run_as(&mut self, uid: u32, gids: &[u32]) -> &mut MockFs {
+        self.uid = uid;
+        self.gids = gids.to_vec();
+        self
+    }
+
+    /// 添加（或替换）目录，不会创建父级目录
+    pub fn add_dir<P: AsRef<Path>>(&mut self, path: P, mode: u16, uid: u32, gid: u32) -> &mut MockFs {
+        self.insert(path.as_ref(), MockEntry { mode: mode & modes::ALL_BITS, uid, gid, is_dir: true })
+    }
+
+    /// 添加（或替换）普通文件，不会创建父级目录
+    pub fn add_file<P: AsRef<Path>>(&mut self, path: P, mode: u16, uid: u32, gid: u32) -> &mut MockFs {
+        self.insert(path.as_ref(), MockEntry { mode: mode & modes::ALL_BITS, uid, gid, is_dir: false })
+    }
+
+    /// 删除路径及其下的所有路径，返回被删除的路径本身
+    pub fn remove<P: AsRef<Path>>(&mut self, path: P) -> Option<MockEntry> {
+        let path = normalize(path.as_ref());
+        let removed = self.entries.remove(&path);
+        self.entries.retain(|p, _| ! p.starts_with(&path));
+        removed
+    }
+
+    /// 变更路径的权限，路径不存在时返回 false
+    pub fn set_mode<P: AsRef<Path>>(&mut self, path: P, mode: u16) -> bool {
+        match self.entries.get_mut(&normalize(path.as_ref())) {
+            Some(entry) => {
+                entry.mode = mode & modes::ALL_BITS;
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// 返回路径的信息，不检查对父级目录的权限
+    pub fn entry<P: AsRef<Path>>(&self, path: P) -> Option<&MockEntry> {
+        self.entries.get(&normalize(path.as_ref()))
+    }
+
+    pub fn path<P: AsRef<Path>>(&self, path: P) -> MockPath<'_> {
+        MockPath {
+            fs: self,
+            path: normalize(path.as_ref()),
+        }
+    }
+
+    fn insert(&mut self, path: &Path, entry: MockEntry) -> &mut MockFs {
+        self.entries.insert(normalize(path), entry);
+        self
+    }
+
+    fn allows(&self, entry: &MockEntry, amode: AccessMode) -> bool {
+        compute::stat_allows(&entry.stat(), self.uid, &self.gids, amode,
+                             Privilege::of_uid(self.uid))
+    }
+
+    // 与路径解析一致：每一级父级目录都需存在、为目录，且有执行（搜索）权限
+    fn resolve(&self, path: &Path) -> io::Result<&MockEntry> {
+        for ancestor in path.ancestors().skip(1).collect::<Vec<_>>().into_iter().rev() {
+            let entry = match self.entries.get(ancestor) {
+                Some(entry) => entry,
+                None => return Err(io::Error::from_raw_os_error(libc::ENOENT)),
+            };
+            if ! entry.is_dir {
+                return Err(io::Error::from_raw_os_error(libc::ENOTDIR))
+            }
+            if ! self.allows(entry, AccessMode::EXECUTE) {
+                return Err(io::Error::from_raw_os_error(libc::EACCES))
+            }
+        }
+        self.entries.get(path).ok_or_else(|| io::Error::from_raw_os_error(libc::ENOENT))
+    }
+
+    // 与 access(2) 一致：无查看此路径的权限时返回 Ok(false)，不存在等返回错误
+    fn access(&self, path: &Path, amode: AccessMode) -> io::Result<bool> {
+        match self.resolve(path) {
+            Ok(entry) => Ok(self.allows(entry, amode)),
+            Err(ref e) if e.raw_os_error() == Some(libc::EACCES) => Ok(false),
+            Err(e) => Err(e),
+        }
+    }
+}
+
+/// MockFs 中的路径，提供与 PathPermission 相同规则的常用检查
+#[derive(Clone, Debug)]
+pub struct MockPath<'a> {
+    fs: &'a MockFs,
+    path: PathBuf,
+}
+
+impl<'a> MockPath<'a> {
+    /// 与 Path::exists 一致，无权限查看时同样返回 false
+    pub fn exists(&self) -> bool {
+        self.fs.resolve(&self.path).is_ok()
+    }
+
+    pub fn access(&self, amode: AccessMode) -> io::Result<bool> {
+        self.fs.access(&self.path, amode)
+    }
+
+    pub fn is_readable(&self) -> io::Result<bool> {
+        self.access(AccessMode::READ)
+    }
+
+    pub fn is_writable(&self) -> io::Result<bool> {
+        self.access(AccessMode::WRITE)
+    }
+
+    /// root 需文件至少有一个执行位（目录除外）
+    pub fn is_excutable(&self) -> io::Result<bool> {
+        self.access(AccessMode::EXECUTE)
+    }
+
+    /// 判断路径能否被创建，规则同 PathPermission::is_creatable
+    ///
+    /// ```
+    /// # use path_permission::testing::MockFs;
+    /// let mut fs = MockFs::new();
+    /// fs.add_dir("/home", 0o755, 0, 0)
+    ///   .add_dir("/home/alice", 0o700, 1000, 1000)
+    ///   .add_file("/home/alice/file", 0o644, 1000, 1000)
+    ///   .run_as(1000, &[1000]);
+    ///
+    /// assert!(fs.path("/home/alice/new").is_creatable().unwrap());
+    /// // 不存在的父级目录，向上查找最近的已存在的目录
+    /// assert!(fs.path("/home/alice/a/b/c").is_creatable().unwrap());
+    /// // 父级路径不是目录
+    /// assert!(! fs.path("/home/alice/file/new").is_creatable().unwrap());
+    /// assert!(! fs.path("/home/new").is_creatable().unwrap());
+    /// // 根目录
+    /// assert!(! fs.path("/").is_creatable().unwrap());
+    ///
+    /// // 其他用户无法进入 /home/alice
+    /// fs.run_as(1001, &[1001]);
+    /// assert!(! fs.path("/home/alice/new").is_creatable().unwrap());
+    /// ```
+    pub fn is_creatable(&self) -> io::Result<bool> {
+        rules::is_creatable(self.fs, &self.path)
+    }
+
+    /// 判断路径能否被删除，规则同 PathPermission::is_removable
+    ///
+    /// ```
+    /// # use path_permission::testing::MockFs;
+    /// let mut fs = MockFs::new();
+    /// fs.add_dir("/tmp", 0o1777, 0, 0)
+    ///   .add_file("/tmp/alice", 0o600, 1000, 1000)
+    ///   .add_file("/tmp/bob", 0o666, 1001, 1001)
+    ///   .add_dir("/shared", 0o777, 0, 0)
+    ///   .add_file("/shared/bob", 0o600, 1001, 1001)
+    ///   .run_as(1000, &[1000]);
+    ///
+    /// assert!(fs.path("/tmp/alice").is_removable().unwrap());
+    /// // S_ISVTX：不是文件或目录的所有者
+    /// assert!(! fs.path("/tmp/bob").is_removable().unwrap());
+    /// // 没有 S_ISVTX 时，只需对目录有写和执行的权限
+    /// assert!(fs.path("/shared/bob").is_removable().unwrap());
+    /// // 不存在
+    /// assert!(! fs.path("/tmp/missing").is_removable().unwrap());
+    ///
+    /// // 无法进入父级目录时，视为不存在
+    /// fs.add_dir("/private", 0o700, 1001, 1001)
+    ///   .add_file("/private/file", 0o600, 1000, 1000);
+    /// assert!(! fs.path("/private/file").is_removable().unwrap());
+    ///
+    /// // root 拥有 CAP_FOWNER
+    /// fs.run_as(0, &[0]);
+    /// assert!(fs.path("/tmp/bob").is_removable().unwrap());
+    /// assert!(! fs.path("/").is_removable().unwrap());
+    /// ```
+    pub fn is_removable(&self) -> io::Result<bool> {
+        rules::is_removable(self.fs, &self.path, true)
+    }
+}
+
+// 不支持符号链接，跟随与否结果相同；以模拟的用户作为实际及有效的 uid
+impl MetadataSource for MockFs {
+    fn stat(&self, path: &Path, _follow: bool) -> io::Result<Stat> {
+        self.resolve(path).map(MockEntry::stat)
+    }
+
+    fn access(&self, path: &Path, amode: AccessMode) -> io::Result<bool> {
+        MockFs::access(self, path, amode)
+    }
+
+    fn effective_access(&self, path: &Path, amode: AccessMode) -> io::Result<bool> {
+        MockFs::access(self, path, amode)
+    }
+
+    fn euid(&self) -> u32 {
+        self.uid
+    }
+
+    fn privilege(&self) -> Privilege {
+        Privilege::of_uid(self.uid)
+    }
+
+    fn parent_dir(&self, path: &Path) -> io::Result<Option<PathBuf>> {
+        Ok(path.parent().map(Path::to_path_buf))
+    }
+}
+
+// 转换为以 "/" 开头、不含 "." 及 ".." 的路径
+fn normalize(path: &Path) -> PathBuf {
+    let mut normalized = PathBuf::from("/");
+    for component in path.components() {
+        match component {
+            Component::Normal(name) => normalized.push(name),
+            Component::ParentDir => {
+                normalized.pop();
+            }
+            _ => {}
+        }
+    }
+    normalized
+}
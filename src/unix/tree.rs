@@ -0,0 +1,473 @@
+//! 对目录树的批量操作
+use std::{
+    collections::HashMap,
+    io,
+    ops::ControlFlow,
+    path::{Path, PathBuf},
+    os::unix::fs::MetadataExt,
+};
+
+use crate::modes;
+
+use super::{
+    inspect::{Credentials, PermissionInspector},
+    stat::{self, Stat},
+    walk::{try_walk, walk},
+    PathPermission,
+};
+
+/// 递归变更权限的选项
+///
+/// 默认保留目录上已有的 S_ISUID、S_ISGID、S_ISVTX（0o7000），与 `chmod -R 755` 在
+/// 目录上的行为一致：如为组继承而设置的 S_ISGID 不会被清除。mode 中指明的特殊位总会被设置。
+///
+/// ```
+/// # use std::fs;
+/// # use path_permission::{ChmodOptions, Mode, PathPermission};
+/// # let dir = std::env::temp_dir().join(format!("pp-chmod-opts-{}", std::process::id()));
+/// # fs::create_dir_all(dir.join("sub")).unwrap();
+/// fs::write(dir.join("sub/file"), b"").unwrap();
+/// dir.set_mode(Mode::new(0o755)).unwrap();
+/// dir.join("sub").set_mode(Mode::new(0o2775)).unwrap();
+/// dir.join("sub/file").set_mode(Mode::new(0o2775)).unwrap();
+///
+/// // 目录保留 S_ISGID，普通文件不保留
+/// assert_eq!(dir.chmod_recursive(0o750).unwrap(), 3);
+/// assert_eq!(dir.join("sub").get_mode().unwrap(), 0o2750);
+/// assert_eq!(dir.join("sub/file").get_mode().unwrap(), 0o750);
+///
+/// // 明确清除
+/// let mut options = ChmodOptions::new();
+/// options.preserve_dir_special(false);
+/// assert_eq!(options.chmod_recursive(&dir, 0o750).unwrap(), 1);
+/// assert_eq!(dir.join("sub").get_mode().unwrap(), 0o750);
+/// # fs::remove_dir_all(&dir).unwrap();
+/// ```
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct ChmodOptions {
+    preserve_dir_special: bool,
+    preserve_executable: bool,
+    strip_bits: u16,
+}
+
+impl Default for ChmodOptions {
+    fn default() -> ChmodOptions {
+        ChmodOptions::new()
+    }
+}
+
+impl ChmodOptions {
+    pub fn new() -> ChmodOptions {
+        ChmodOptions {
+            preserve_dir_special: true,
+            preserve_executable: false,
+            strip_bits: modes::SETUID | modes::SETGID | modes::GROUP_W | modes::OTHER_W,
+        }
+    }
+
+    /// 为 false 时，目录的权限同样被精确地设置为 mode，清除其上未在 mode 中指明的特殊位
+    pub fn preserve_dir_special(&mut self, preserve: bool) -> &mut ChmodOptions {
+        self.preserve_dir_special = preserve;
+        self
+    }
+
+    /// 为 true 时，normalize_mode 及 normalize_tree 保留普通文件上已有的执行权限：
+    /// 已有任一执行位的文件（如脚本），在 file_mode 中可读的分类上加入执行位，即 0o644 变为 0o755
+    pub fn preserve_executable(&mut self, preserve: bool) -> &mut ChmodOptions {
+        self.preserve_executable = preserve;
+        self
+    }
+
+    /// sanitize 及 sanitize_tree 所清除的位，默认为 0o6022，即 S_ISUID、S_ISGID，以及所属组
+    /// 和其他用户的写权限；不受 preserve_dir_special 影响
+    pub fn strip_bits(&mut self, bits: u16) -> &mut ChmodOptions {
+        self.strip_bits = bits & modes::ALL_BITS;
+        self
+    }
+
+    /// 同 PathPermission::normalize_mode，按此选项变更权限
+    pub fn normalize_mode<P: AsRef<Path>>(&self, path: P, file_mode: u16, dir_mode: u16) -> io::Result<bool> {
+        chmod_one(path.as_ref(), true, |stat| self.normalized_mode(stat, file_mode, dir_mode))
+    }
+
+    /// 同 PathPermission::normalize_tree，按此选项变更权限
+    pub fn normalize_tree<P: AsRef<Path>>(&self, root: P, file_mode: u16, dir_mode: u16) -> io::Result<usize> {
+        chmod_each(root.as_ref(), |stat| self.normalized_mode(stat, file_mode, dir_mode))
+    }
+
+    /// 同 PathPermission::sanitize，清除 strip_bits 中的位
+    pub fn sanitize<P: AsRef<Path>>(&self, path: P) -> io::Result<bool> {
+        chmod_one(path.as_ref(), false, |stat| self.sanitized_mode(stat))
+    }
+
+    /// 同 PathPermission::sanitize_tree，清除 strip_bits 中的位
+    pub fn sanitize_tree<P: AsRef<Path>>(&self, root: P) -> io::Result<usize> {
+        chmod_each(root.as_ref(), |stat| self.sanitized_mode(stat))
+    }
+
+    /// 同 PathPermission::chmod_recursive，按此选项变更权限
+    pub fn chmod_recursive<P: AsRef<Path>>(&self, root: P, mode: u16) -> io::Result<usize> {
+        chmod_tree(root.as_ref(), mode, *self, |path, _, target| apply_chmod(path, target))
+    }
+
+    /// 同 PathPermission::chmod_recursive_dry_run，按此选项预览
+    pub fn chmod_recursive_dry_run<P: AsRef<Path>>(&self, root: P, mode: u16)
+        -> io::Result<Vec<(PathBuf, u16, u16)>> {
+        let mut changes = Vec::new();
+        chmod_tree(root.as_ref(), mode, *self, |path, current, target| {
+            changes.push((path.to_path_buf(), current, target));
+            Ok(())
+        })?;
+        Ok(changes)
+    }
+
+    // 按类型规范化后的权限，普通文件及目录以外的路径（包括符号链接）返回 None
+    fn normalized_mode(&self, stat: &Stat, file_mode: u16, dir_mode: u16) -> Option<u16> {
+        if stat.is_dir() {
+            Some(self.target_mode(stat, dir_mode))
+        } else if stat.is_file() {
+            let mut mode = file_mode & modes::ALL_BITS;
+            if self.preserve_executable && stat.perm() & modes::ALL_X != 0 {
+                mode |= (mode & modes::ALL_R) >> 2;
+            }
+            Some(mode)
+        } else {
+            None
+        }
+    }
+
+    // 清除 strip_bits 后的权限，符号链接返回 None
+    fn sanitized_mode(&self, stat: &Stat) -> Option<u16> {
+        (! stat.is_symlink()).then_some(stat.perm() & ! self.strip_bits)
+    }
+
+    // 路径将被变更为的权限
+    fn target_mode(&self, stat: &Stat, mode: u16) -> u16 {
+        let mode = mode & modes::ALL_BITS;
+        if self.preserve_dir_special && stat.is_dir() {
+            mode | (stat.perm() & modes::SPECIAL)
+        } else {
+            mode
+        }
+    }
+}
+
+// 将 root 及其下的所有路径的权限变更为 mode，跳过符号链接，默认保留目录上的特殊位
+// 遇到错误时继续处理其余路径，最后返回遇到的第一个错误；
+// 全部成功时返回实际变更了权限的路径数量
+pub(crate) fn chmod_recursive(root: &Path, mode: u16) -> io::Result<usize> {
+    ChmodOptions::new().chmod_recursive(root, mode)
+}
+
+// 与 chmod_recursive 相同，但只记录各路径当前的权限及将变更为的权限，不做修改
+pub(crate) fn chmod_recursive_dry_run(root: &Path, mode: u16)
+    -> io::Result<Vec<(PathBuf, u16, u16)>> {
+    ChmodOptions::new().chmod_recursive_dry_run(root, mode)
+}
+
+#[cfg(feature = "rayon")]
+pub(crate) fn par_chmod_recursive(root: &Path, mode: u16) -> io::Result<usize> {
+    let options = ChmodOptions::new();
+    let results = super::walk::walk_parallel(root, |path, stat, dir_err| {
+        chmod_entry(path, stat, dir_err, mode, options, |path, _, target| apply_chmod(path, target))
+    });
+
+    let mut changed = 0;
+    for result in results {
+        if result? {
+            changed += 1;
+        }
+    }
+    Ok(changed)
+}
+
+// 遍历 root，对权限需要变更的路径调用 apply（参数为路径、其当前的权限及将变更为的权限）
+fn chmod_tree<F>(root: &Path, mode: u16, options: ChmodOptions, mut apply: F) -> io::Result<usize>
+where
+    F: FnMut(&Path, u16, u16) -> io::Result<()>,
+{
+    let mut changed = 0;
+    let mut first_err = None;
+
+    walk(root, |path, stat, dir_err| {
+        match chmod_entry(path, stat, dir_err, mode, options, &mut apply) {
+            Ok(true) => changed += 1,
+            Ok(false) => {}
+            Err(err) => {
+                first_err.get_or_insert(err);
+            }
+        }
+    });
+
+    match first_err {
+        Some(err) => Err(err),
+        None => Ok(changed),
+    }
+}
+
+// 返回是否（需要）变更权限
+fn chmod_entry<F>(path: &Path, stat: io::Result<Stat>, dir_err: Option<io::Error>,
+                  mode: u16, options: ChmodOptions, apply: F) -> io::Result<bool>
+where
+    F: FnOnce(&Path, u16, u16) -> io::Result<()>,
+{
+    let stat = stat?;
+    // chmod 会跟随符号链接，修改的将是链接目标
+    if stat.is_symlink() {
+        return Ok(false)
+    }
+
+    let current = stat.perm();
+    let target = options.target_mode(&stat, mode);
+    let changed = if current != target {
+        apply(path, current, target)?;
+        true
+    } else {
+        false
+    };
+
+    match dir_err {
+        Some(err) => Err(err),
+        None => Ok(changed),
+    }
+}
+
+// 将路径的权限变更为 target 所返回的权限，target 返回 None 时不变更；返回是否变更了权限
+fn chmod_one<F>(path: &Path, follow: bool, target: F) -> io::Result<bool>
+where
+    F: Fn(&Stat) -> Option<u16>,
+{
+    let stat = stat::stat(path, follow)?;
+    match target(&stat) {
+        Some(target) if target != stat.perm() => {
+            apply_chmod(path, target)?;
+            Ok(true)
+        }
+        _ => Ok(false),
+    }
+}
+
+// 对 root 及其下的所有路径（不跟随符号链接）调用 chmod_one 的规则
+// 遇到错误时继续处理其余路径，最后返回遇到的第一个错误；全部成功时返回实际变更了权限的路径数量
+fn chmod_each<F>(root: &Path, target: F) -> io::Result<usize>
+where
+    F: Fn(&Stat) -> Option<u16>,
+{
+    let mut changed = 0;
+    let mut first_err = None;
+
+    walk(root, |path, stat, dir_err| {
+        let result = stat.and_then(|stat| {
+            match target(&stat) {
+                Some(mode) if mode != stat.perm() => {
+                    apply_chmod(path, mode)?;
+                    changed += 1;
+                }
+                _ => {}
+            }
+            Ok(())
+        });
+        if let Some(err) = result.err().or(dir_err) {
+            first_err.get_or_insert(err);
+        }
+    });
+
+    match first_err {
+        Some(err) => Err(err),
+        None => Ok(changed),
+    }
+}
+
+fn apply_chmod(path: &Path, mode: u16) -> io::Result<()> {
+    super::chmod(path, libc::mode_t::from(mode))
+}
+
+/// 将 src 下各路径的权限复制到 dst 下相同相对路径上，即递归的 `chmod --reference`
+/// 仅一侧存在的路径及符号链接会被跳过；遇到错误时继续处理其余路径，最后返回
+/// 遇到的第一个错误。全部成功时返回实际变更了权限的路径数量。
+///
+/// ```
+/// # use std::fs;
+/// # use path_permission::{clone_permissions_tree, Mode, PathPermission};
+/// # let root = std::env::temp_dir().join(format!("pp-clone-{}", std::process::id()));
+/// let (src, dst) = (root.join("src"), root.join("dst"));
+/// for dir in &[&src, &dst] {
+///     fs::create_dir_all(dir.join("sub")).unwrap();
+///     fs::write(dir.join("sub/file"), b"").unwrap();
+/// }
+/// fs::write(src.join("only-in-src"), b"").unwrap();
+/// src.join("sub").set_mode(Mode::new(0o750)).unwrap();
+/// src.join("sub/file").set_mode(Mode::new(0o600)).unwrap();
+/// dst.join("sub").set_mode(Mode::new(0o755)).unwrap();
+/// dst.join("sub/file").set_mode(Mode::new(0o644)).unwrap();
+/// dst.set_mode(Mode::new(src.get_mode().unwrap())).unwrap();
+///
+/// assert_eq!(clone_permissions_tree(&src, &dst).unwrap(), 2);
+/// assert_eq!(dst.join("sub").get_mode().unwrap(), 0o750);
+/// assert_eq!(dst.join("sub/file").get_mode().unwrap(), 0o600);
+/// assert!(! dst.join("only-in-src").exists());
+/// // 已一致，无需变更
+/// assert_eq!(clone_permissions_tree(&src, &dst).unwrap(), 0);
+/// # fs::remove_dir_all(&root).unwrap();
+/// ```
+pub fn clone_permissions_tree(src: &Path, dst: &Path) -> io::Result<usize> {
+    clone_tree(src, dst, false)
+}
+
+/// 与 clone_permissions_tree 相同，同时复制所有者及所属组
+/// 通常需要 root 权限（或 CAP_CHOWN）。
+pub fn clone_permissions_tree_with_owner(src: &Path, dst: &Path) -> io::Result<usize> {
+    clone_tree(src, dst, true)
+}
+
+fn clone_tree(src: &Path, dst: &Path, owner: bool) -> io::Result<usize> {
+    let mut changed = 0;
+    let mut first_err = None;
+
+    walk(src, |path, stat, dir_err| {
+        let result = stat.and_then(|stat| {
+            // walk 返回的路径都以 src 开头
+            let target = dst.join(path.strip_prefix(src).unwrap_or(path));
+            clone_entry(&stat, &target, owner)
+        });
+        match result {
+            Ok(true) => changed += 1,
+            Ok(false) => {}
+            Err(err) => {
+                first_err.get_or_insert(err);
+            }
+        }
+        if let Some(err) = dir_err {
+            first_err.get_or_insert(err);
+        }
+    });
+
+    match first_err {
+        Some(err) => Err(err),
+        None => Ok(changed),
+    }
+}
+
+// 返回是否变更了 target 的权限（或所有者）
+fn clone_entry(reference: &Stat, target: &Path, owner: bool) -> io::Result<bool> {
+    let current = match target.symlink_metadata() {
+        Ok(metadata) => metadata,
+        Err(ref e) if e.kind() == io::ErrorKind::NotFound => return Ok(false),
+        Err(e) => return Err(e),
+    };
+    // chmod 会跟随符号链接，修改的将是链接目标
+    if reference.is_symlink() || current.file_type().is_symlink() {
+        return Ok(false)
+    }
+
+    let mut changed = false;
+    // 先变更所有者，chown 会清除 setuid、setgid 位
+    if owner && (current.uid() != reference.uid || current.gid() != reference.gid) {
+        super::chown(target, reference.uid, reference.gid)?;
+        changed = true;
+    }
+    let mode = reference.perm();
+    if changed || (current.mode() & u32::from(modes::ALL_BITS)) as u16 != mode {
+        apply_chmod(target, mode)?;
+        changed = true;
+    }
+    Ok(changed)
+}
+
+/// chown_recursive 的结果
+#[derive(Debug, Default)]
+pub struct ChownReport {
+    /// 实际变更了所有者或所属组的路径数量
+    pub changed: usize,
+    /// 未能变更的路径及其错误（如 EPERM），仅在 keep_going 为 true 时记录
+    pub failed: Vec<(PathBuf, io::Error)>,
+}
+
+// 将 root 及其下的所有路径的所有者、所属组变更为 uid、gid，为 None 时保持不变
+// 符号链接使用 lchown，变更的是链接本身；其余路径使用 chown
+// keep_going 为 true 时记录失败的路径并继续，否则在遇到第一个错误时返回
+pub(crate) fn chown_recursive(root: &Path, uid: Option<u32>, gid: Option<u32>, keep_going: bool)
+    -> io::Result<ChownReport> {
+    let mut report = ChownReport::default();
+    let mut first_err = None;
+
+    try_walk(root, |path, stat, dir_err| {
+        let result = stat.and_then(|stat| {
+            let new_uid = uid.unwrap_or(stat.uid);
+            let new_gid = gid.unwrap_or(stat.gid);
+            if new_uid == stat.uid && new_gid == stat.gid {
+                return Ok(false)
+            }
+            if stat.is_symlink() {
+                super::lchown(path, new_uid, new_gid)?;
+            } else {
+                super::chown(path, new_uid, new_gid)?;
+            }
+            Ok(true)
+        });
+        let err = match result {
+            Ok(true) => {
+                report.changed += 1;
+                None
+            }
+            Ok(false) => None,
+            Err(err) => Some(err),
+        };
+        for err in err.into_iter().chain(dir_err) {
+            if keep_going {
+                report.failed.push((path.to_path_buf(), err));
+            } else {
+                first_err.get_or_insert(err);
+            }
+        }
+        // 不继续时，遇到第一个错误即停止遍历
+        if first_err.is_some() {
+            ControlFlow::Break(())
+        } else {
+            ControlFlow::Continue(())
+        }
+    });
+
+    match first_err {
+        Some(err) => Err(err),
+        None => Ok(report),
+    }
+}
+
+// 返回第一个阻碍递归删除 root 的路径，可以删除时返回 None
+// 每个目录都需要有读（列出）、写及执行的权限，每个路径都需满足从其父级目录中删除的条件
+// root 以外的路径基于遍历时获取的 stat 按权限位计算，不再重复获取
+pub(crate) fn tree_removal_blocker(root: &Path) -> io::Result<Option<PathBuf>> {
+    if ! root.is_removable()? {
+        return Ok(Some(root.to_path_buf()))
+    }
+
+    let credentials = Credentials::current()?;
+    // 已遍历的目录的 stat，先序遍历保证父级目录先于其下的路径
+    let mut dirs = HashMap::new();
+    let mut blocker = None;
+    try_walk(root, |path, stat, dir_err| {
+        let blocked = match (stat, dir_err) {
+            (Ok(stat), None) => {
+                let parent = path.parent().and_then(|parent| dirs.get(parent).cloned());
+                let inspector = PermissionInspector::from_parts(path, stat, parent,
+                                                                credentials.clone());
+                let removable = path == root || (parent.is_some()
+                    && inspector.is_removable().unwrap_or(false));
+                if inspector.is_dir() {
+                    dirs.insert(path.to_path_buf(), stat);
+                }
+                ! removable || (inspector.is_dir() && ! (inspector.is_readable_by_mode()
+                    && inspector.is_writable_by_mode() && inspector.is_executable_by_mode()))
+            }
+            _ => true,
+        };
+        if blocked {
+            blocker = Some(path.to_path_buf());
+            return ControlFlow::Break(())
+        }
+        ControlFlow::Continue(())
+    });
+
+    Ok(blocker)
+}
@@ -0,0 +1,93 @@
+//! 检查路径及其各级父级目录是否可信，同 sshd 的 StrictModes
+use std::{
+    fmt, fs, io,
+    path::{Path, PathBuf},
+};
+
+use crate::{modes, Mode};
+
+use super::{stat, users};
+
+/// 不可信的路径，见 PathPermission::ancestors_are_trusted
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct UntrustedPath {
+    pub path: PathBuf,
+    /// 权限，包括 S_ISUID、S_ISGID、S_ISVTX
+    pub mode: Mode,
+    pub uid: u32,
+    pub gid: u32,
+    /// 所属组或其他用户可写（且不受 S_ISVTX 保护）
+    pub writable_by_others: bool,
+    /// 所有者既不是 root，也不是当前进程的有效 uid
+    pub bad_owner: bool,
+}
+
+impl fmt::Display for UntrustedPath {
+    /// 如："/home/user: mode 0775, owner 1000 (group or world writable)"
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}: mode {}, owner {}", self.path.display(), self.mode, self.uid)?;
+        match (self.writable_by_others, self.bad_owner) {
+            (true, true) => f.write_str(" (group or world writable, bad owner)"),
+            (true, false) => f.write_str(" (group or world writable)"),
+            _ => f.write_str(" (bad owner)"),
+        }
+    }
+}
+
+/// PathPermission::ancestors_are_trusted 的结果
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct TrustReport {
+    untrusted: Vec<UntrustedPath>,
+}
+
+impl TrustReport {
+    /// 路径及其各级父级目录是否均可信
+    pub fn is_trusted(&self) -> bool {
+        self.untrusted.is_empty()
+    }
+
+    /// 不可信的路径，按路径本身至根目录的顺序
+    pub fn untrusted(&self) -> &[UntrustedPath] {
+        &self.untrusted
+    }
+}
+
+impl fmt::Display for TrustReport {
+    /// 每个不可信的路径一行，均可信时为空
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for (i, path) in self.untrusted.iter().enumerate() {
+            if i > 0 {
+                f.write_str("\n")?;
+            }
+            write!(f, "{}", path)?;
+        }
+        Ok(())
+    }
+}
+
+pub(crate) fn ancestors_are_trusted(path: &Path) -> io::Result<TrustReport> {
+    let path = fs::canonicalize(path)?;
+    let euid = unsafe { libc::geteuid() };
+    let home = users::home_dir(euid)?.and_then(|home| fs::canonicalize(home).ok());
+
+    let mut untrusted = Vec::new();
+    for ancestor in path.ancestors() {
+        let stat = stat::stat(ancestor, true)?;
+        let perm = stat.perm();
+        // 同 sshd，主目录不因 S_ISVTX 而例外：0755 可信，0775 不可信
+        let sticky = perm & modes::STICKY != 0 && home.as_deref() != Some(ancestor);
+        let writable_by_others = perm & (modes::GROUP_W | modes::OTHER_W) != 0 && ! sticky;
+        let bad_owner = stat.uid != 0 && stat.uid != euid;
+        if writable_by_others || bad_owner {
+            untrusted.push(UntrustedPath {
+                path: ancestor.to_path_buf(),
+                mode: Mode::new(perm),
+                uid: stat.uid,
+                gid: stat.gid,
+                writable_by_others,
+                bad_owner,
+            });
+        }
+    }
+    Ok(TrustReport { untrusted })
+}
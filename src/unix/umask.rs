@@ -0,0 +1,52 @@
+//! 进程的 umask
+use std::io;
+
+/// 获取当前进程的 umask
+///
+/// Linux 4.7 及以上版本从 /proc/self/status 中读取，不会修改 umask；
+/// 其它情况下，由于 umask(2) 只能在设置的同时返回旧值，需设置后再还原，
+/// 若其它线程恰在此期间创建文件，将受到影响，此时非线程安全。
+pub fn current_umask() -> u32 {
+    #[cfg(target_os = "linux")]
+    {
+        if let Ok(mask) = umask_from_proc() {
+            return mask
+        }
+    }
+    unsafe {
+        let mask = libc::umask(0);
+        libc::umask(mask);
+        mask as u32
+    }
+}
+
+/// 以 mask 作为 umask 执行 f，结束后还原
+/// 注意：umask 为进程级别的属性，期间会影响其它线程，非线程安全
+///
+/// ```
+/// # use path_permission::{current_umask, with_umask};
+/// let old = current_umask();
+/// with_umask(0o077, || assert_eq!(current_umask(), 0o077));
+/// assert_eq!(current_umask(), old);
+/// ```
+pub fn with_umask<T, F: FnOnce() -> T>(mask: u32, f: F) -> T {
+    let old = unsafe { libc::umask(mask as libc::mode_t) };
+    // 保证 f 发生 panic 时同样能够还原
+    struct Restore(libc::mode_t);
+    impl Drop for Restore {
+        fn drop(&mut self) {
+            unsafe { libc::umask(self.0) };
+        }
+    }
+    let _restore = Restore(old);
+    f()
+}
+
+#[cfg(target_os = "linux")]
+fn umask_from_proc() -> io::Result<u32> {
+    let status = std::fs::read_to_string("/proc/self/status")?;
+    status.lines()
+        .find(|line| line.starts_with("Umask:"))
+        .and_then(|line| u32::from_str_radix(line["Umask:".len()..].trim(), 8).ok())
+        .ok_or_else(|| io::Error::from(io::ErrorKind::NotFound))
+}
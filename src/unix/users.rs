@@ -0,0 +1,101 @@
+//! 用户、组的查询（getpwuid_r、getgrgid_r、getpwnam_r、getgrouplist）
+use std::{
+    ffi::{CStr, CString, OsStr},
+    io,
+    os::{raw::c_char, unix::ffi::OsStrExt},
+    path::PathBuf,
+};
+
+// 初始缓冲区大小，不足时（ERANGE）加倍重试
+const BUF_SIZE: usize = 1024;
+
+// 返回 uid 对应的用户名，不存在时返回 None
+pub(crate) fn user_name(uid: u32) -> io::Result<Option<String>> {
+    lookup(|pwd: &mut libc::passwd, buf, len, result| unsafe {
+        libc::getpwuid_r(uid, pwd, buf, len, result)
+    }, |pwd| c_string(pwd.pw_name))
+}
+
+// 返回 gid 对应的组名，不存在时返回 None
+pub(crate) fn group_name(gid: u32) -> io::Result<Option<String>> {
+    lookup(|grp: &mut libc::group, buf, len, result| unsafe {
+        libc::getgrgid_r(gid, grp, buf, len, result)
+    }, |grp| c_string(grp.gr_name))
+}
+
+// 返回 uid 对应用户的主目录（pw_dir），用户不存在时返回 None
+pub(crate) fn home_dir(uid: u32) -> io::Result<Option<PathBuf>> {
+    lookup(|pwd: &mut libc::passwd, buf, len, result| unsafe {
+        libc::getpwuid_r(uid, pwd, buf, len, result)
+    }, |pwd| {
+        let dir = unsafe { CStr::from_ptr(pwd.pw_dir) };
+        PathBuf::from(OsStr::from_bytes(dir.to_bytes()))
+    })
+}
+
+// 返回用户 name 的 uid 及其所属的全部组（主组及附加组）
+// 用户不存在时返回 NotFound 错误
+pub(crate) fn user_groups(name: &str) -> io::Result<(u32, Vec<u32>)> {
+    let c_name = CString::new(name)
+        .map_err(|_| io::Error::new(io::ErrorKind::InvalidInput, "user name contains a NUL byte"))?;
+    let account = lookup(|pwd: &mut libc::passwd, buf, len, result| unsafe {
+        libc::getpwnam_r(c_name.as_ptr(), pwd, buf, len, result)
+    }, |pwd| (pwd.pw_uid, pwd.pw_gid))?;
+    let (uid, gid) = account.ok_or_else(|| {
+        io::Error::new(io::ErrorKind::NotFound, format!("no such user: {}", name))
+    })?;
+    Ok((uid, group_list(&c_name, gid)))
+}
+
+// getgrouplist(3)，结果总是包含主组 gid
+// 数组不足时返回 -1：Linux 上 ngroups 被更新为所需的大小，其它系统上加倍重试
+fn group_list(name: &CStr, gid: u32) -> Vec<u32> {
+    let mut ngroups: libc::c_int = 32;
+    loop {
+        let mut groups = vec![0; ngroups as usize];
+        let mut count = ngroups;
+        #[cfg(target_os = "macos")]
+        let result = unsafe {
+            libc::getgrouplist(name.as_ptr(), gid as libc::c_int, groups.as_mut_ptr(), &mut count)
+        };
+        #[cfg(not(target_os = "macos"))]
+        let result = unsafe {
+            libc::getgrouplist(name.as_ptr(), gid, groups.as_mut_ptr(), &mut count)
+        };
+        if result >= 0 {
+            groups.truncate(count as usize);
+            // macOS 上为 c_int
+            #[allow(clippy::unnecessary_cast)]
+            return groups.into_iter().map(|gid| gid as u32).collect()
+        }
+        ngroups = if count > ngroups { count } else { ngroups * 2 };
+    }
+}
+
+// 以可重入的 get*_r 查询，call 的参数依次为：结果结构、缓冲区、其大小、结果指针
+// 找到时以 map 从结果结构中取出所需的字段
+fn lookup<T, R, F, M>(call: F, map: M) -> io::Result<Option<R>>
+where
+    F: Fn(&mut T, *mut c_char, usize, *mut *mut T) -> libc::c_int,
+    M: Fn(&T) -> R,
+{
+    let mut size = BUF_SIZE;
+    loop {
+        let mut entry = unsafe { std::mem::zeroed::<T>() };
+        let mut buf = vec![0 as c_char; size];
+        let mut result = std::ptr::null_mut();
+        match call(&mut entry, buf.as_mut_ptr(), size, &mut result) {
+            0 if result.is_null() => return Ok(None),
+            0 => return Ok(Some(map(&entry))),
+            libc::ERANGE => size *= 2,
+            // 部分系统以 ENOENT 等表示不存在
+            libc::ENOENT | libc::ESRCH | libc::EBADF | libc::EPERM => return Ok(None),
+            errno => return Err(io::Error::from_raw_os_error(errno)),
+        }
+    }
+}
+
+// 结果结构中的字符串指向缓冲区，需在其释放前复制
+fn c_string(ptr: *const c_char) -> String {
+    unsafe { CStr::from_ptr(ptr) }.to_string_lossy().into_owned()
+}
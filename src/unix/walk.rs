@@ -0,0 +1,166 @@
+//! 目录树的遍历
+//!
+//! 每个目录只打开一次，其下各路径的 stat 在读取目录时相对于目录的文件描述符获取
+//! （见 stat::stat_at），子路径出栈时不再重复获取。
+use std::{
+    ffi::{CStr, OsStr},
+    io,
+    ops::ControlFlow,
+    path::{Path, PathBuf},
+    os::unix::ffi::OsStrExt,
+};
+
+use super::stat::{self, Stat};
+
+// 先序遍历 root 及其下的所有路径，同一目录下按名称排序，不跟随符号链接
+// visit 的参数依次为：路径、lstat 的结果、读取目录时的错误
+// 遇到错误时交由 visit 记录，并继续遍历其余路径
+pub(crate) fn walk<F>(root: &Path, mut visit: F)
+where
+    F: FnMut(&Path, io::Result<Stat>, Option<io::Error>),
+{
+    try_walk(root, |path, stat, dir_err| {
+        visit(path, stat, dir_err);
+        ControlFlow::Continue(())
+    })
+}
+
+// 同 walk，但 visit 返回 ControlFlow::Break 时立即停止，不再打开其余的目录或获取 stat
+pub(crate) fn try_walk<F>(root: &Path, mut visit: F)
+where
+    F: FnMut(&Path, io::Result<Stat>, Option<io::Error>) -> ControlFlow<()>,
+{
+    let mut stack = vec![(root.to_path_buf(), stat::stat(root, false))];
+
+    while let Some((path, stat)) = stack.pop() {
+        let mut flow = ControlFlow::Continue(());
+        let children = visit_one(&path, stat, &mut |path, stat, dir_err| {
+            flow = visit(path, stat, dir_err)
+        });
+        if flow.is_break() {
+            return
+        }
+        // 逆序压栈，以保证按名称顺序出栈
+        stack.extend(children.into_iter().rev());
+    }
+}
+
+// 以 rayon 多线程遍历，各目录的读取及 lstat 相互独立，同一目录下的子路径由不同的线程处理
+// 返回 visit 的返回值，rayon 合并时保持原有的顺序，即与 walk 相同的先序
+#[cfg(feature = "rayon")]
+pub(crate) fn walk_parallel<T, F>(root: &Path, visit: F) -> Vec<T>
+where
+    T: Send,
+    F: Fn(&Path, io::Result<Stat>, Option<io::Error>) -> T + Sync,
+{
+    walk_subtree(root, stat::stat(root, false), &visit)
+}
+
+#[cfg(feature = "rayon")]
+fn walk_subtree<T, F>(path: &Path, stat: io::Result<Stat>, visit: &F) -> Vec<T>
+where
+    T: Send,
+    F: Fn(&Path, io::Result<Stat>, Option<io::Error>) -> T + Sync,
+{
+    use rayon::prelude::*;
+
+    let mut results = Vec::new();
+    let children = visit_one(path, stat, &mut |path, stat, err| results.push(visit(path, stat, err)));
+    results.par_extend(children.into_par_iter()
+        .flat_map_iter(|(path, stat)| walk_subtree(&path, stat, visit)));
+    results
+}
+
+// 访问单个路径，返回其为目录时的子路径及其 stat
+fn visit_one<F>(path: &Path, stat: io::Result<Stat>, visit: &mut F) -> Vec<(PathBuf, io::Result<Stat>)>
+where
+    F: FnMut(&Path, io::Result<Stat>, Option<io::Error>),
+{
+    let is_dir = match stat {
+        Ok(ref stat) => stat.is_dir(),
+        Err(_) => false,
+    };
+    if ! is_dir {
+        visit(path, stat, None);
+        return Vec::new()
+    }
+
+    match read_dir_sorted(path) {
+        Ok(children) => {
+            visit(path, stat, None);
+            children
+        }
+        Err(err) => {
+            visit(path, stat, Some(err));
+            Vec::new()
+        }
+    }
+}
+
+fn read_dir_sorted(dir: &Path) -> io::Result<Vec<(PathBuf, io::Result<Stat>)>> {
+    let mut children = Vec::new();
+    Dir::open(dir)?.for_each(|name, stat| {
+        children.push((dir.join(OsStr::from_bytes(name.to_bytes())), stat))
+    })?;
+    children.sort_by(|a, b| a.0.cmp(&b.0));
+    Ok(children)
+}
+
+// 已打开的目录，关闭时释放
+struct Dir(*mut libc::DIR);
+
+impl Dir {
+    // 以 O_NOFOLLOW 打开：遍历时已确认其不是符号链接，避免其间被替换
+    fn open(path: &Path) -> io::Result<Dir> {
+        let buf = super::c_path(path)?;
+        let flags = libc::O_RDONLY | libc::O_DIRECTORY | libc::O_NOFOLLOW | libc::O_CLOEXEC;
+        let fd = unsafe { libc::open(buf.as_ptr() as *const libc::c_char, flags) };
+        if fd < 0 {
+            return Err(io::Error::last_os_error())
+        }
+        let dirp = unsafe { libc::fdopendir(fd) };
+        if dirp.is_null() {
+            let err = io::Error::last_os_error();
+            unsafe { libc::close(fd) };
+            return Err(err)
+        }
+        Ok(Dir(dirp))
+    }
+
+    // 依次以目录下各条目（"." 及 ".." 除外）的名称及 lstat 的结果调用 f
+    fn for_each<F>(&mut self, mut f: F) -> io::Result<()>
+    where
+        F: FnMut(&CStr, io::Result<Stat>),
+    {
+        let fd = unsafe { libc::dirfd(self.0) };
+        loop {
+            // readdir 以返回 NULL 表示结束或出错，需通过 errno 区分
+            unsafe { *errno_location() = 0 };
+            let entry = unsafe { libc::readdir(self.0) };
+            if entry.is_null() {
+                return match unsafe { *errno_location() } {
+                    0 => Ok(()),
+                    errno => Err(io::Error::from_raw_os_error(errno)),
+                }
+            }
+            let name = unsafe { CStr::from_ptr((*entry).d_name.as_ptr()) };
+            if name.to_bytes() == b"." || name.to_bytes() == b".." {
+                continue
+            }
+            f(name, stat::stat_at(fd, name, false));
+        }
+    }
+}
+
+impl Drop for Dir {
+    fn drop(&mut self) {
+        unsafe { libc::closedir(self.0) };
+    }
+}
+
+fn errno_location() -> *mut libc::c_int {
+    #[cfg(any(target_os = "macos", target_os = "ios", target_os = "freebsd"))]
+    unsafe { libc::__error() }
+    #[cfg(not(any(target_os = "macos", target_os = "ios", target_os = "freebsd")))]
+    unsafe { libc::__errno_location() }
+}
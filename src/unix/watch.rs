@@ -0,0 +1,255 @@
+//! 基于 inotify(7) 监视权限及所有者的变化，仅 Linux
+use std::{
+    collections::{HashMap, VecDeque},
+    ffi::{CStr, OsStr},
+    io,
+    path::{Path, PathBuf},
+    os::unix::{ffi::OsStrExt, io::{AsRawFd, RawFd}},
+};
+
+use crate::Mode;
+
+use super::{
+    c_path,
+    poll::PermissionEvent,
+    stat::{self, Stat},
+    walk::walk,
+};
+
+/// PermissionWatcher 产生的事件
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum WatchEvent {
+    Changed(PermissionEvent),
+    /// inotify 的事件队列溢出（IN_Q_OVERFLOW），其间的变化已丢失，调用者应重新检查
+    Overflow,
+}
+
+/// 由 PathPermission::watch_permissions 创建，监视路径本身及（为目录时）其直接子路径
+///
+/// 作为迭代器时阻塞地等待事件；也可将 as_raw_fd 返回的描述符交给 poll(2) 等，可读时再
+/// 调用 next。只报告权限、所有者或所属组确有变化的路径（inotify 的 IN_ATTRIB 同样会
+/// 因时间戳等变化而触发），变化前的权限来自上一次看到的值。需启用 watch 特性；
+/// 其他系统或网络文件系统上可使用基于轮询的 PollWatcher。
+///
+/// ```
+/// # use std::fs;
+/// # use path_permission::{Mode, PathPermission, WatchEvent};
+/// # let dir = std::env::temp_dir().join(format!("pp-watch-{}", std::process::id()));
+/// # fs::create_dir_all(&dir).unwrap();
+/// let file = dir.join("file");
+/// fs::write(&file, b"").unwrap();
+/// file.set_mode(Mode::new(0o644)).unwrap();
+///
+/// let mut watcher = dir.watch_permissions().unwrap();
+/// file.set_mode(Mode::new(0o600)).unwrap();
+/// match watcher.next().unwrap().unwrap() {
+///     WatchEvent::Changed(event) => {
+///         assert_eq!(event.path, file);
+///         assert_eq!(event.old, Some(Mode::new(0o644)));
+///         assert_eq!(event.new, Mode::new(0o600));
+///     }
+///     WatchEvent::Overflow => unreachable!(),
+/// }
+///
+/// // 递归监视，之后新建的子目录同样被监视
+/// watcher.watch_recursive().unwrap();
+/// fs::create_dir(dir.join("sub")).unwrap();
+/// dir.join("sub").set_mode(Mode::new(0o700)).unwrap();
+/// assert!(matches!(watcher.next().unwrap().unwrap(),
+///                  WatchEvent::Changed(ref e) if e.path == dir.join("sub")));
+/// fs::write(dir.join("sub/file"), b"").unwrap();
+/// dir.join("sub/file").set_mode(Mode::new(0o400)).unwrap();
+/// assert!(matches!(watcher.next().unwrap().unwrap(),
+///                  WatchEvent::Changed(ref e) if e.path == dir.join("sub/file")
+///                                                && e.new == Mode::new(0o400)));
+/// # fs::remove_dir_all(&dir).unwrap();
+/// ```
+#[derive(Debug)]
+pub struct PermissionWatcher {
+    fd: RawFd,
+    root: PathBuf,
+    // watch descriptor 对应的目录（或文件）
+    watches: HashMap<libc::c_int, PathBuf>,
+    // 上一次看到的 stat
+    seen: HashMap<PathBuf, Stat>,
+    recursive: bool,
+    pending: VecDeque<WatchEvent>,
+}
+
+const WATCH_MASK: u32 = libc::IN_ATTRIB | libc::IN_CREATE | libc::IN_MOVED_TO
+    | libc::IN_DELETE_SELF | libc::IN_EXCL_UNLINK;
+
+impl PermissionWatcher {
+    pub(crate) fn new(path: &Path) -> io::Result<PermissionWatcher> {
+        let fd = unsafe { libc::inotify_init1(libc::IN_CLOEXEC) };
+        if fd < 0 {
+            return Err(io::Error::last_os_error())
+        }
+        let mut watcher = PermissionWatcher {
+            fd,
+            root: path.to_path_buf(),
+            watches: HashMap::new(),
+            seen: HashMap::new(),
+            recursive: false,
+            pending: VecDeque::new(),
+        };
+        watcher.add_watch(path)?;
+        Ok(watcher)
+    }
+
+    /// 同时监视所有子目录，之后新建（或移入）的子目录也会被监视
+    /// 子目录被加入监视之前，其中发生的变化无法被报告。
+    pub fn watch_recursive(&mut self) -> io::Result<()> {
+        self.recursive = true;
+        let root = self.root.clone();
+        self.add_tree(&root)
+    }
+
+    // 监视路径，并记录其（为目录时包括其直接子路径）当前的 stat
+    fn add_watch(&mut self, path: &Path) -> io::Result<()> {
+        let buf = c_path(path)?;
+        let wd = unsafe {
+            libc::inotify_add_watch(self.fd, buf.as_ptr() as *const libc::c_char, WATCH_MASK)
+        };
+        if wd < 0 {
+            return Err(io::Error::last_os_error())
+        }
+        self.watches.insert(wd, path.to_path_buf());
+
+        let stat = stat::stat(path, false)?;
+        self.seen.insert(path.to_path_buf(), stat);
+        if stat.is_dir() {
+            for entry in std::fs::read_dir(path)? {
+                let entry = entry?;
+                if let Ok(stat) = stat::stat(&entry.path(), false) {
+                    self.seen.insert(entry.path(), stat);
+                }
+            }
+        }
+        Ok(())
+    }
+
+    // 监视 root 下的所有目录，跳过已监视的及无法读取的目录
+    fn add_tree(&mut self, root: &Path) -> io::Result<()> {
+        let mut dirs = Vec::new();
+        walk(root, |path, stat, _| {
+            if matches!(stat, Ok(stat) if stat.is_dir()) {
+                dirs.push(path.to_path_buf());
+            }
+        });
+        for dir in dirs {
+            if self.watches.values().all(|watched| *watched != dir) {
+                self.add_watch(&dir)?;
+            }
+        }
+        Ok(())
+    }
+
+    // 阻塞地读取一批事件，转换后加入 pending
+    fn read_events(&mut self) -> io::Result<()> {
+        // 足以容纳多个带有最长文件名的事件
+        let mut buf = [0u8; 4096 + libc::PATH_MAX as usize];
+        let len = loop {
+            let len = unsafe { libc::read(self.fd, buf.as_mut_ptr() as *mut libc::c_void, buf.len()) };
+            if len >= 0 {
+                break len as usize
+            }
+            let err = io::Error::last_os_error();
+            if err.kind() != io::ErrorKind::Interrupted {
+                return Err(err)
+            }
+        };
+
+        let header = std::mem::size_of::<libc::inotify_event>();
+        let mut offset = 0;
+        while offset + header <= len {
+            let event = unsafe {
+                std::ptr::read_unaligned(buf[offset..].as_ptr() as *const libc::inotify_event)
+            };
+            let name_bytes = &buf[offset + header..offset + header + event.len as usize];
+            offset += header + event.len as usize;
+
+            if event.mask & libc::IN_Q_OVERFLOW != 0 {
+                self.pending.push_back(WatchEvent::Overflow);
+                continue
+            }
+            if event.mask & libc::IN_IGNORED != 0 {
+                if let Some(path) = self.watches.remove(&event.wd) {
+                    self.seen.remove(&path);
+                }
+                continue
+            }
+            let dir = match self.watches.get(&event.wd) {
+                Some(dir) => dir.clone(),
+                None => continue,
+            };
+            // 名称以 '\0' 结尾，并可能以多个 '\0' 填充
+            let path = match CStr::from_bytes_until_nul(name_bytes) {
+                Ok(name) if ! name.to_bytes().is_empty() => dir.join(OsStr::from_bytes(name.to_bytes())),
+                _ => dir,
+            };
+            self.handle(event.mask, path);
+        }
+        Ok(())
+    }
+
+    fn handle(&mut self, mask: u32, path: PathBuf) {
+        let stat = match stat::stat(&path, false) {
+            Ok(stat) => stat,
+            // 已被删除
+            Err(_) => return,
+        };
+        if mask & (libc::IN_CREATE | libc::IN_MOVED_TO) != 0 {
+            if self.recursive && stat.is_dir() {
+                // 无法监视时（如已被删除）忽略
+                let _ = self.add_tree(&path);
+            }
+            // 此时的 stat 可能已包含之后的变化（其 IN_ATTRIB 尚未读取），不作为变化前的值
+            self.seen.remove(&path);
+            return
+        }
+
+        let old = self.seen.insert(path.clone(), stat);
+        if old.is_some_and(|old| old.perm() == stat.perm() && old.uid == stat.uid
+                                 && old.gid == stat.gid) {
+            return
+        }
+        self.pending.push_back(WatchEvent::Changed(PermissionEvent {
+            path,
+            old: old.map(|old| Mode::new(old.perm())),
+            new: Mode::new(stat.perm()),
+            uid: stat.uid,
+            gid: stat.gid,
+        }));
+    }
+}
+
+impl Iterator for PermissionWatcher {
+    type Item = io::Result<WatchEvent>;
+
+    fn next(&mut self) -> Option<io::Result<WatchEvent>> {
+        loop {
+            if let Some(event) = self.pending.pop_front() {
+                return Some(Ok(event))
+            }
+            if self.watches.is_empty() {
+                return None
+            }
+            if let Err(err) = self.read_events() {
+                return Some(Err(err))
+            }
+        }
+    }
+}
+
+impl AsRawFd for PermissionWatcher {
+    fn as_raw_fd(&self) -> RawFd {
+        self.fd
+    }
+}
+
+impl Drop for PermissionWatcher {
+    fn drop(&mut self) {
+        unsafe { libc::close(self.fd) };
+    }
+}
@@ -0,0 +1,115 @@
+//! 在 PATH 中查找可执行文件，与 which 类似
+use std::{
+    env,
+    ffi::{OsStr, OsString},
+    io,
+    path::{Path, PathBuf},
+    os::unix::ffi::OsStrExt,
+};
+
+use super::PathPermission;
+
+/// 在 PATH 环境变量的目录中查找名为 name 的可执行文件，返回第一个匹配的路径
+/// 只匹配可执行（is_excutable）的普通文件，会跟随符号链接。name 含有 '/' 时，
+/// 不查找 PATH，直接检查此路径。PATH 中的空项被跳过，需将其视为当前目录时，
+/// 请使用 find_executable_in。
+pub fn find_executable(name: &OsStr) -> io::Result<Option<PathBuf>> {
+    find_executable_in(name, &path_var(), false)
+}
+
+/// 与 find_executable 相同，但返回 PATH 中所有匹配的路径，按 PATH 中的顺序
+pub fn find_all_executables(name: &OsStr) -> io::Result<Vec<PathBuf>> {
+    find_all_executables_in(name, &path_var(), false)
+}
+
+/// 在 search_path（以 ':' 分隔的目录）中查找，empty_as_cwd 为 true 时，
+/// 空项视为当前目录（POSIX 中的旧式写法）
+///
+/// ```
+/// # use std::{ffi::{OsStr, OsString}, fs};
+/// # use path_permission::{find_all_executables_in, find_executable_in, Mode, PathPermission};
+/// # let root = std::env::temp_dir().join(format!("pp-which-{}", std::process::id()));
+/// let (first, second) = (root.join("first"), root.join("second"));
+/// fs::create_dir_all(&first).unwrap();
+/// fs::create_dir_all(&second).unwrap();
+/// fs::write(first.join("tool"), b"").unwrap();
+/// first.join("tool").set_mode(Mode::new(0o644)).unwrap();
+/// fs::write(second.join("tool"), b"").unwrap();
+/// second.join("tool").set_mode(Mode::new(0o755)).unwrap();
+///
+/// let mut search_path = OsString::from(&first);
+/// search_path.push("::");
+/// search_path.push(&second);
+/// let name = OsStr::new("tool");
+/// assert_eq!(find_executable_in(name, &search_path, false).unwrap(), Some(second.join("tool")));
+/// assert_eq!(find_all_executables_in(name, &search_path, false).unwrap(), vec![second.join("tool")]);
+/// assert_eq!(find_executable_in(OsStr::new("missing"), &search_path, false).unwrap(), None);
+///
+/// // 含有 '/' 时直接检查
+/// let direct = second.join("tool");
+/// assert_eq!(find_executable_in(direct.as_os_str(), OsStr::new(""), false).unwrap(), Some(direct));
+/// # fs::remove_dir_all(&root).unwrap();
+/// ```
+pub fn find_executable_in(name: &OsStr, search_path: &OsStr, empty_as_cwd: bool)
+    -> io::Result<Option<PathBuf>> {
+    Ok(search(name, search_path, empty_as_cwd, true)?.into_iter().next())
+}
+
+/// 与 find_executable_in 相同，但返回所有匹配的路径
+pub fn find_all_executables_in(name: &OsStr, search_path: &OsStr, empty_as_cwd: bool)
+    -> io::Result<Vec<PathBuf>> {
+    search(name, search_path, empty_as_cwd, false)
+}
+
+fn path_var() -> OsString {
+    env::var_os("PATH").unwrap_or_default()
+}
+
+fn search(name: &OsStr, search_path: &OsStr, empty_as_cwd: bool, first_only: bool)
+    -> io::Result<Vec<PathBuf>> {
+    let mut found = Vec::new();
+    if name.is_empty() {
+        return Ok(found)
+    }
+
+    if name.as_bytes().contains(&b'/') {
+        let candidate = Path::new(name);
+        if is_executable_candidate(candidate)? {
+            found.push(candidate.to_path_buf());
+        }
+        return Ok(found)
+    }
+
+    for dir in search_path.as_bytes().split(|&b| b == b':') {
+        let dir = if dir.is_empty() {
+            if ! empty_as_cwd {
+                continue
+            }
+            Path::new(".")
+        } else {
+            Path::new(OsStr::from_bytes(dir))
+        };
+
+        let candidate = dir.join(name);
+        if is_executable_candidate(&candidate)? {
+            found.push(candidate);
+            if first_only {
+                break
+            }
+        }
+    }
+    Ok(found)
+}
+
+// 可执行的普通文件，路径不存在或无权限访问时返回 false
+fn is_executable_candidate(path: &Path) -> io::Result<bool> {
+    match path.metadata() {
+        Ok(metadata) if metadata.is_file() => {}
+        _ => return Ok(false),
+    }
+    match path.is_excutable() {
+        Ok(executable) => Ok(executable),
+        Err(ref e) if e.kind() == io::ErrorKind::NotFound => Ok(false),
+        Err(e) => Err(e),
+    }
+}
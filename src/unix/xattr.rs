@@ -0,0 +1,79 @@
+//! Linux 扩展属性（xattr）的读写
+use std::{
+    io,
+    path::Path,
+};
+
+use super::c_path;
+
+/// 读取扩展属性，属性不存在或文件系统不支持时返回 None
+pub(crate) fn get(path: &Path, name: &str) -> io::Result<Option<Vec<u8>>> {
+    let buf = c_path(path)?;
+    let path_ptr = buf.as_ptr() as *const libc::c_char;
+    let name = c_name(name);
+    let name_ptr = name.as_ptr() as *const libc::c_char;
+
+    loop {
+        // 先获取属性值的长度
+        let size = unsafe {
+            libc::getxattr(path_ptr, name_ptr, std::ptr::null_mut(), 0)
+        };
+        if size < 0 {
+            return not_found_as_none(io::Error::last_os_error());
+        }
+
+        let mut value = vec![0u8; size as usize];
+        let size = unsafe {
+            libc::getxattr(path_ptr, name_ptr,
+                           value.as_mut_ptr() as *mut libc::c_void, value.len())
+        };
+        if size < 0 {
+            let err = io::Error::last_os_error();
+            // 两次调用之间属性值变长了，重新获取
+            if err.raw_os_error() == Some(libc::ERANGE) {
+                continue
+            }
+            return not_found_as_none(err);
+        }
+        value.truncate(size as usize);
+        return Ok(Some(value))
+    }
+}
+
+/// 写入扩展属性，无权限时返回 Ok(false)
+#[allow(dead_code)]
+pub(crate) fn set(path: &Path, name: &str, value: &[u8]) -> io::Result<bool> {
+    let buf = c_path(path)?;
+    let path_ptr = buf.as_ptr() as *const libc::c_char;
+    let name = c_name(name);
+    let name_ptr = name.as_ptr() as *const libc::c_char;
+
+    let result = unsafe {
+        libc::setxattr(path_ptr, name_ptr,
+                       value.as_ptr() as *const libc::c_void, value.len(), 0)
+    };
+
+    match result {
+        0 => Ok(true),
+        _ => {
+            let err = io::Error::last_os_error();
+            match err.raw_os_error() {
+                Some(libc::EPERM) | Some(libc::EACCES) => Ok(false),
+                _ => Err(err),
+            }
+        }
+    }
+}
+
+fn c_name(name: &str) -> Vec<u8> {
+    let mut buf = Vec::from(name.as_bytes());
+    buf.push(0);
+    buf
+}
+
+fn not_found_as_none(err: io::Error) -> io::Result<Option<Vec<u8>>> {
+    match err.raw_os_error() {
+        Some(libc::ENODATA) | Some(libc::ENOTSUP) => Ok(None),
+        _ => Err(err),
+    }
+}
@@ -0,0 +1,134 @@
+//! WASI 上的有限支持
+//!
+//! WASI 只提供 path_filestat_get，其中没有 Unix 的权限位，也没有 chmod、
+//! access 等调用。此处的权限由文件类型及只读标志推算而来：
+//!   * 只读时为 0o444，否则为 0o666；目录另加 0o111。
+//!
+//! 可用的方法：get_mode、check_access、get_access、is_readonly。
+//! 以下方法总是返回 io::ErrorKind::Unsupported 错误：
+//! access、is_readable、is_writable、is_excutable、chmod。
+use std::{
+    io,
+    path::{Path, PathBuf},
+};
+
+use crate::modes;
+
+pub trait PathPermission {
+    /// 不支持，返回 Unsupported 错误
+    fn access(&self, amode: i32) -> io::Result<bool>;
+
+    /// 不支持，返回 Unsupported 错误
+    fn is_readable(&self) -> io::Result<bool>;
+
+    /// 不支持，返回 Unsupported 错误
+    fn is_writable(&self) -> io::Result<bool>;
+
+    /// 不支持，返回 Unsupported 错误
+    fn is_excutable(&self) -> io::Result<bool>;
+
+    /// 判断路径是否为只读
+    fn is_readonly(&self) -> io::Result<bool>;
+
+    /// 检查文件的权限，权限由文件类型及只读标志推算而来
+    fn check_access(&self, mode: u16) -> io::Result<bool>;
+
+    /// 返回路径的权限，以 stat 的形式：0o0644
+    /// 注意：已经格式化为字符串！
+    fn get_access(&self) -> io::Result<String>;
+
+    /// 返回路径的权限，由文件类型及只读标志推算而来
+    fn get_mode(&self) -> io::Result<u16>;
+
+    /// 不支持，返回 Unsupported 错误
+    fn chmod(&self, mode: u16) -> io::Result<bool>;
+}
+
+impl PathPermission for Path {
+    fn access(&self, _amode: i32) -> io::Result<bool> {
+        Err(unsupported("access"))
+    }
+
+    fn is_readable(&self) -> io::Result<bool> {
+        Err(unsupported("access"))
+    }
+
+    fn is_writable(&self) -> io::Result<bool> {
+        Err(unsupported("access"))
+    }
+
+    fn is_excutable(&self) -> io::Result<bool> {
+        Err(unsupported("access"))
+    }
+
+    fn is_readonly(&self) -> io::Result<bool> {
+        Ok(self.metadata()?.permissions().readonly())
+    }
+
+    fn check_access(&self, mode: u16) -> io::Result<bool> {
+        Ok(self.get_mode()? & mode == mode)
+    }
+
+    fn get_access(&self) -> io::Result<String> {
+        Ok(format!("{:04o}", self.get_mode()?))
+    }
+
+    fn get_mode(&self) -> io::Result<u16> {
+        let metadata = self.metadata()?;
+        let mut mode = if metadata.permissions().readonly() {
+            modes::ALL_R
+        } else {
+            modes::ALL_R | modes::ALL_W
+        };
+        if metadata.is_dir() {
+            mode |= modes::ALL_X;
+        }
+        Ok(mode)
+    }
+
+    fn chmod(&self, _mode: u16) -> io::Result<bool> {
+        Err(unsupported("chmod"))
+    }
+}
+
+impl PathPermission for PathBuf {
+    fn access(&self, amode: i32) -> io::Result<bool> {
+        self.as_path().access(amode)
+    }
+
+    fn is_readable(&self) -> io::Result<bool> {
+        self.as_path().is_readable()
+    }
+
+    fn is_writable(&self) -> io::Result<bool> {
+        self.as_path().is_writable()
+    }
+
+    fn is_excutable(&self) -> io::Result<bool> {
+        self.as_path().is_excutable()
+    }
+
+    fn is_readonly(&self) -> io::Result<bool> {
+        self.as_path().is_readonly()
+    }
+
+    fn check_access(&self, mode: u16) -> io::Result<bool> {
+        self.as_path().check_access(mode)
+    }
+
+    fn get_access(&self) -> io::Result<String> {
+        self.as_path().get_access()
+    }
+
+    fn get_mode(&self) -> io::Result<u16> {
+        self.as_path().get_mode()
+    }
+
+    fn chmod(&self, mode: u16) -> io::Result<bool> {
+        self.as_path().chmod(mode)
+    }
+}
+
+fn unsupported(call: &str) -> io::Error {
+    io::Error::new(io::ErrorKind::Unsupported, format!("{} is not available on WASI", call))
+}